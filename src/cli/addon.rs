@@ -1,17 +1,26 @@
-use std::{path::Path, fs::{write, create_dir_all}};
+use std::{collections::HashMap, fs::{create_dir_all, read_to_string, write}, path::{Path, PathBuf}};
 use clap::Subcommand;
 use inquire::{MultiSelect, validator::Validation, list_option::ListOption};
-use paris::{success, error, info};
 use itertools::Itertools;
+use regex::Regex;
 
+use crate::cli::vmf::content_collector::{self as vmf_collector, SummaryCategory};
+use crate::library::collector::{self, SourceContentFile, SourceMaterialData};
 use crate::templates;
 use crate::library;
+use crate::library::log::{error, info, success, warn};
 
 #[derive(Subcommand)]
 pub enum Actions {
 	Init {
 		#[arg(value_parser = validate_target_directory)]
 		target_directory: String
+	},
+	CheckContent {
+		#[arg(long, value_name = "GAME_DIR", help = "Path to the Garry's Mod install directory (which contains `garrysmod/`), if it can not be found via Steam.")]
+		game_dir: Option<PathBuf>,
+		#[arg(long, visible_alias = "offline", conflicts_with = "game_dir", help = "Check without the game files. Referenced content which is part of the game can not be distinguished and is reported as missing.")]
+		skip_game_check: bool,
 	}
 }
 
@@ -84,4 +93,203 @@ pub fn init(target_directory: String) {
 
 	success!("Successfully created addon <magenta>{input_pretty_name}</>!");
 
-}
\ No newline at end of file
+}
+
+#[derive(Clone, Copy)]
+enum LuaReferenceKind {
+	Material,
+	Model,
+	Sound,
+	Scene,
+	File,
+}
+
+// Checks whether the content referenced by the Lua files of the addon in the current directory is part of the addon (or the game).
+// Returns whether the addon is self-contained, or None if the check failed.
+pub fn check_content(game_dir: Option<PathBuf>, skip_game_check: bool) -> Option<bool> {
+
+	// Check for addon.json
+	if !Path::new("./addon.json").is_file() {
+		error!("Failed to find addon.json! Are you inside an addon directory?");
+		return None;
+	}
+
+	//
+	// Locate game install
+	//
+	let game_dir = match collector::locate_game(skip_game_check, game_dir.as_ref()) {
+		Ok(game_dir) => game_dir,
+		Err(err) => {
+			error!("{}", err.to_string());
+			return None;
+		}
+	};
+
+	let game_fs_open = collector::open_file_system(game_dir.as_ref())?;
+
+	// Content missing in the addon is only looked up in the game files if there is a game
+	let game_fs_lookup = game_dir.as_ref().map(|_| &game_fs_open);
+
+	//
+	// Create a hashmap with all files of the addon
+	//
+	let source_files = vmf_collector::build_source_files_map(&vec!(PathBuf::from(".")), None, false, None);
+
+	info!("Found <cyan>{}</> files in the addon", source_files.len());
+
+	//
+	// Collect the content referenced by the Lua files
+	//
+	let reference_regexes: Vec<(LuaReferenceKind, Regex)> = vec![
+		// Models are referenced via Model(...) / util.PrecacheModel(...), but also assigned directly (e.g. SWEP.ViewModel), so every string literal ending in .mdl is a reference.
+		// The same applies to sounds (e.g. sound.Add, surface.PlaySound or EmitSound) and scenes (e.g. PrecacheScene).
+		(LuaReferenceKind::Model, Regex::new(r#"(?i)["']([^"'\r\n]+\.mdl)["']"#).unwrap()),
+		(LuaReferenceKind::Sound, Regex::new(r#"(?i)["']([^"'\r\n]+\.(?:wav|mp3|ogg))["']"#).unwrap()),
+		(LuaReferenceKind::Scene, Regex::new(r#"(?i)["']([^"'\r\n]+\.vcd)["']"#).unwrap()),
+		(LuaReferenceKind::File, Regex::new(r#"\bresource\.Add(?:Single)?File\(\s*["']([^"']+)["']"#).unwrap()),
+	];
+
+	let mut used_materials: HashMap<String, SourceContentFile> = HashMap::new();
+	let mut missing_materials: HashMap<String, String> = HashMap::new();
+	let mut used_models: HashMap<String, SourceContentFile> = HashMap::new();
+	let mut missing_models: HashMap<String, String> = HashMap::new();
+	let mut used_sounds: HashMap<String, SourceContentFile> = HashMap::new();
+	let mut missing_sounds: HashMap<String, String> = HashMap::new();
+	let mut used_scenes: HashMap<String, SourceContentFile> = HashMap::new();
+	let mut missing_scenes: HashMap<String, String> = HashMap::new();
+	let mut used_resource_files: HashMap<String, SourceContentFile> = HashMap::new();
+	let mut missing_resource_files: HashMap<String, String> = HashMap::new();
+
+	let lua_files: Vec<(&String, &SourceContentFile)> = source_files
+		.iter()
+		.filter(|(source_file_path, _)| source_file_path.starts_with("lua\\") && source_file_path.ends_with(".lua"))
+		.sorted_by_key(|(source_file_path, _)| source_file_path.to_owned())
+		.collect();
+
+	info!("Collecting content referenced by <cyan>{}</> Lua files...", lua_files.len());
+
+	for (_, lua_file) in lua_files {
+
+		let lua_content = match read_to_string(&lua_file.full_path) {
+			Ok(content) => content,
+			Err(err) => {
+				warn!("Failed to read Lua file \"{}\": {}", lua_file.full_path, err.to_string());
+				continue;
+			}
+		};
+
+		// Materials are found like by vmf collect-content --scan-lua
+		let material_references = vmf_collector::resolve::read_lua_material_references(&lua_content)
+			.into_iter()
+			.map(|material| (&LuaReferenceKind::Material, material));
+		let other_references = reference_regexes
+			.iter()
+			.flat_map(|(kind, regex)| regex.captures_iter(&lua_content).map(move |captures| (kind, captures[1].to_owned())));
+
+		for (kind, reference) in material_references.chain(other_references) {
+
+			let reference = reference.trim().replace("/", "\\").to_lowercase();
+
+			let (reference_source_path, used_files, missing_files) = match kind {
+				LuaReferenceKind::Material => (vmf_collector::make_material_path(&reference), &mut used_materials, &mut missing_materials),
+				LuaReferenceKind::Model => match vmf_collector::make_model_path(&reference) {
+					Some(model_source_path) => (model_source_path, &mut used_models, &mut missing_models),
+					None => continue,
+				},
+				LuaReferenceKind::Sound => (vmf_collector::make_sound_path(reference.trim_start_matches("sound\\")), &mut used_sounds, &mut missing_sounds),
+				LuaReferenceKind::Scene => (vmf_collector::make_scene_path(&reference), &mut used_scenes, &mut missing_scenes),
+				LuaReferenceKind::File => (reference.trim_start_matches('\\').to_owned(), &mut used_resource_files, &mut missing_resource_files),
+			};
+
+			match source_files.get(&reference_source_path) {
+				Some(source_file) => {
+					used_files.insert(reference_source_path, source_file.to_owned());
+				},
+				None => {
+					missing_files.insert(reference_source_path, format!("Referenced in Lua file \"{}\"", lua_file.local_path));
+				}
+			}
+
+		}
+
+	}
+
+	//
+	// Collect materials used by the referenced models and textures used by all materials
+	//
+	info!("Collecting materials used by <cyan>{}</> models...", used_models.len());
+
+	let (models_used_materials, models_missing_materials) = vmf_collector::collect_models_materials(&used_models, &source_files, &game_fs_open, false, 0);
+	used_materials.extend(models_used_materials);
+	missing_materials.extend(models_missing_materials);
+
+	info!("Collecting textures used by <cyan>{}</> materials...", used_materials.len());
+
+	// Not strict, so materials which fail to be read are skipped with a warning instead of returning an error
+	let mut used_materials_data = collector::collect_materials_data(&mut used_materials, false, |source_file| vmf_collector::read_material_data(&source_file.full_path, &source_files, &game_fs_open))
+		.unwrap_or_else(|_| SourceMaterialData::new());
+
+	// Add materials which are referenced by materials, but missing in the addon (found ones were added while reading)
+	missing_materials.extend(used_materials_data.missing_materials);
+
+	//
+	// Find content included in the game and report the content which is missing in the addon
+	//
+	let missing_categories: [(&str, &mut HashMap<String, String>); 6] = [
+		("materials", &mut missing_materials),
+		("textures", &mut used_materials_data.missing_textures),
+		("models", &mut missing_models),
+		("sounds", &mut missing_sounds),
+		("scenes", &mut missing_scenes),
+		("resource files", &mut missing_resource_files),
+	];
+
+	for (name, missing_files) in missing_categories {
+
+		vmf_collector::filter::remove_game_content_logged(name, missing_files, game_fs_lookup, 0, false);
+
+		if missing_files.len() > 0 {
+			vmf_collector::report::log_missing_files_hashmap(name, missing_files);
+		} else {
+			success!("<green>No {} missing in the addon!</>", name);
+		}
+
+	}
+
+	//
+	// Content summary
+	//
+	let summary_categories = vec![
+		SummaryCategory { name: "Materials", found: used_materials.len(), missing: missing_materials.len(), size: vmf_collector::report::files_size(&used_materials, None) },
+		SummaryCategory { name: "Textures", found: used_materials_data.used_textures.len(), missing: used_materials_data.missing_textures.len(), size: vmf_collector::report::files_size(&used_materials_data.used_textures, None) },
+		SummaryCategory { name: "Models", found: used_models.len(), missing: missing_models.len(), size: vmf_collector::report::files_size(&used_models, None) },
+		SummaryCategory { name: "Sounds", found: used_sounds.len(), missing: missing_sounds.len(), size: vmf_collector::report::files_size(&used_sounds, None) },
+		SummaryCategory { name: "Scenes", found: used_scenes.len(), missing: missing_scenes.len(), size: vmf_collector::report::files_size(&used_scenes, None) },
+		SummaryCategory { name: "Resource files", found: used_resource_files.len(), missing: missing_resource_files.len(), size: vmf_collector::report::files_size(&used_resource_files, None) },
+	];
+
+	vmf_collector::report::print_content_summary(source_files.len(), &summary_categories, false);
+
+	let missing_content: Vec<(&str, &HashMap<String, String>)> = vec![
+		("materials", &missing_materials),
+		("textures", &used_materials_data.missing_textures),
+		("models", &missing_models),
+		("sounds", &missing_sounds),
+		("scenes", &missing_scenes),
+		("resource files", &missing_resource_files),
+	];
+	vmf_collector::report::record_content(Path::new("."), source_files.len(), &summary_categories, &missing_content);
+
+	if game_fs_lookup.is_none() {
+		warn!("The game check was skipped, so the missing content includes content which is part of the game");
+	}
+
+	let self_contained = summary_categories.iter().all(|category| category.missing == 0);
+	match self_contained {
+		true => success!("<green>The addon is self-contained, all content referenced by its Lua files is part of the addon or the game!</>"),
+		false => warn!("The addon is not self-contained, <red>{}</> files referenced by its Lua files are missing", summary_categories.iter().map(|category| category.missing).sum::<usize>()),
+	}
+
+	return Some(self_contained);
+
+}
@@ -1,6 +1,8 @@
-use std::{path::Path, fs::{create_dir_all, write}, vec};
+use std::{collections::{HashMap, HashSet}, path::{Component, Path, PathBuf}, fs::{create_dir_all, read_to_string, remove_dir_all, remove_file, rename as rename_path, write}, vec};
 use clap::Subcommand;
-use paris::{success, error, info};
+use crate::library::log::{success, error, info, warn};
+use regex::Regex;
+use walkdir::WalkDir;
 use crate::{library, templates};
 
 #[derive(Subcommand)]
@@ -8,6 +10,18 @@ pub enum Actions {
 	Create {
 		#[arg(value_parser = validate_directory_name)]
 		directory_name: String
+	},
+	LuaDeps {
+		#[arg(value_parser = validate_directory_name)]
+		directory_name: String
+	},
+	Rename {
+		#[arg(value_parser = validate_directory_name)]
+		old_directory_name: String,
+		#[arg(value_parser = validate_directory_name)]
+		new_directory_name: String,
+		#[arg(long, help = "New pretty name (ENT.PrintName) of the entity. If not provided, you are asked whether it should be updated, unless `--assume-yes` is set, which keeps it.")]
+		print_name: Option<String>,
 	}
 }
 
@@ -133,4 +147,306 @@ pub fn create(directory_name: String) {
 
 	success!("Created entity <magenta>{}</>!", &input_pretty_name);
 
-}
\ No newline at end of file
+	// Spawn menu icon
+	if input_spawnable {
+
+		let icon_directory = "./materials/entities";
+		let icon_path = format!("{}/{}.png", icon_directory, &directory_name);
+
+		let input_icon = library::inquire::confirm_no("Should the directory for the entity's spawn menu icon be created?");
+		if input_icon {
+			let create_icon_dir_res = create_dir_all(icon_directory);
+			if create_icon_dir_res.is_err() {
+				error!("Failed to create spawn menu icon directory: {}", create_icon_dir_res.unwrap_err().to_string());
+				return;
+			}
+		}
+
+		info!("Place the spawn menu icon (128x128 PNG) at <cyan>{}</>", icon_path);
+
+	}
+
+}
+
+pub fn lua_deps(directory_name: String) {
+
+	// Check for addon.json
+	if !Path::new("./addon.json").is_file() {
+		error!("Failed to find addon.json! Are you inside an addon directory?");
+		return;
+	}
+
+	// Entities are either a directory with init.lua / cl_init.lua / shared.lua or a single file
+	let entity_directory = PathBuf::from(format!("lua/entities/{}", &directory_name));
+	let entity_file = PathBuf::from(format!("lua/entities/{}.lua", &directory_name));
+
+	let entry_files: Vec<PathBuf> = if entity_directory.is_dir() {
+		["init.lua", "cl_init.lua", "shared.lua"]
+			.iter()
+			.map(|file_name| entity_directory.join(file_name))
+			.filter(|path| path.is_file())
+			.collect()
+	} else if entity_file.is_file() {
+		vec![entity_file]
+	} else {
+		error!("Failed to find entity <cyan>{}</> in this addon!", &directory_name);
+		return;
+	};
+
+	if entry_files.len() == 0 {
+		error!("Entity directory \"{}\" does not contain any of init.lua, cl_init.lua or shared.lua", entity_directory.display());
+		return;
+	}
+
+	//
+	// Build the transitive set of referenced Lua files
+	//
+	let reference_regex = Regex::new(r#"\b(AddCSLuaFile|include)\(\s*["']([^"']+)["']\s*\)"#).unwrap();
+
+	// Referencing file -> (function, reference as written, resolved path)
+	let mut references: HashMap<PathBuf, Vec<(String, String, Option<PathBuf>)>> = HashMap::new();
+	let mut visited_files: Vec<PathBuf> = vec!();
+	let mut queue: Vec<PathBuf> = entry_files.iter().rev().cloned().collect();
+
+	while let Some(lua_file) = queue.pop() {
+
+		if visited_files.contains(&lua_file) {
+			continue;
+		}
+		visited_files.push(lua_file.to_owned());
+
+		let lua_content = match read_to_string(&lua_file) {
+			Ok(content) => content,
+			Err(err) => {
+				warn!("Failed to read \"{}\": {}", lua_file.display(), err.to_string());
+				continue;
+			}
+		};
+
+		let mut file_references: Vec<(String, String, Option<PathBuf>)> = vec!();
+		for captures in reference_regex.captures_iter(&lua_content) {
+
+			let resolved_path = resolve_lua_path(&lua_file, &captures[2]);
+			if let Some(resolved_path) = &resolved_path {
+				queue.push(resolved_path.to_owned());
+			}
+
+			file_references.push((captures[1].to_string(), captures[2].to_string(), resolved_path));
+
+		}
+
+		references.insert(lua_file, file_references);
+
+	}
+
+	// Files which are sent to clients via AddCSLuaFile
+	let client_registered_files: HashSet<&PathBuf> = references
+		.values()
+		.flatten()
+		.filter(|(function, _, _)| function == "AddCSLuaFile")
+		.filter_map(|(_, _, resolved_path)| resolved_path.as_ref())
+		.collect();
+
+	// Files which are run on the client, i.e. cl_init.lua and everything it includes
+	let mut client_files: Vec<&PathBuf> = vec!();
+	let mut client_queue: Vec<&PathBuf> = entry_files.iter().filter(|path| path.ends_with("cl_init.lua")).collect();
+	while let Some(lua_file) = client_queue.pop() {
+		if client_files.contains(&lua_file) {
+			continue;
+		}
+		client_files.push(lua_file);
+		for (function, _, resolved_path) in references.get(lua_file).into_iter().flatten() {
+			if let (true, Some(resolved_path)) = (function == "include", resolved_path) {
+				client_queue.push(resolved_path);
+			}
+		}
+	}
+
+	//
+	// Print dependency tree
+	//
+	info!("<magenta>LUA FILES:</>");
+	let mut missing_count = 0;
+	for lua_file in &visited_files {
+
+		info!("\t<green>{}</>", display_lua_path(lua_file));
+
+		for (function, reference, resolved_path) in references.get(lua_file).unwrap_or(&vec!()) {
+			match resolved_path {
+				Some(resolved_path) => info!("\t  <magenta>↳</> {}(\"{}\") -> {}", function, reference, display_lua_path(resolved_path)),
+				None => {
+					missing_count += 1;
+					warn!("\t  <magenta>↳</> {}(\"{}\") -> <red>missing</>", function, reference);
+				}
+			}
+		}
+
+	}
+
+	let unregistered_client_files: Vec<&&PathBuf> = client_files
+		.iter()
+		.filter(|lua_file| !client_registered_files.contains(*lua_file))
+		.collect();
+
+	for lua_file in &unregistered_client_files {
+		warn!("<cyan>{}</> runs on the client but is never sent to clients via AddCSLuaFile", display_lua_path(lua_file));
+	}
+
+	if missing_count > 0 {
+		warn!("<red>{}</> referenced Lua files are missing on disk", missing_count);
+	}
+
+	if missing_count == 0 && unregistered_client_files.len() == 0 {
+		success!("<green>All {} Lua files of the entity are present and client files are registered correctly!</>", visited_files.len());
+	}
+
+}
+
+pub fn rename(old_directory_name: String, new_directory_name: String, print_name: Option<String>) {
+
+	// Check for addon.json
+	if !Path::new("./addon.json").is_file() {
+		error!("Failed to find addon.json! Are you inside an addon directory?");
+		return;
+	}
+
+	// Entities are either a directory or a single file
+	let (old_entity_path, new_entity_path) = if Path::new(&format!("./lua/entities/{}", &old_directory_name)).is_dir() {
+		(PathBuf::from(format!("./lua/entities/{}", &old_directory_name)), PathBuf::from(format!("./lua/entities/{}", &new_directory_name)))
+	} else if Path::new(&format!("./lua/entities/{}.lua", &old_directory_name)).is_file() {
+		(PathBuf::from(format!("./lua/entities/{}.lua", &old_directory_name)), PathBuf::from(format!("./lua/entities/{}.lua", &new_directory_name)))
+	} else {
+		error!("Failed to find entity <cyan>{}</> in this addon!", &old_directory_name);
+		return;
+	};
+
+	if old_directory_name == new_directory_name {
+		error!("The new name of the entity is the same as the old one!");
+		return;
+	}
+
+	// Check for existing entity
+	if new_entity_path.exists() {
+
+		let input_override = library::inquire::confirm_no("An entity with the new name already exists in this addon! Should it be overwritten?");
+		if !input_override {
+			info!("<on-red> Cancelled. </>");
+			return;
+		}
+
+		let remove_res = match new_entity_path.is_dir() {
+			true => remove_dir_all(&new_entity_path),
+			false => remove_file(&new_entity_path),
+		};
+		if let Err(err) = remove_res {
+			error!("Failed to remove existing entity \"{}\": {}", new_entity_path.display(), err.to_string());
+			return;
+		}
+
+	}
+
+	// Rename entity
+	if let Err(err) = rename_path(&old_entity_path, &new_entity_path) {
+		error!("Failed to rename entity: {}", err.to_string());
+		return;
+	}
+
+	success!("Renamed entity <cyan>{}</> to <magenta>{}</>!", &old_directory_name, &new_directory_name);
+
+	// Spawn menu icon (see create)
+	let old_icon_path = PathBuf::from(format!("./materials/entities/{}.png", &old_directory_name));
+	if old_icon_path.is_file() {
+		let new_icon_path = PathBuf::from(format!("./materials/entities/{}.png", &new_directory_name));
+		match rename_path(&old_icon_path, &new_icon_path) {
+			Ok(_) => success!("Renamed spawn menu icon to <cyan>{}</>", new_icon_path.display()),
+			Err(err) => warn!("Failed to rename spawn menu icon \"{}\": {}", old_icon_path.display(), err.to_string()),
+		}
+	}
+
+	// Pretty name
+	let shared_path = match new_entity_path.is_dir() {
+		true => new_entity_path.join("shared.lua"),
+		false => new_entity_path.to_owned(),
+	};
+	let print_name_regex = Regex::new(r#"(ENT\.PrintName\s*=\s*)(["'])[^"'\r\n]*(["'])"#).unwrap();
+
+	if let Ok(shared_content) = read_to_string(&shared_path) {
+		if print_name_regex.is_match(&shared_content) {
+
+			// Asking for the new pretty name would block a run with --assume-yes, so it is kept unless provided via --print-name
+			let new_pretty_name = match (print_name, library::inquire::assume_yes()) {
+				(Some(print_name), _) => Some(print_name),
+				(None, true) => None,
+				(None, false) => match library::inquire::confirm_no("Should the pretty name (ENT.PrintName) of the entity be updated as well?") {
+					true => Some(library::inquire::text_required("New pretty name for the entity:")),
+					false => None,
+				},
+			};
+
+			if let Some(input_pretty_name) = new_pretty_name {
+
+				let shared_content = print_name_regex.replace(&shared_content, |captures: &regex::Captures| format!("{}{}{}{}", &captures[1], &captures[2], input_pretty_name, &captures[3]));
+
+				match write(&shared_path, shared_content.as_bytes()) {
+					Ok(_) => success!("Updated pretty name to <magenta>{}</>", input_pretty_name),
+					Err(err) => error!("Failed to update pretty name in \"{}\": {}", shared_path.display(), err.to_string()),
+				}
+
+			}
+
+		}
+	}
+
+	// Other Lua files may still use the old class name, e.g. in ents.Create("<old>")
+	let class_name_regex = Regex::new(&format!(r#"["']{}["']"#, regex::escape(&old_directory_name))).unwrap();
+	let referencing_files: Vec<PathBuf> = WalkDir::new("./lua")
+		.into_iter()
+		.filter_map(|entry| entry.ok())
+		.filter(|entry| entry.file_type().is_file() && entry.path().extension().map_or(false, |extension| extension == "lua"))
+		.filter(|entry| read_to_string(entry.path()).map_or(false, |content| class_name_regex.is_match(&content)))
+		.map(|entry| entry.into_path())
+		.collect();
+
+	for referencing_file in &referencing_files {
+		warn!("<cyan>{}</> still references the old class name \"{}\"", display_lua_path(referencing_file), &old_directory_name);
+	}
+
+}
+
+// Resolves an include / AddCSLuaFile path like the game does: relative to the referencing file first, then relative to lua/
+fn resolve_lua_path(referencing_file: &Path, reference: &str) -> Option<PathBuf> {
+
+	let reference = reference.replace("\\", "/");
+
+	let mut candidates: Vec<PathBuf> = vec!();
+	if let Some(referencing_directory) = referencing_file.parent() {
+		candidates.push(normalize_path(&referencing_directory.join(&reference)));
+	}
+	candidates.push(normalize_path(&Path::new("lua").join(&reference)));
+
+	return candidates.into_iter().find(|candidate| candidate.is_file());
+
+}
+
+// Removes "." and ".." components without touching the file system
+fn normalize_path(path: &Path) -> PathBuf {
+
+	let mut normalized = PathBuf::new();
+
+	for component in path.components() {
+		match component {
+			Component::CurDir => {},
+			Component::ParentDir => {
+				normalized.pop();
+			},
+			component => normalized.push(component),
+		}
+	}
+
+	return normalized;
+
+}
+
+fn display_lua_path(path: &Path) -> String {
+	return path.to_string_lossy().replace("\\", "/");
+}
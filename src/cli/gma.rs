@@ -0,0 +1,71 @@
+use std::{fs, path::PathBuf};
+use clap::Subcommand;
+use crate::library::log::{error, info, success, warn};
+use crate::library;
+
+#[derive(Subcommand)]
+pub enum Actions {
+	List {
+		#[arg(value_parser = validate_gma_path)]
+		gma_path: PathBuf,
+	}
+}
+
+fn validate_gma_path(input: &str) -> Result<PathBuf, String> {
+	return library::validation::validate_input_file_exists(input, "gma");
+}
+
+pub fn list(gma_path: &PathBuf) {
+
+	// Read gma
+	let gma_content = match fs::read(gma_path) {
+		Ok(content) => content,
+		Err(err) => {
+			error!("Failed to read gma file \"{}\": {}", gma_path.display(), err.to_string());
+			return;
+		}
+	};
+
+	// Parse header and file table
+	let gma = match library::gma::read(&gma_content) {
+		Ok(gma) => gma,
+		Err(err) => {
+			error!("Failed to parse gma file \"{}\": {}", gma_path.display(), err.to_string());
+			return;
+		}
+	};
+
+	info!("<magenta>ADDON:</>");
+	info!("\t<magenta>↳</> Title: <cyan>{}</>", gma.title);
+	info!("\t<magenta>↳</> Type: <cyan>{}</>", gma.description_field("type").unwrap_or(String::from("-")));
+	info!("\t<magenta>↳</> Tags: <cyan>{}</>", gma.tags().join(", "));
+	info!("\t<magenta>↳</> Author: <cyan>{}</>", gma.author);
+	info!("\t<magenta>↳</> Format version: <cyan>{}</>", gma.version);
+
+	// List files and verify their checksums
+	info!("<magenta>FILES:</>");
+	let mut crc_mismatches = 0;
+	let mut total_size: u64 = 0;
+	for entry in &gma.entries {
+
+		info!("\t<green>{}</> ({} bytes)", entry.path, entry.size);
+		total_size += entry.size;
+
+		// gmad writes a crc of 0 when checksums are disabled
+		let entry_crc = library::gma::crc32(library::gma::entry_data(&gma_content, entry));
+		if entry.crc != 0 && entry.crc != entry_crc {
+			warn!("\t  ↳ CRC mismatch (expected {:08x}, got {:08x})", entry.crc, entry_crc);
+			crc_mismatches += 1;
+		}
+
+	}
+
+	info!("Total: <cyan>{}</> files, <cyan>{}</> bytes", gma.entries.len(), total_size);
+
+	if crc_mismatches > 0 {
+		warn!("<red>{}</> files have a CRC mismatch, the gma may be corrupted", crc_mismatches);
+	} else {
+		success!("<green>All file checksums are valid!</>");
+	}
+
+}
@@ -0,0 +1,761 @@
+use std::{collections::{HashMap, HashSet}, fs, path::PathBuf, time::Instant};
+use clap::Args;
+use itertools::Itertools;
+use regex::Regex;
+use crate::cli::vmf::content_collector::{self as vmf_collector, SummaryCategory};
+use crate::library;
+use crate::library::collector::{self, SourceContentFile, SourceMaterialData};
+use crate::library::log::{error, info, success, warn};
+
+#[derive(Args, Debug, Clone)]
+pub struct CollectOptions {
+	#[arg(long, help = "Write all copied files and directories in lowercase, regardless of the casing in the source paths. Recommended for content which is used on Linux (dedicated) servers.")]
+	pub flatten_case: bool,
+	#[arg(short, long, help = "Print additional information useful for debugging, e.g. which material of which model was looked up in which cdmaterials path.")]
+	pub verbose: bool,
+	#[arg(long = "model-ext", value_name = "EXTENSION", default_values = vmf_collector::MODEL_COMPANION_EXTENSIONS, help = "Extension of a companion file which is copied alongside every `.mdl` file. This option can be used multiple times and replaces the default set of `dx90.vtx`, `phy` and `vvd`.")]
+	pub model_ext: Vec<String>,
+	#[arg(long, value_name = "THREADS", default_value_t = 0, help = "Maximum number of threads used to look up missing content in the game files. Defaults to one thread per CPU core.")]
+	pub concurrency_limit: usize,
+	#[arg(long, help = "Also collect sound files played by animation events of the models, see `vmf collect-content`.")]
+	pub include_model_sounds: bool,
+	#[arg(long, help = "Collect the models as playermodels: Also collect their hands model and the Lua file which registers both (`player_manager.AddValidModel` / `player_manager.AddValidHands`), as well as the models they include via `$includemodel` (e.g. animations), and report each of these parts.")]
+	pub playermodel: bool,
+	#[arg(long, help = "Only collect the `.vmt` files of materials without reading them, so no textures (`.vtf`) are collected or copied, see `vmf collect-content`.")]
+	pub materials_only_vmt: bool,
+	#[arg(long, help = "For missing normal maps, copy their `_ssbump` / `_normal` naming variant to the missing name, see `vmf collect-content`.")]
+	pub fuzzy_textures: bool,
+	#[arg(long, help = "Report texture parameters of the collected materials which contain a drive letter (`C:\\...`), a leading slash or backslashes, see `vmf collect-content`.")]
+	pub strict_paths: bool,
+	#[arg(long, help = "Abort the whole collection with a nonzero exit code if any material (vmt) fails to be read or parsed, instead of skipping it with a warning.")]
+	pub strict: bool,
+	#[arg(long, help = "If another run is collecting into the same output path, wait until it is done instead of exiting with an error.")]
+	pub wait: bool,
+	#[arg(long, help = "After collecting, keep running and collect again whenever the models or a file inside of the source paths change, see `vmf collect-content`.")]
+	pub watch: bool,
+	#[arg(long, help = "Do not check whether the output path has enough free space for the collected content before copying.")]
+	pub no_space_check: bool,
+	#[arg(long, help = "Do not copy files again which already exist in the output path with the same size, e.g. to continue an interrupted copy, see `vmf collect-content`.")]
+	pub resume: bool,
+	#[arg(long, help = "Do not log how much of the missing content was found in the game files, see `vmf collect-content`.")]
+	pub exclude_found_in_game_report: bool,
+	#[arg(long, help = "Print all options as they are used, including the default values, as a JSON object before collecting, see `vmf collect-content`.")]
+	pub print_config: bool,
+	#[arg(long, value_name = "GAME_DIR", help = "Path to the Garry's Mod install directory (which contains `garrysmod/`), if it can not be found via Steam.")]
+	pub game_dir: Option<PathBuf>,
+	#[arg(long, visible_alias = "offline", conflicts_with = "game_dir", help = "Collect without the game files. Content which is part of the game can not be distinguished and is reported as missing.")]
+	pub skip_game_check: bool,
+}
+
+// The options shared with `vmf collect-content`, used for the models of its input list
+impl From<&vmf_collector::CollectOptions> for CollectOptions {
+	fn from(options: &vmf_collector::CollectOptions) -> Self {
+		Self {
+			flatten_case: options.flatten_case,
+			verbose: options.verbose,
+			model_ext: options.model_ext.to_owned(),
+			concurrency_limit: options.concurrency_limit,
+			include_model_sounds: options.include_model_sounds,
+			playermodel: false,
+			materials_only_vmt: options.materials_only_vmt,
+			fuzzy_textures: options.fuzzy_textures,
+			strict_paths: options.strict_paths,
+			strict: options.strict,
+			wait: options.wait,
+			watch: false,
+			no_space_check: options.no_space_check,
+			resume: options.resume,
+			exclude_found_in_game_report: options.exclude_found_in_game_report,
+			print_config: false,
+			game_dir: options.game_dir.to_owned(),
+			skip_game_check: options.skip_game_check,
+		}
+	}
+}
+
+// Collects the content of a single model or of all models in a directory into one output directory and returns its content summary, or None if the collection failed
+pub fn collect_content(model_path: &PathBuf, source_path_strings: Vec<String>, output_path: &PathBuf, options: &CollectOptions) -> Option<Vec<SummaryCategory>> {
+
+	//
+	// Resolve and validate output_path
+	//
+	let output_path = &match collector::prepare_output_path(output_path, model_path) {
+		Some(output_path) => output_path,
+		None => return None,
+	};
+
+	//
+	// Validate source_paths
+	//
+	let source_paths = match collector::validate_source_paths(source_path_strings) {
+		Some(source_paths) => source_paths,
+		None => return None,
+	};
+
+	//
+	// Locate game install
+	//
+	let game_dir = match collector::locate_game(options.skip_game_check, options.game_dir.as_ref()) {
+		Ok(game_dir) => game_dir,
+		Err(err) => {
+			error!("{}", err.to_string());
+			return None;
+		}
+	};
+
+	//
+	// Lock output_path until the collection is done, so concurrent runs can not write into it at the same time.
+	// The lock is released when it is dropped, i.e. on every return.
+	//
+	let _output_lock = match library::lock::DirectoryLock::acquire(output_path, options.wait) {
+		Ok(lock) => lock,
+		Err(err) => {
+			error!("{}", err);
+			return None;
+		}
+	};
+
+	//
+	// Create a hashmap with all source path files
+	//
+	let source_files = vmf_collector::build_source_files_map(&source_paths, None, false, None);
+
+	info!("Found <cyan>{}</> files in all source paths", source_files.len());
+
+	//
+	// Find the models inside the model path, they are looked up in the source files to know their path local to the source path
+	//
+	let canonical_model_path = match fs::canonicalize(model_path) {
+		Ok(path) => path,
+		Err(err) => {
+			error!("Failed to resolve model path \"{}\": {}", model_path.display(), err.to_string());
+			return None;
+		}
+	};
+
+	let mut used_models: HashMap<String, SourceContentFile> = source_files
+		.iter()
+		.filter(|(source_file_path, _)| source_file_path.ends_with(".mdl"))
+		.filter(|(_, source_file)| match fs::canonicalize(&source_file.full_path) {
+			Ok(path) => path.starts_with(&canonical_model_path),
+			Err(_) => false,
+		})
+		.map(|(source_file_path, source_file)| (source_file_path.to_owned(), source_file.to_owned()))
+		.collect();
+
+	if used_models.len() == 0 {
+		error!("Did not find any models in \"{}\". Make sure that the models are inside one of the source paths (<cyan>-s</>).", model_path.display());
+		return None;
+	}
+
+	let mut missing_models: HashMap<String, String> = HashMap::new();
+
+	//
+	// Collect the parts of playermodels which are not referenced by the model itself: the hands model and the Lua file registering them.
+	// Models included via $includemodel (e.g. the shared animations) are referenced by the model, but only needed by playermodels and NPCs.
+	//
+	let mut playermodels: Vec<Playermodel> = vec!();
+	let mut used_lua_files: HashMap<String, SourceContentFile> = HashMap::new();
+	if options.playermodel {
+
+		info!("Collecting hands and included models of <cyan>{}</> playermodels...", used_models.len());
+
+		let registrations = read_playermodel_registrations(&source_files);
+
+		for (model_source_path, content_file) in used_models.clone().iter().sorted_by_key(|(source_file_path, _)| source_file_path.to_owned()) {
+			playermodels.push(collect_playermodel(model_source_path, content_file, &registrations, &source_files, &mut used_models, &mut missing_models, &mut used_lua_files));
+		}
+
+		// The hands and animations can be inside the model path as well, they are only reported as part of their playermodel
+		let parts: HashSet<String> = playermodels
+			.iter()
+			.flat_map(|playermodel| playermodel.hands.iter().chain(playermodel.include_models.iter()).cloned())
+			.collect();
+		playermodels.retain(|playermodel| !parts.contains(&playermodel.model));
+
+	}
+
+	//
+	// Collect gib models of the models, which breakable props spawn when they break
+	//
+	let found_break_models = vmf_collector::collect_break_models(&mut used_models, &mut missing_models, &source_files);
+	if found_break_models > 0 || missing_models.len() > 0 {
+		info!("Found <green>{}</> gib models, <red>{}</> are missing in source files", found_break_models, missing_models.len());
+	}
+
+	let game_fs_open = match collector::open_file_system(game_dir.as_ref()) {
+		Some(fs) => fs,
+		None => return None,
+	};
+
+	// Content missing in the source paths is only looked up in the game files if there is a game
+	let game_fs_lookup = game_dir.as_ref().map(|_| &game_fs_open);
+
+	//
+	// Collect materials used by the models
+	//
+	info!("Collecting materials used by <cyan>{}</> models...", used_models.len());
+
+	let mut used_materials: HashMap<String, SourceContentFile> = HashMap::new();
+	let mut missing_materials: HashMap<String, String> = HashMap::new();
+
+	// Materials of every model (found, missing), used for the playermodel report
+	let mut models_materials: HashMap<String, (usize, Vec<String>)> = HashMap::new();
+
+	for (index, (source_file_path, content_file)) in used_models.iter().sorted_by_key(|(source_file_path, _)| source_file_path.to_owned()).enumerate() {
+
+		info!("<magenta>[{}/{}]</> {}", index + 1, used_models.len(), content_file.local_path);
+
+		let (model_used_materials, model_missing_materials) = vmf_collector::collect_model_materials(content_file, &source_files, &game_fs_open, options.verbose);
+		models_materials.insert(source_file_path.to_owned(), (model_used_materials.len(), model_missing_materials.keys().cloned().collect()));
+		used_materials.extend(model_used_materials);
+		missing_materials.extend(model_missing_materials);
+
+	}
+
+	//
+	// Collect sound files played by animation events of the models
+	//
+	let (used_sounds, mut missing_sounds) = match options.include_model_sounds {
+		true => vmf_collector::collect_models_sounds(&used_models, &source_files),
+		false => (HashMap::new(), HashMap::new()),
+	};
+
+	//
+	// Collect textures used by used_materials materials
+	//
+	let mut used_materials_data = SourceMaterialData::new();
+	if options.materials_only_vmt {
+		info!("Skipping textures of <cyan>{}</> materials (<cyan>--materials-only-vmt</>)", used_materials.len());
+	} else {
+		info!("Collecting textures used by <cyan>{}</> materials...", used_materials.len());
+		match collector::collect_materials_data(&mut used_materials, options.strict, |source_file| vmf_collector::read_material_data(&source_file.full_path, &source_files, &game_fs_open)) {
+			Ok(data) => used_materials_data.extend(data),
+			Err(err) => {
+				collector::log_strict_abort(&err);
+				return None;
+			}
+		}
+	}
+
+	// Add materials which are referenced by materials, but missing in source files (found ones were added while reading)
+	missing_materials.extend(used_materials_data.missing_materials);
+
+	//
+	// Find materials and textures included in the game and remove them from missing_materials / missing_textures
+	//
+	vmf_collector::filter::remove_game_content_logged("materials", &mut missing_materials, game_fs_lookup, options.concurrency_limit, options.exclude_found_in_game_report);
+	vmf_collector::filter::remove_game_content_logged("textures", &mut used_materials_data.missing_textures, game_fs_lookup, options.concurrency_limit, options.exclude_found_in_game_report);
+
+	// Look for the naming variants of missing normal maps
+	if options.fuzzy_textures && used_materials_data.missing_normal_maps.len() > 0 {
+		let variant_textures = vmf_collector::filter::hashmap_resolve_normal_map_variants(&used_materials_data.missing_normal_maps, &mut used_materials_data.missing_textures, &mut used_materials_data.used_textures, &source_files);
+		if variant_textures > 0 {
+			warn!("Collected <cyan>{}</> normal map variants under the names of the missing normal maps", variant_textures);
+		}
+	}
+
+	// Gib models are often shared with the game (e.g. models/props_junk/), as well as the animations and hands of playermodels
+	vmf_collector::filter::remove_game_content_logged("models", &mut missing_models, game_fs_lookup, options.concurrency_limit, options.exclude_found_in_game_report);
+
+	if missing_models.len() > 0 {
+		vmf_collector::report::log_missing_files_hashmap("models", &missing_models);
+	}
+
+	if options.include_model_sounds {
+
+		vmf_collector::filter::remove_game_content_logged("sounds", &mut missing_sounds, game_fs_lookup, options.concurrency_limit, options.exclude_found_in_game_report);
+
+		if missing_sounds.len() > 0 {
+			vmf_collector::report::log_missing_files_hashmap("sounds", &missing_sounds);
+		} else {
+			success!("<green>No sounds missing in source files!</>");
+		}
+
+	}
+
+	if missing_materials.len() > 0 {
+		vmf_collector::report::log_missing_files_hashmap("materials", &missing_materials);
+	} else {
+		success!("<green>No materials missing in source files!</>");
+	}
+
+	if used_materials_data.missing_textures.len() > 0 {
+		vmf_collector::report::log_missing_files_hashmap("textures", &used_materials_data.missing_textures);
+	} else if !options.materials_only_vmt {
+		success!("<green>No textures missing in source files!</>");
+	}
+
+	// Texture parameters which contain absolute paths or backslashes
+	if options.strict_paths {
+		collector::log_non_portable_references(&vmf_collector::find_non_portable_material_references(&used_materials), "materials");
+	}
+
+	//
+	// Playermodel report
+	//
+	for playermodel in &playermodels {
+
+		info!("<magenta>PLAYERMODEL:</> {}", playermodel.model);
+
+		match &playermodel.registration {
+			Some((name, lua_source_path)) => info!("\t<magenta>↳</> Registered as <cyan>{}</> in {}", name, lua_source_path),
+			None => warn!("\t<magenta>↳</> Not registered via <cyan>player_manager.AddValidModel</> in any Lua file of the source paths"),
+		}
+
+		log_model_materials("Materials", &playermodel.model, &models_materials, &missing_materials);
+
+		match &playermodel.hands {
+			Some(hands) => {
+				info!("\t<magenta>↳</> Hands: {}", model_status(hands, &used_models, &missing_models));
+				log_model_materials("Materials of the hands", hands, &models_materials, &missing_materials);
+			},
+			None => warn!("\t<magenta>↳</> Hands: no hands model registered via <cyan>player_manager.AddValidHands</>"),
+		}
+
+		match playermodel.include_models.len() {
+			0 => warn!("\t<magenta>↳</> Included models ($includemodel): none, the playermodel will not have any animations"),
+			_ => {
+				info!("\t<magenta>↳</> Included models ($includemodel):");
+				for include_model in &playermodel.include_models {
+					info!("\t  {}", model_status(include_model, &used_models, &missing_models));
+				}
+			},
+		}
+
+	}
+
+	//
+	// Content summary
+	//
+	let model_companion_extensions: Vec<&str> = options.model_ext
+		.iter()
+		.map(|extension| extension.trim_start_matches('.'))
+		.filter(|extension| !extension.is_empty())
+		.collect();
+
+	let mut summary_categories = vec![
+		SummaryCategory { name: "Models", found: used_models.len(), missing: missing_models.len(), size: vmf_collector::report::files_size(&used_models, Some(&model_companion_extensions)) },
+		SummaryCategory { name: "Materials", found: used_materials.len(), missing: missing_materials.len(), size: vmf_collector::report::files_size(&used_materials, None) },
+	];
+
+	if options.include_model_sounds {
+		summary_categories.push(SummaryCategory { name: "Sounds", found: used_sounds.len(), missing: missing_sounds.len(), size: vmf_collector::report::files_size(&used_sounds, None) });
+	}
+
+	if !options.materials_only_vmt {
+		summary_categories.push(SummaryCategory { name: "Textures", found: used_materials_data.used_textures.len(), missing: used_materials_data.missing_textures.len(), size: vmf_collector::report::files_size(&used_materials_data.used_textures, None) });
+	}
+
+	if options.playermodel {
+		summary_categories.push(SummaryCategory { name: "Lua files", found: used_lua_files.len(), missing: 0, size: vmf_collector::report::files_size(&used_lua_files, None) });
+	}
+
+	vmf_collector::report::print_content_summary(source_files.len(), &summary_categories, false);
+
+	let missing_content: Vec<(&str, &HashMap<String, String>)> = vec![
+		("models", &missing_models),
+		("materials", &missing_materials),
+		("textures", &used_materials_data.missing_textures),
+		("sounds", &missing_sounds),
+	];
+	vmf_collector::report::record_content(model_path, source_files.len(), &summary_categories, &missing_content);
+
+	if game_fs_lookup.is_none() {
+		warn!("The game check was skipped, so the missing content includes content which is part of the game");
+	}
+
+	//
+	// Copy all content to output directory
+	//
+	info!("");
+	info!("<cyan>Copying content to output directory \"{}\"...</>", &output_path.display());
+
+	let mut collected_content: Vec<(&str, &HashMap<String, SourceContentFile>, Option<&Vec<&str>>)> = vec![
+		("materials", &used_materials, None),
+		("models", &used_models, Some(&model_companion_extensions)),
+	];
+
+	if !options.materials_only_vmt {
+		collected_content.push(("textures", &used_materials_data.used_textures, None));
+	}
+
+	if options.include_model_sounds {
+		collected_content.push(("sounds", &used_sounds, None));
+	}
+
+	if options.playermodel {
+		collected_content.push(("Lua files", &used_lua_files, None));
+	}
+
+	if !options.no_space_check {
+		if let Err(err) = vmf_collector::copy::check_output_space(&collected_content, output_path) {
+			error!("{}", err.to_string());
+			return None;
+		}
+	}
+
+	let mut copied_files_count = 0;
+	let mut resumed_files_count = 0;
+	for (name, files, copy_additional_extensions) in collected_content {
+		info!("Copying <cyan>{}</> {}...", files.len(), name);
+		for copied_file in vmf_collector::copy::copy_files_to_output(files, output_path, copy_additional_extensions, options.flatten_case, false, false, options.resume) {
+			match copied_file.resumed {
+				true => resumed_files_count += 1,
+				false => copied_files_count += 1,
+			}
+		}
+	}
+
+	if options.resume {
+		success!("Skipped <cyan>{}</> files which were already copied completely", resumed_files_count);
+	}
+
+	success!("Copied <cyan>{}</> files to \"<green>{}</>\"", copied_files_count, output_path.display());
+
+	return Some(summary_categories);
+
+}
+
+// Collects the content of every model listed in a newline-delimited file, e.g. for batch jobs across several prop packs.
+// Returns whether all models are complete, or None if any of them failed to be collected.
+pub fn collect_content_list(input_list: &PathBuf, source_path_strings: Vec<String>, output_path: &PathBuf, shared_output: bool, options: &CollectOptions) -> Option<bool> {
+
+	let inputs = collector::read_input_list(input_list, &["mdl"])?;
+
+	info!("Collecting content of <cyan>{}</> models listed in \"<green>{}</>\"...", inputs.len(), input_list.display());
+
+	return collector::collect_input_list(&inputs, &Some(output_path.to_owned()), shared_output, |input, input_output_path| {
+		let input_output_path = input_output_path.as_ref().unwrap_or(output_path);
+		return collect_content(input, source_path_strings.to_owned(), input_output_path, options)
+			.map(|summary_categories| summary_categories.iter().map(|category| category.missing).sum());
+	});
+
+}
+
+// Collects the content of the models, then collects it again whenever a model or a file inside of the source paths changes, until the process is interrupted
+pub fn watch_content(model_path: &PathBuf, source_path_strings: Vec<String>, output_path: &PathBuf, options: &CollectOptions) {
+
+	collect_content(model_path, source_path_strings.to_owned(), output_path, options);
+
+	let mut watched_paths: Vec<PathBuf> = vec!(model_path.to_owned());
+	watched_paths.extend(source_path_strings.iter().map(PathBuf::from).filter(|path| path.is_dir()));
+
+	// Copying into the output path must not cause another run, even if it is inside of a source path
+	let ignored_paths: Vec<PathBuf> = vec!(library::validation::expand_output_path(output_path, model_path));
+
+	info!("Watching <cyan>{}</> and <cyan>{}</> source paths for changes, press <cyan>Ctrl+C</> to stop...", model_path.display(), watched_paths.len() - 1);
+
+	let result = library::watch::watch(&watched_paths, &ignored_paths, |changed_paths| {
+
+		match changed_paths.len() {
+			1 => info!("<magenta>CHANGED:</> {}", changed_paths[0].display()),
+			count => info!("<magenta>CHANGED:</> {} and <cyan>{}</> more", changed_paths[0].display(), count - 1),
+		}
+
+		let start = Instant::now();
+		match collect_content(model_path, source_path_strings.to_owned(), output_path, options) {
+			Some(_) => success!("Collected in <cyan>{:.1}s</>, waiting for changes...", start.elapsed().as_secs_f64()),
+			None => error!("Collection failed, waiting for changes..."),
+		}
+
+	});
+
+	if let Err(err) = result {
+		error!("{}", err.to_string());
+	}
+
+}
+
+// Parts of a playermodel which are reported separately
+struct Playermodel {
+	model: String,
+	// (name, Lua file) of the player_manager.AddValidModel call
+	registration: Option<(String, String)>,
+	hands: Option<String>,
+	include_models: Vec<String>,
+}
+
+struct PlayermodelRegistrations {
+	// Model -> (name, Lua file)
+	models: HashMap<String, (String, String)>,
+	// Name -> (hands model, Lua file)
+	hands: HashMap<String, (String, String)>,
+}
+
+// Reads the playermodels and their hands registered by the Lua files of the source paths, e.g.
+// player_manager.AddValidModel("Name", "models/player/name.mdl") and player_manager.AddValidHands("Name", "models/weapons/c_arms_name.mdl", 0, "00000000")
+fn read_playermodel_registrations(source_files: &HashMap<String, SourceContentFile>) -> PlayermodelRegistrations {
+
+	let model_regex = Regex::new(r#"player_manager\.AddValidModel\(\s*["']([^"']+)["']\s*,\s*["']([^"']+)["']"#).unwrap();
+	let hands_regex = Regex::new(r#"player_manager\.AddValidHands\(\s*["']([^"']+)["']\s*,\s*["']([^"']+)["']"#).unwrap();
+
+	let mut registrations = PlayermodelRegistrations { models: HashMap::new(), hands: HashMap::new() };
+
+	for (source_file_path, source_file) in source_files.iter().sorted_by_key(|(source_file_path, _)| source_file_path.to_owned()) {
+
+		if !source_file_path.starts_with("lua\\") || !source_file_path.ends_with(".lua") {
+			continue;
+		}
+
+		let lua_content = match fs::read_to_string(&source_file.full_path) {
+			Ok(content) => content,
+			Err(err) => {
+				warn!("Failed to read Lua file \"{}\": {}", source_file.full_path, err.to_string());
+				continue;
+			}
+		};
+
+		for captures in model_regex.captures_iter(&lua_content) {
+			if let Some(model_source_path) = vmf_collector::make_model_path(&captures[2]) {
+				registrations.models.entry(model_source_path).or_insert((captures[1].to_owned(), source_file_path.to_owned()));
+			}
+		}
+
+		for captures in hands_regex.captures_iter(&lua_content) {
+			registrations.hands.entry(captures[1].to_owned()).or_insert((captures[2].to_owned(), source_file_path.to_owned()));
+		}
+
+	}
+
+	return registrations;
+
+}
+
+// Collects the parts of a playermodel which are not referenced by the model itself (see --playermodel)
+fn collect_playermodel(model_source_path: &String, content_file: &SourceContentFile, registrations: &PlayermodelRegistrations, source_files: &HashMap<String, SourceContentFile>, used_models: &mut HashMap<String, SourceContentFile>, missing_models: &mut HashMap<String, String>, used_lua_files: &mut HashMap<String, SourceContentFile>) -> Playermodel {
+
+	let mut playermodel = Playermodel { model: model_source_path.to_owned(), registration: None, hands: None, include_models: vec!() };
+
+	for include_model in vmf_collector::read_model_include_models(content_file) {
+
+		let include_model_source_path = match vmf_collector::make_model_path(&include_model) {
+			Some(path) => path,
+			None => continue,
+		};
+
+		match source_files.get(&include_model_source_path) {
+			Some(source_file) => {
+				used_models.insert(include_model_source_path.to_owned(), source_file.to_owned());
+			},
+			None => {
+				missing_models.insert(include_model_source_path.to_owned(), format!("Included via $includemodel by model \"{}\"", content_file.local_path));
+			}
+		}
+
+		playermodel.include_models.push(include_model_source_path);
+
+	}
+
+	if let Some((name, lua_source_path)) = registrations.models.get(model_source_path) {
+
+		used_lua_files.insert(lua_source_path.to_owned(), source_files[lua_source_path].to_owned());
+		playermodel.registration = Some((name.to_owned(), lua_source_path.to_owned()));
+
+		if let Some((hands, hands_lua_source_path)) = registrations.hands.get(name) {
+
+			used_lua_files.insert(hands_lua_source_path.to_owned(), source_files[hands_lua_source_path].to_owned());
+
+			if let Some(hands_source_path) = vmf_collector::make_model_path(hands) {
+				match source_files.get(&hands_source_path) {
+					Some(source_file) => {
+						used_models.insert(hands_source_path.to_owned(), source_file.to_owned());
+					},
+					None => {
+						missing_models.insert(hands_source_path.to_owned(), format!("Hands model of playermodel \"{}\"", name));
+					}
+				}
+				playermodel.hands = Some(hands_source_path);
+			}
+
+		}
+
+	}
+
+	return playermodel;
+
+}
+
+fn model_status(model_source_path: &String, used_models: &HashMap<String, SourceContentFile>, missing_models: &HashMap<String, String>) -> String {
+	if used_models.contains_key(model_source_path) {
+		return format!("<green>+</> {}", model_source_path);
+	} else if missing_models.contains_key(model_source_path) {
+		return format!("<red>-</> {} (missing)", model_source_path);
+	}
+	return format!("<cyan>~</> {} (game content)", model_source_path);
+}
+
+// Missing materials of the model which were found in the game files are not counted
+fn log_model_materials(name: &str, model_source_path: &String, models_materials: &HashMap<String, (usize, Vec<String>)>, missing_materials: &HashMap<String, String>) {
+
+	let (found_count, model_missing_materials) = match models_materials.get(model_source_path) {
+		Some(materials) => materials,
+		None => return,
+	};
+
+	let model_missing_materials: Vec<&String> = model_missing_materials
+		.iter()
+		.filter(|material| missing_materials.contains_key(*material))
+		.sorted()
+		.collect();
+
+	info!("\t<magenta>↳</> {}: <green>{}</> found, <red>{}</> missing", name, found_count, model_missing_materials.len());
+	for material in model_missing_materials {
+		info!("\t  <red>-</> {}", material);
+	}
+
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+
+	#[derive(clap::Parser)]
+	struct TestCommand {
+		#[command(flatten)]
+		options: CollectOptions,
+	}
+
+	#[test]
+	fn playermodel_collects_hands_registration_and_animations() {
+
+		let directory = std::env::temp_dir().join(format!("gcli_model_collector_test_playermodel_{}", std::process::id()));
+		let _ = fs::remove_dir_all(&directory);
+
+		// Model including "models/m_anm.mdl" via $includemodel, with the model group directly behind the header
+		let mut mdl = vec!(0u8; 348);
+		mdl[..4].copy_from_slice(b"IDST");
+		mdl[336..340].copy_from_slice(&1i32.to_le_bytes());
+		mdl[340..344].copy_from_slice(&348i32.to_le_bytes());
+		mdl.extend_from_slice(&0i32.to_le_bytes());
+		mdl.extend_from_slice(&8i32.to_le_bytes());
+		mdl.extend_from_slice(b"models/m_anm.mdl\0");
+
+		let files: Vec<(&str, Vec<u8>)> = vec!(
+			("models/player/mymod/soldier.mdl", mdl),
+			("models/weapons/c_arms_soldier.mdl", b"IDST".to_vec()),
+			("lua/autorun/soldier_playermodel.lua", b"player_manager.AddValidModel( \"MyMod Soldier\", \"models/player/mymod/soldier.mdl\" )\nplayer_manager.AddValidHands( \"MyMod Soldier\", \"models/weapons/c_arms_soldier.mdl\", 0, \"00000000\" )\n".to_vec()),
+		);
+
+		let mut source_files: HashMap<String, SourceContentFile> = HashMap::new();
+		for (local_path, content) in files {
+			let full_path = directory.join(local_path);
+			fs::create_dir_all(full_path.parent().unwrap()).unwrap();
+			fs::write(&full_path, content).unwrap();
+			source_files.insert(local_path.replace("/", "\\"), SourceContentFile { full_path: full_path.to_string_lossy().to_string(), local_path: local_path.replace("/", "\\") });
+		}
+
+		let model_source_path = String::from("models\\player\\mymod\\soldier.mdl");
+		let registrations = read_playermodel_registrations(&source_files);
+		let (mut used_models, mut missing_models, mut used_lua_files) = (HashMap::new(), HashMap::new(), HashMap::new());
+		let playermodel = collect_playermodel(&model_source_path, &source_files[&model_source_path], &registrations, &source_files, &mut used_models, &mut missing_models, &mut used_lua_files);
+
+		assert_eq!(playermodel.registration, Some((String::from("MyMod Soldier"), String::from("lua\\autorun\\soldier_playermodel.lua"))));
+		assert_eq!(playermodel.hands.as_deref(), Some("models\\weapons\\c_arms_soldier.mdl"));
+		assert_eq!(playermodel.include_models, vec!("models\\m_anm.mdl"));
+
+		// The shared animations are part of the game, so they are not in the source paths
+		assert!(used_models.contains_key("models\\weapons\\c_arms_soldier.mdl"));
+		assert!(missing_models.contains_key("models\\m_anm.mdl"));
+		assert_eq!(used_lua_files.keys().collect::<Vec<_>>(), vec!("lua\\autorun\\soldier_playermodel.lua"));
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	// Writes a model with a single texture, cdmaterials path and $includemodel (none if empty) and without any meshes, along with the .vvd and .dx90.vtx it is read with
+	fn write_model(directory: &std::path::Path, local_path: &str, cdmaterials: &str, texture_name: &str, include_model: &str) {
+
+		// studiohdr_t is followed by the texture table, the cdmaterials table, the include model table and the strings they reference
+		let texture_index = 408;
+		let cdmaterials_index = texture_index + 64;
+		let include_model_index = cdmaterials_index + 4;
+		let texture_name_index = include_model_index + 8;
+		let cdmaterials_name_index = texture_name_index + texture_name.len() + 1;
+		let include_model_name_index = cdmaterials_name_index + cdmaterials.len() + 1;
+		let include_model_count = match include_model.is_empty() {
+			true => 0,
+			false => 1,
+		};
+		let checksum = 1234;
+
+		let mut mdl = vec!(0u8; texture_name_index);
+		for name in [texture_name, cdmaterials, include_model] {
+			mdl.extend(name.as_bytes());
+			mdl.push(0);
+		}
+		mdl[..4].copy_from_slice(b"IDST");
+		let length = mdl.len();
+		for (offset, value) in [
+			(4, 48), (8, checksum), (76, length),
+			(204, 1), (208, texture_index), (212, 1), (216, cdmaterials_index), (336, include_model_count), (340, include_model_index),
+			(texture_index, texture_name_index - texture_index), (cdmaterials_index, cdmaterials_name_index), (include_model_index + 4, include_model_name_index - include_model_index),
+		] {
+			mdl[offset..offset + 4].copy_from_slice(&(value as i32).to_le_bytes());
+		}
+
+		let mut vvd = vec!(0u8; 64);
+		vvd[..4].copy_from_slice(b"IDSV");
+		vvd[4..8].copy_from_slice(&4i32.to_le_bytes());
+		vvd[8..12].copy_from_slice(&(checksum as i32).to_le_bytes());
+
+		let mut vtx = vec!(0u8; 36);
+		vtx[..4].copy_from_slice(&7i32.to_le_bytes());
+		vtx[16..20].copy_from_slice(&(checksum as i32).to_le_bytes());
+
+		let path = directory.join(local_path);
+		fs::create_dir_all(path.parent().unwrap()).unwrap();
+		fs::write(&path, mdl).unwrap();
+		fs::write(path.with_extension("vvd"), vvd).unwrap();
+		fs::write(path.with_extension("dx90.vtx"), vtx).unwrap();
+
+	}
+
+	#[test]
+	fn playermodel_is_copied_with_its_hands_registration_and_animations() {
+
+		let source_path = std::env::temp_dir().join(format!("gcli_model_collector_test_playermodel_collect_source_{}", std::process::id()));
+		let output_path = std::env::temp_dir().join(format!("gcli_model_collector_test_playermodel_collect_output_{}", std::process::id()));
+		let _ = fs::remove_dir_all(&source_path);
+		let _ = fs::remove_dir_all(&output_path);
+		fs::create_dir_all(&output_path).unwrap();
+
+		write_model(&source_path, "models/player/mymod/soldier.mdl", "models/player/mymod/", "soldier", "models/player/mymod/soldier_anims.mdl");
+		write_model(&source_path, "models/player/mymod/soldier_anims.mdl", "models/player/mymod/", "soldier", "");
+		write_model(&source_path, "models/weapons/c_arms_soldier.mdl", "models/weapons/", "c_arms_soldier", "");
+		for (local_path, content) in [
+			("lua/autorun/soldier_playermodel.lua", "player_manager.AddValidModel( \"MyMod Soldier\", \"models/player/mymod/soldier.mdl\" )\nplayer_manager.AddValidHands( \"MyMod Soldier\", \"models/weapons/c_arms_soldier.mdl\", 0, \"00000000\" )\n"),
+			("materials/models/player/mymod/soldier.vmt", "VertexLitGeneric\n{\n\t$basetexture models/player/mymod/soldier\n}\n"),
+			("materials/models/player/mymod/soldier.vtf", "VTF"),
+			("materials/models/weapons/c_arms_soldier.vmt", "VertexLitGeneric { }"),
+		] {
+			fs::create_dir_all(source_path.join(local_path).parent().unwrap()).unwrap();
+			fs::write(source_path.join(local_path), content).unwrap();
+		}
+
+		let options = <TestCommand as clap::Parser>::parse_from(["gcli", "--playermodel", "--skip-game-check"]).options;
+		let model_path = source_path.join("models/player/mymod/soldier.mdl");
+		let summary_categories = collect_content(&model_path, vec!(source_path.to_string_lossy().to_string()), &output_path, &options).unwrap();
+
+		let summary_counts: Vec<(&str, usize, usize)> = summary_categories.iter().map(|category| (category.name, category.found, category.missing)).collect();
+		assert_eq!(summary_counts, vec!(("Models", 3, 0), ("Materials", 2, 0), ("Textures", 1, 0), ("Lua files", 1, 0)));
+
+		for local_path in [
+			"models/player/mymod/soldier.mdl",
+			"models/player/mymod/soldier_anims.mdl",
+			"models/player/mymod/soldier_anims.vvd",
+			"models/weapons/c_arms_soldier.mdl",
+			"materials/models/weapons/c_arms_soldier.vmt",
+			"materials/models/player/mymod/soldier.vtf",
+			"lua/autorun/soldier_playermodel.lua",
+		] {
+			assert!(output_path.join(local_path).is_file(), "{}", local_path);
+		}
+
+		fs::remove_dir_all(&source_path).unwrap();
+		fs::remove_dir_all(&output_path).unwrap();
+
+	}
+
+}
@@ -0,0 +1,42 @@
+use std::path::PathBuf;
+use clap::Subcommand;
+use crate::library;
+
+pub mod content_collector;
+
+#[derive(Subcommand)]
+pub enum Actions {
+	CollectContent {
+		#[arg(value_parser = validate_model_path, required_unless_present = "input_list", help = "Path to a `.mdl` file or to a directory whose `.mdl` files (including subdirectories) are collected. The models have to be inside one of the source paths.")]
+		model_path: Option<PathBuf>,
+		#[arg(long, value_parser = validate_input_list_path, conflicts_with_all = ["model_path", "watch"], help = "Path to a file which lists one `.mdl` path per line (relative to the file). The content of every listed model is collected into its own subdirectory of the output path.")]
+		input_list: Option<PathBuf>,
+		#[arg(long, requires = "input_list", help = "Collect the content of all models of `--input-list` into the output path itself instead of a subdirectory per model.")]
+		shared_output: bool,
+		#[arg(short, long, help = "Path to a directory which contains content the models potentially use. The directory should contain subdirectories like `materials/` and `models/`. This option can be used multiple times.")]
+		source_path: Vec<String>,
+		#[arg(short, long, value_parser = validate_output_path, help = "Path to a directory where the models and all of the content they use will be copied to.")]
+		output_path: PathBuf,
+		#[command(flatten)]
+		options: content_collector::CollectOptions,
+	}
+}
+
+fn validate_model_path(input: &str) -> Result<PathBuf, String> {
+	if PathBuf::from(input).is_dir() {
+		return Ok(PathBuf::from(input));
+	}
+	return library::validation::validate_input_file_exists(input, "mdl");
+}
+
+fn validate_input_list_path(input: &str) -> Result<PathBuf, String> {
+	let path = PathBuf::from(input);
+	if !path.is_file() {
+		return Err("Provided path is not a file".to_owned());
+	}
+	return Ok(path);
+}
+
+fn validate_output_path(input: &str) -> Result<PathBuf, String> {
+	return library::validation::validate_output_path_template(input);
+}
@@ -0,0 +1,1265 @@
+use std::{collections::{HashMap, HashSet}, fs, path::{Path, PathBuf}, time::{SystemTime, UNIX_EPOCH}};
+use crate::library;
+use crate::library::collector::SourceContentFile;
+use crate::library::log::{error, info, success, warn};
+use schemars::JsonSchema;
+use serde::Serialize;
+use plumber_core::fs::OpenFileSystem;
+use walkdir::WalkDir;
+use itertools::Itertools;
+use sha2::{Digest, Sha256};
+use simple_error::{bail, SimpleError};
+use super::{CollectContext, SummaryCategory, CLEAN_SUBDIRECTORIES, CONTENT_LOG_FILE_NAME, OUTPUT_CONTENT_SUBDIRECTORIES, WINDOWS_MAX_PATH, collect_model_materials, read_material_data};
+use super::report::format_size;
+
+// Copies all collected content to the output path (split into chunks with --chunk-output) and writes the manifest and log of the copied files.
+// Returns None if the output path can not be cleaned or has not enough free space.
+pub fn copy_content(context: &CollectContext, vmf: &PathBuf, source_paths: &Vec<PathBuf>, output_path: &PathBuf, summary_categories: &Vec<SummaryCategory>, missing_content: &Vec<(&str, &HashMap<String, String>)>, collected_content: &Vec<(&str, &HashMap<String, SourceContentFile>, Option<&Vec<&str>>)>) -> Option<()> {
+
+	let options = context.options;
+
+	info!("");
+	info!("<cyan>Copying content to output directory \"{}\"...</>", &output_path.display());
+
+	// Content directories of all categories, so content of categories which are no longer used by the map is found as well
+	let content_subdirectories = output_content_subdirectories(collected_content);
+
+	// Remove stale content of previous runs
+	if options.clean {
+
+		let clean_subdirectories = clean_subdirectories(&content_subdirectories, options.clean_all_categories);
+
+		let stale_files = find_stale_output_files(output_path, &clean_subdirectories, &collected_local_paths(collected_content));
+		if stale_files.len() > 0 {
+			info!("Removing <cyan>{}</> files of previous runs which are not used by the map...", stale_files.len());
+		}
+
+		for stale_file in &stale_files {
+			if let Err(err) = fs::remove_file(output_path.join(stale_file)) {
+				error!("Failed to remove \"{}\": {}", output_path.join(stale_file).display(), err.to_string());
+				return None;
+			}
+		}
+
+		remove_empty_directories(output_path, &clean_subdirectories);
+
+	}
+
+	// Symlinks take no space for the content itself
+	if !options.no_space_check && !options.relative_symlink {
+		if let Err(err) = check_output_space(collected_content, output_path) {
+			error!("{}", err.to_string());
+			return None;
+		}
+	}
+
+	let mut copied_files: Vec<(&str, CopiedFile)> = vec!();
+	match options.chunk_output {
+		Some(chunk_size) => {
+
+			info!("Splitting content into chunks of <cyan>{}</>...", format_size(chunk_size));
+			let chunks = chunk_collected_content(collected_content, chunk_size, context.source_files, context.open_fs);
+
+			for (chunk_index, (size, chunk_files)) in chunks.iter().enumerate() {
+
+				let chunk_path = output_path.join(format!("output_{}", chunk_index + 1));
+
+				info!("<magenta>CHUNK {}:</> \"{}\" (<cyan>{}</>)", chunk_index + 1, chunk_path.display(), format_size(*size));
+				if *size > chunk_size {
+					warn!("\t<magenta>↳</> Larger than <cyan>{}</>, since a model can not be split from its materials and textures", format_size(chunk_size));
+				}
+
+				for ((name, _, copy_additional_extensions), files) in collected_content.iter().zip(chunk_files) {
+					if files.len() == 0 {
+						continue;
+					}
+					info!("\t<magenta>↳</> Copying <cyan>{}</> {}...", files.len(), name);
+					for copied_file in copy_files_to_output(files, &chunk_path, *copy_additional_extensions, options.flatten_case, options.manifest_hashes, options.relative_symlink, options.resume) {
+						copied_files.push((name, copied_file));
+					}
+				}
+
+			}
+
+			success!("Split content into <cyan>{}</> chunks", chunks.len());
+
+		},
+		None => {
+			for (name, files, copy_additional_extensions) in collected_content {
+				info!("Copying <cyan>{}</> {}...", files.len(), name);
+				for copied_file in copy_files_to_output(files, output_path, *copy_additional_extensions, options.flatten_case, options.manifest_hashes, options.relative_symlink, options.resume) {
+					copied_files.push((name, copied_file));
+				}
+			}
+		},
+	}
+
+	if options.resume {
+		let resumed_files_count = copied_files.iter().filter(|(_, copied_file)| copied_file.resumed).count();
+		success!("Skipped <cyan>{}</> files which were already copied completely, copied <cyan>{}</> files", resumed_files_count, copied_files.len() - resumed_files_count);
+	}
+
+	// Write a manifest of all copied and missing files
+	if let Some(manifest_path) = &options.manifest {
+		match fs::write(manifest_path, content_manifest_json(vmf, &copied_files, missing_content)) {
+			Ok(_) => success!("Wrote manifest of <cyan>{}</> copied files to \"<green>{}</>\"", copied_files.len(), manifest_path.display()),
+			Err(err) => warn!("Failed to write manifest \"{}\": {}", manifest_path.display(), err.to_string()),
+		}
+	}
+
+	// Write a human-readable log into the output directory
+	if options.write_log {
+		let log_path = output_path.join(CONTENT_LOG_FILE_NAME);
+		match fs::write(&log_path, content_log_text(vmf, source_paths, context.source_files.len(), summary_categories, &copied_files, missing_content)) {
+			Ok(_) => success!("Wrote log to \"<green>{}</>\"", log_path.display()),
+			Err(err) => warn!("Failed to write log \"{}\": {}", log_path.display(), err.to_string()),
+		}
+	}
+
+	// Replace byte-identical copies with hardlinks
+	if options.dedupe_output {
+		let output_files: Vec<PathBuf> = copied_files.iter().map(|(_, copied_file)| copied_file.output_path.to_owned()).collect();
+		info!("Looking for identical files in <cyan>{}</> copied files...", output_files.len());
+		match dedupe_output_files(&output_files) {
+			Ok((linked_files, saved_bytes)) => success!("Replaced <cyan>{}</> identical files with hardlinks, saving <cyan>{:.2}</> MB", linked_files, saved_bytes as f64 / 1024.0 / 1024.0),
+			Err(err) => warn!("Failed to deduplicate output files: {}", err.to_string()),
+		}
+	}
+
+	// List files in the output directory which are not used by the map anymore
+	if options.list_stale {
+
+		let stale_files = find_stale_output_files(output_path, &content_subdirectories, &collected_local_paths(collected_content));
+		if stale_files.len() > 0 {
+			warn!("Found <red>{}</> files in output directory which are not used by the map (candidates for deletion):", stale_files.len());
+			for stale_file in &stale_files {
+				warn!("\t<red>-</> {}", stale_file);
+			}
+		} else {
+			success!("<green>No stale files in output directory!</>");
+		}
+
+	}
+
+	return Some(());
+
+}
+
+// Packs all collected content into a gma, leaving out the files which are ignored by the addon.json of a source path or not allowed by gmad.
+// Returns None if the gma can not be written.
+pub fn pack_gma(vmf: &PathBuf, source_paths: &Vec<PathBuf>, gma_path: &PathBuf, author: &Option<String>, collected_content: &Vec<(&str, &HashMap<String, SourceContentFile>, Option<&Vec<&str>>)>) -> Option<()> {
+
+	info!("");
+	info!("<cyan>Packing content into \"{}\"...</>", gma_path.display());
+
+	// Apply the ignore rules of the addon.json of a source path, if one of them is an addon
+	let addon_json_path = source_paths.iter().map(|source_path| source_path.join("addon.json")).find(|path| path.is_file());
+	let addon_json = match &addon_json_path {
+		Some(addon_json_path) => match fs::read_to_string(addon_json_path) {
+			Ok(addon_json) => {
+				info!("Applying the ignore rules of \"{}\"", addon_json_path.display());
+				Some(addon_json)
+			},
+			Err(err) => {
+				warn!("Failed to read \"{}\", packing without its ignore rules: {}", addon_json_path.display(), err.to_string());
+				None
+			}
+		},
+		None => None,
+	};
+	let ignore_patterns = addon_json.as_ref().map(|addon_json| library::gma::addon_json_ignore_patterns(addon_json)).unwrap_or_default();
+
+	// --gma-author takes precedence over the author of the addon.json
+	let author = author
+		.to_owned()
+		.or_else(|| addon_json.as_ref().and_then(|addon_json| library::gma::addon_json_author(addon_json)))
+		.unwrap_or(String::from(library::gma::GMA_DEFAULT_AUTHOR));
+
+	let mut excluded_files_count = 0;
+
+	let mut gma_files: Vec<(String, PathBuf)> = vec!();
+	for (_, files, copy_additional_extensions) in collected_content {
+		for source_file in files.values() {
+
+			let mut file_paths = vec![(source_file.local_path.to_owned(), PathBuf::from(&source_file.full_path))];
+			for extension in copy_additional_extensions.unwrap_or(&vec!()) {
+				let companion_full_path = Path::new(&source_file.full_path).with_extension(extension);
+				if companion_full_path.is_file() {
+					let companion_local_path = Path::new(&source_file.local_path).with_extension(extension);
+					file_paths.push((companion_local_path.to_string_lossy().to_string(), companion_full_path));
+				}
+			}
+
+			for (local_path, full_path) in file_paths {
+				let gma_file_path = local_path.replace("\\", "/").to_lowercase();
+				if let Some(pattern) = ignore_patterns.iter().find(|pattern| library::gma::wildcard_match(pattern, &gma_file_path)) {
+					warn!("Leaving out <cyan>{}</>, it is ignored by the pattern \"{}\" of addon.json", gma_file_path, pattern);
+					excluded_files_count += 1;
+					continue;
+				}
+				if !library::gma::is_whitelisted(&gma_file_path) {
+					warn!("Leaving out <cyan>{}</>, gmad does not allow this file in a gma", gma_file_path);
+					excluded_files_count += 1;
+					continue;
+				}
+				gma_files.push((gma_file_path, full_path));
+			}
+
+		}
+	}
+
+	gma_files.sort();
+
+	if excluded_files_count > 0 {
+		warn!("<red>{}</> collected files are not part of the gma, they have to be distributed separately", excluded_files_count);
+	}
+
+	let map_name = vmf.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or(String::from("map"));
+	let description = library::gma::description_json(&format!("Content of {}", map_name), "map", &[]);
+
+	match library::gma::write(gma_path, &map_name, &description, &author, &gma_files) {
+		Ok(size) => success!("Packed <cyan>{}</> files into \"<green>{}</>\" (<cyan>{:.2}</> MB)", gma_files.len(), gma_path.display(), size as f64 / 1024.0 / 1024.0),
+		Err(err) => {
+			error!("Failed to create gma: {}", err.to_string());
+			return None;
+		}
+	}
+
+	return Some(());
+
+}
+
+// Top-level directories of the output path which the collected categories copy content into, e.g. "materials" or "sound".
+// The directories of all categories are included, even if they have no content in this run.
+pub fn output_content_subdirectories(collected_content: &Vec<(&str, &HashMap<String, SourceContentFile>, Option<&Vec<&str>>)>) -> Vec<String> {
+
+	let mut subdirectories: HashSet<String> = OUTPUT_CONTENT_SUBDIRECTORIES.iter().map(|subdirectory| subdirectory.to_string()).collect();
+
+	for (_, files, _) in collected_content {
+		for local_path in files.keys() {
+			if let Some((subdirectory, _)) = local_path.split_once('\\') {
+				subdirectories.insert(subdirectory.to_owned());
+			}
+		}
+	}
+
+	return subdirectories.into_iter().sorted().collect();
+
+}
+
+// Content directories --clean removes stale files from, only materials/ and models/ unless all categories are cleaned
+pub fn clean_subdirectories(content_subdirectories: &Vec<String>, all_categories: bool) -> Vec<String> {
+	return content_subdirectories
+		.iter()
+		.filter(|subdirectory| all_categories || CLEAN_SUBDIRECTORIES.contains(&subdirectory.as_str()))
+		.cloned()
+		.collect();
+}
+
+// Local paths (lowercase, with backslashes) of all collected files and their companion files
+pub fn collected_local_paths(collected_content: &Vec<(&str, &HashMap<String, SourceContentFile>, Option<&Vec<&str>>)>) -> HashSet<String> {
+
+	let mut local_paths: HashSet<String> = HashSet::new();
+
+	for (_, files, copy_additional_extensions) in collected_content {
+		for source_file in files.values() {
+			local_paths.insert(source_file.local_path.replace("/", "\\").to_lowercase());
+			for extension in copy_additional_extensions.unwrap_or(&vec!()) {
+				let companion_local_path = Path::new(&source_file.local_path).with_extension(extension);
+				local_paths.insert(companion_local_path.to_string_lossy().replace("/", "\\").to_lowercase());
+			}
+		}
+	}
+
+	return local_paths;
+
+}
+
+// Compiled maps are never collected, but are usually put next to their content (e.g. "maps/my_map.bsp" of an addon), so they are never stale
+pub fn find_stale_output_files(output_path: &PathBuf, subdirectories: &Vec<String>, collected_local_paths: &HashSet<String>) -> Vec<String> {
+
+	let mut stale_files: Vec<String> = vec!();
+
+	for subdirectory_path in output_subdirectory_paths(output_path, subdirectories) {
+
+		for entry in WalkDir::new(subdirectory_path) {
+
+			let entry = match entry {
+				Ok(entry) => entry,
+				Err(_) => continue,
+			};
+
+			if entry.file_type().is_dir() {
+				continue;
+			}
+
+			let local_path = match entry.path().strip_prefix(output_path) {
+				Ok(path) => path.to_string_lossy().to_string(),
+				Err(_) => continue,
+			};
+
+			let normalized_local_path = local_path.replace("/", "\\").to_lowercase();
+			if normalized_local_path.starts_with("maps\\") && normalized_local_path.ends_with(".bsp") {
+				continue;
+			}
+
+			if !collected_local_paths.contains(&normalized_local_path) {
+				stale_files.push(local_path);
+			}
+
+		}
+
+	}
+
+	stale_files.sort();
+
+	return stale_files;
+
+}
+
+// Existing directories of the output path with one of the names (case-insensitive, since the content may have been copied without --flatten-case)
+fn output_subdirectory_paths(output_path: &PathBuf, subdirectories: &Vec<String>) -> Vec<PathBuf> {
+
+	let entries = match fs::read_dir(output_path) {
+		Ok(entries) => entries,
+		Err(_) => return vec!(),
+	};
+
+	return entries
+		.filter_map(|entry| entry.ok())
+		.filter(|entry| entry.path().is_dir())
+		.filter(|entry| subdirectories.iter().any(|subdirectory| subdirectory.eq_ignore_ascii_case(&entry.file_name().to_string_lossy())))
+		.map(|entry| entry.path())
+		.sorted()
+		.collect();
+
+}
+
+// Removes directories which are empty after stale files were removed, including the subdirectories themselves
+pub fn remove_empty_directories(output_path: &PathBuf, subdirectories: &Vec<String>) {
+	for subdirectory_path in output_subdirectory_paths(output_path, subdirectories) {
+		for entry in WalkDir::new(subdirectory_path).contents_first(true).into_iter().filter_map(|entry| entry.ok()) {
+			if entry.file_type().is_dir() {
+				// Fails for directories which are not empty
+				let _ = fs::remove_dir(entry.path());
+			}
+		}
+	}
+}
+
+pub struct CopiedFile {
+	pub local_path: String,
+	pub output_path: PathBuf,
+	pub size: u64,
+	pub sha256: Option<String>,
+	// Already copied completely by an interrupted run (see --resume)
+	pub resumed: bool,
+}
+
+// Splits the collected content into chunks of approximately chunk_size bytes, e.g. for the size limit of Workshop addons.
+// A model is kept together with its companion files, its materials and their textures (a material is kept together with its textures),
+// so files which are used by models in multiple chunks are part of each of these chunks. A chunk is larger than chunk_size if a single model with its
+// content is. Returns the size of every chunk and its files, with one map of files for every category of collected_content.
+pub fn chunk_collected_content(collected_content: &Vec<(&str, &HashMap<String, SourceContentFile>, Option<&Vec<&str>>)>, chunk_size: u64, source_files: &HashMap<String, SourceContentFile>, open_fs: &OpenFileSystem) -> Vec<(u64, Vec<HashMap<String, SourceContentFile>>)> {
+
+	let category_index = |name: &str| collected_content.iter().position(|(category, _, _)| *category == name);
+	let (models_index, materials_index, textures_index) = (category_index("models"), category_index("materials"), category_index("textures"));
+
+	let file_size = |index: usize, source_file: &SourceContentFile| -> u64 {
+		let mut size = fs::metadata(&source_file.full_path).map(|metadata| metadata.len()).unwrap_or(0);
+		for extension in collected_content[index].2.unwrap_or(&vec!()) {
+			size += fs::metadata(Path::new(&source_file.full_path).with_extension(extension)).map(|metadata| metadata.len()).unwrap_or(0);
+		}
+		return size;
+	};
+
+	// A material and the collected textures it uses
+	let material_unit = |material_source_path: &String, material_source_file: &SourceContentFile| -> Vec<(usize, String, SourceContentFile)> {
+
+		let materials_index = match materials_index {
+			Some(index) if collected_content[index].1.contains_key(material_source_path) => index,
+			_ => return vec!(),
+		};
+
+		let mut unit = vec![(materials_index, material_source_path.to_owned(), material_source_file.to_owned())];
+
+		if let (Some(textures_index), true) = (textures_index, material_source_path.ends_with(".vmt")) {
+			if let Ok(material_data) = read_material_data(&material_source_file.full_path, source_files, open_fs) {
+				for (texture_source_path, texture_source_file) in material_data.used_textures.into_iter().sorted_by(|(a, _), (b, _)| a.cmp(b)) {
+					if collected_content[textures_index].1.contains_key(&texture_source_path) {
+						unit.push((textures_index, texture_source_path, texture_source_file));
+					}
+				}
+			}
+		}
+
+		return unit;
+
+	};
+
+	// Files which have to end up in the same chunk
+	let mut units: Vec<Vec<(usize, String, SourceContentFile)>> = vec!();
+
+	if let Some(models_index) = models_index {
+		for (model_source_path, model_source_file) in collected_content[models_index].1.iter().sorted_by_key(|(source_path, _)| *source_path) {
+			let mut unit = vec![(models_index, model_source_path.to_owned(), model_source_file.to_owned())];
+			let (model_materials, _) = collect_model_materials(model_source_file, source_files, open_fs, false);
+			for (material_source_path, material_source_file) in model_materials.iter().sorted_by_key(|(source_path, _)| *source_path) {
+				unit.extend(material_unit(material_source_path, material_source_file));
+			}
+			units.push(unit);
+		}
+	}
+
+	let mut assigned: HashSet<(usize, String)> = units.iter().flatten().map(|(index, source_path, _)| (*index, source_path.to_owned())).collect();
+
+	if let Some(materials_index) = materials_index {
+		for (material_source_path, material_source_file) in collected_content[materials_index].1.iter().sorted_by_key(|(source_path, _)| *source_path) {
+			if assigned.contains(&(materials_index, material_source_path.to_owned())) {
+				continue;
+			}
+			let unit = material_unit(material_source_path, material_source_file);
+			assigned.extend(unit.iter().map(|(index, source_path, _)| (*index, source_path.to_owned())));
+			units.push(unit);
+		}
+	}
+
+	// All remaining files (e.g. sounds) can be placed in any chunk
+	for (index, (_, files, _)) in collected_content.iter().enumerate() {
+		for (source_path, source_file) in files.iter().sorted_by_key(|(source_path, _)| *source_path) {
+			if !assigned.contains(&(index, source_path.to_owned())) {
+				units.push(vec![(index, source_path.to_owned(), source_file.to_owned())]);
+			}
+		}
+	}
+
+	let mut chunks: Vec<(u64, Vec<HashMap<String, SourceContentFile>>)> = vec!();
+
+	for unit in units {
+
+		let unit_size: u64 = unit.iter().map(|(index, _, source_file)| file_size(*index, source_file)).sum();
+
+		let new_chunk = match chunks.last() {
+			Some((size, _)) => *size > 0 && size + unit_size > chunk_size,
+			None => true,
+		};
+
+		if new_chunk {
+			chunks.push((0, collected_content.iter().map(|_| HashMap::new()).collect()));
+		}
+
+		let (size, files) = chunks.last_mut().unwrap();
+		for (index, source_path, source_file) in unit {
+			let source_file_size = file_size(index, &source_file);
+			if files[index].insert(source_path, source_file).is_none() {
+				*size += source_file_size;
+			}
+		}
+
+	}
+
+	return chunks;
+
+}
+
+// Fails early if the volume of the output path has less free space than the collected content (including companion files) needs,
+// instead of filling the disk while copying. Files which already exist in the output path are counted as well, so the estimate is an upper bound.
+pub fn check_output_space(collected_content: &Vec<(&str, &HashMap<String, SourceContentFile>, Option<&Vec<&str>>)>, output_path: &Path) -> Result<(), SimpleError> {
+
+	let required_size: u64 = collected_files_with_companions(collected_content)
+		.iter()
+		.filter_map(|source_file| fs::metadata(&source_file.full_path).ok())
+		.map(|metadata| metadata.len())
+		.sum();
+
+	info!("Estimated size of the collected content: <cyan>{}</>", format_size(required_size));
+
+	let available_size = match available_space(output_path) {
+		Ok(available_size) => available_size,
+		Err(err) => {
+			warn!("Failed to get the free space of \"{}\", skipping the space check: {}", output_path.display(), err.to_string());
+			return Ok(());
+		}
+	};
+
+	if available_size < required_size {
+		bail!("Not enough free space for the collected content in \"{}\": {} required, {} available. Use --no-space-check to copy anyway.", output_path.display(), format_size(required_size), format_size(available_size));
+	}
+
+	return Ok(());
+
+}
+
+// Returns the collected files together with their companion files (e.g. the .vvd of a .mdl) which exist and are copied alongside them
+pub fn collected_files_with_companions(collected_content: &Vec<(&str, &HashMap<String, SourceContentFile>, Option<&Vec<&str>>)>) -> Vec<SourceContentFile> {
+
+	let mut files: Vec<SourceContentFile> = vec!();
+
+	for (_, source_files, copy_additional_extensions) in collected_content {
+		for (_, source_file) in source_files.iter() {
+
+			files.push(source_file.to_owned());
+
+			if let Some(copy_additional_extensions) = copy_additional_extensions {
+				for extension in copy_additional_extensions.iter() {
+					let companion_full_path = Path::new(&source_file.full_path).with_extension(extension);
+					if companion_full_path.is_file() {
+						files.push(SourceContentFile {
+							full_path: companion_full_path.to_string_lossy().to_string(),
+							local_path: Path::new(&source_file.local_path).with_extension(extension).to_string_lossy().to_string(),
+						});
+					}
+				}
+			}
+
+		}
+	}
+
+	return files;
+
+}
+
+// Copies the files to the output path and returns all written files
+pub fn copy_files_to_output(source_files: &HashMap<String, SourceContentFile>, output_path: &PathBuf, copy_additional_extensions: Option<&Vec<&str>>, flatten_case: bool, compute_hashes: bool, symlink: bool, resume: bool) -> Vec<CopiedFile> {
+
+	let mut output_files: Vec<CopiedFile> = vec!();
+
+	// Falls back to copying for all remaining files once creating a symlink failed
+	let mut symlink = symlink;
+
+	for (_, source_file) in source_files {
+
+		// Only the destination is lowercased, the source file is still read from its original path
+		let output_file_path = match flatten_case {
+			true => output_path.join(source_file.local_path.to_lowercase()),
+			false => output_path.join(&source_file.local_path),
+		};
+		let output_file_path = extended_length_path(&output_file_path);
+		let output_file_dir_path = match output_file_path.parent() {
+			Some(path) => path,
+			None => {
+				warn!("Failed to get parent directory of \"{}\"", output_file_path.display());
+				continue
+			}
+		};
+
+		match fs::create_dir_all(&output_file_dir_path) {
+			Ok(_) => {
+
+				let source_file_path = Path::new(&source_file.full_path);
+
+				match copy_or_resume_file(&source_file_path, &output_file_path, compute_hashes, &mut symlink, resume) {
+					Ok((size, sha256, resumed)) => output_files.push(CopiedFile { local_path: source_file.local_path.to_owned(), output_path: output_file_path.to_owned(), size, sha256, resumed }),
+					Err(err) => {
+						// Companion files are useless without the base file (e.g. .vvd without .mdl)
+						warn!("Failed to copy \"{}\" to \"{}\": {}{}", source_file.full_path, output_file_path.display(), err.to_string(), path_error_hint(&err));
+						continue;
+					}
+				}
+
+				if let Some(copy_additional_extensions) = copy_additional_extensions {
+					for extension in copy_additional_extensions {
+						let source_file_path_ext = source_file_path.with_extension(extension);
+						let output_file_path_ext = output_file_path.with_extension(extension);
+
+						// Companion files may legitimately not exist (e.g. models without a .phy)
+						if !source_file_path_ext.is_file() {
+							continue;
+						}
+
+						match copy_or_resume_file(&source_file_path_ext, &output_file_path_ext, compute_hashes, &mut symlink, resume) {
+							Ok((size, sha256, resumed)) => {
+								let local_path = Path::new(&source_file.local_path).with_extension(extension).to_string_lossy().to_string();
+								output_files.push(CopiedFile { local_path, output_path: output_file_path_ext, size, sha256, resumed });
+							},
+							Err(err) => warn!("Failed to copy \"{}\" to \"{}\": {}{}", source_file_path_ext.display(), output_file_path_ext.display(), err.to_string(), path_error_hint(&err))
+						}
+					}
+				}
+
+			},
+			Err(err) => warn!("Failed to create directory \"{}\": {}{}", output_file_dir_path.display(), err.to_string(), path_error_hint(&err))
+		}
+
+	}
+
+	return output_files;
+
+}
+
+// Paths longer than MAX_PATH (260 characters) can not be written on Windows unless long path support is enabled for the whole system.
+// Absolute paths with the "\\?\" prefix are passed to the file system as they are and may always be up to ~32767 characters long.
+pub fn extended_length_path(path: &Path) -> PathBuf {
+
+	if !cfg!(windows) || path.as_os_str().len() < WINDOWS_MAX_PATH || path.to_string_lossy().starts_with("\\\\?\\") {
+		return path.to_owned();
+	}
+
+	// The prefix disables the normalization of the path, so it has to be absolute and must not contain "/" or ".."
+	let absolute_path = match std::path::absolute(path) {
+		Ok(path) => path.to_string_lossy().to_string(),
+		Err(_) => return path.to_owned(),
+	};
+
+	return match absolute_path.strip_prefix("\\\\") {
+		// Network share (\\server\share\...)
+		Some(unc_path) => PathBuf::from(format!("\\\\?\\UNC\\{}", unc_path)),
+		None => PathBuf::from(format!("\\\\?\\{}", absolute_path)),
+	};
+
+}
+
+// Windows reports paths which are still too long (e.g. a single directory name over 255 characters) with a generic error
+fn path_error_hint(err: &std::io::Error) -> &'static str {
+
+	// ERROR_FILENAME_EXCED_RANGE
+	if cfg!(windows) && err.raw_os_error() == Some(206) {
+		return " (the path is too long, use a shorter output path or enable long path support in Windows via the LongPathsEnabled registry value)";
+	}
+
+	return "";
+
+}
+
+// Copies a file like copy_file_unlinked, unless resume is set and a previous run already copied it completely.
+// Returns the size, optionally the SHA-256 hash and whether the existing file was kept.
+fn copy_or_resume_file(source_file_path: &Path, output_file_path: &Path, hash: bool, symlink: &mut bool, resume: bool) -> std::io::Result<(u64, Option<String>, bool)> {
+
+	if resume {
+		if let Some(size) = completely_copied_size(source_file_path, output_file_path) {
+			let sha256 = match hash {
+				true => Some(hash_file(output_file_path)?.iter().map(|byte| format!("{:02x}", byte)).collect::<String>()),
+				false => None,
+			};
+			return Ok((size, sha256, true));
+		}
+	}
+
+	let (size, sha256) = copy_file_unlinked(source_file_path, output_file_path, hash, symlink)?;
+	return Ok((size, sha256, false));
+
+}
+
+// Returns the size of the output file if it is a regular file with the same size as the source file.
+// The file which was being written when a run was interrupted is usually smaller and is copied again.
+// Symlinks of a previous run with --relative-symlink are not regular files and are replaced as well.
+fn completely_copied_size(source_file_path: &Path, output_file_path: &Path) -> Option<u64> {
+
+	let output_metadata = fs::symlink_metadata(output_file_path).ok()?;
+	if !output_metadata.is_file() {
+		return None;
+	}
+
+	let source_size = fs::metadata(source_file_path).ok()?.len();
+	return match output_metadata.len() == source_size {
+		true => Some(source_size),
+		false => None,
+	};
+
+}
+
+// Copies a file, removing an existing destination first, and returns its size and optionally its SHA-256 hash.
+// fs::copy writes into the existing file, which would also change all other names of a hardlinked file (see --dedupe-output)
+// or the source file of a symlink created by a previous run with --relative-symlink.
+// If symlink is set, a symlink to the source file is created instead. If that is not permitted, symlink is unset and the file is copied.
+pub fn copy_file_unlinked(source_file_path: &Path, output_file_path: &Path, hash: bool, symlink: &mut bool) -> std::io::Result<(u64, Option<String>)> {
+
+	match fs::symlink_metadata(output_file_path) {
+		Ok(metadata) if !metadata.is_dir() => fs::remove_file(output_file_path)?,
+		_ => {},
+	}
+
+	if *symlink {
+		match create_symlink(source_file_path, output_file_path) {
+			Ok(_) => {
+				let size = fs::metadata(source_file_path)?.len();
+				let sha256 = match hash {
+					true => Some(hash_file(source_file_path)?.iter().map(|byte| format!("{:02x}", byte)).collect::<String>()),
+					false => None,
+				};
+				return Ok((size, sha256));
+			},
+			Err(err) => {
+				warn!("Failed to create symlink \"{}\", copying all remaining files instead: {}", output_file_path.display(), err.to_string());
+				*symlink = false;
+			}
+		}
+	}
+
+	if !hash {
+		return Ok((fs::copy(source_file_path, output_file_path)?, None));
+	}
+
+	// Hash while copying to avoid reading the file twice
+	let mut source_file = fs::File::open(source_file_path)?;
+	let mut output_file = fs::File::create(output_file_path)?;
+	let mut hasher = Sha256::new();
+	let mut buffer = [0u8; 64 * 1024];
+	let mut size: u64 = 0;
+
+	loop {
+		let read = std::io::Read::read(&mut source_file, &mut buffer)?;
+		if read == 0 {
+			break;
+		}
+		std::io::Write::write_all(&mut output_file, &buffer[..read])?;
+		hasher.update(&buffer[..read]);
+		size += read as u64;
+	}
+
+	let sha256 = hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect::<String>();
+
+	return Ok((size, Some(sha256)));
+
+}
+
+// Symlinks point at the source path relative to the directory of the link, so they stay valid if the output and source paths are moved together.
+// Paths without a relative path between them (e.g. on different drives on Windows) are linked absolutely.
+fn create_symlink(source_file_path: &Path, output_file_path: &Path) -> std::io::Result<()> {
+
+	let source_file_path = fs::canonicalize(source_file_path)?;
+
+	let source_file_path = match output_file_path.parent().map(fs::canonicalize) {
+		Some(Ok(output_directory)) => pathdiff::diff_paths(&source_file_path, output_directory).unwrap_or(source_file_path),
+		_ => source_file_path,
+	};
+
+	#[cfg(windows)]
+	return std::os::windows::fs::symlink_file(source_file_path, output_file_path);
+
+	#[cfg(not(windows))]
+	return std::os::unix::fs::symlink(source_file_path, output_file_path);
+
+}
+
+// Free space of the file system containing the path which is available to the current user
+#[cfg(windows)]
+pub fn available_space(path: &Path) -> std::io::Result<u64> {
+
+	use std::os::windows::ffi::OsStrExt;
+
+	let path: Vec<u16> = path.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+	let mut available_size: u64 = 0;
+
+	if unsafe { windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW(path.as_ptr(), &mut available_size, std::ptr::null_mut(), std::ptr::null_mut()) } == 0 {
+		return Err(std::io::Error::last_os_error());
+	}
+
+	return Ok(available_size);
+
+}
+
+#[cfg(not(windows))]
+pub fn available_space(path: &Path) -> std::io::Result<u64> {
+
+	use std::os::unix::ffi::OsStrExt;
+
+	let path = match std::ffi::CString::new(path.as_os_str().as_bytes()) {
+		Ok(path) => path,
+		Err(err) => return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, err)),
+	};
+
+	let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+	if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+		return Err(std::io::Error::last_os_error());
+	}
+
+	// The sizes of these types differ between platforms
+	let available_blocks: libc::fsblkcnt_t = stat.f_bavail;
+	let block_size: libc::c_ulong = stat.f_frsize;
+
+	return Ok(available_blocks as u64 * block_size as u64);
+
+}
+
+/// Content a map uses, written by --manifest
+#[derive(Serialize, JsonSchema)]
+#[schemars(title = "gmod-developer-cli content manifest", deny_unknown_fields)]
+pub struct ContentManifest {
+	/// Path to the vmf the content was collected for
+	pub vmf: String,
+	/// All copied files, sorted by path
+	pub files: Vec<ManifestFile>,
+	/// All content which was not found in the source paths or the game files
+	pub missing: Vec<ManifestMissingFile>,
+}
+
+#[derive(Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct ManifestFile {
+	/// Content category, e.g. "materials" or "models"
+	pub category: String,
+	/// Path of the file local to the source / output path, with "/" separators
+	pub path: String,
+	/// Size of the file in bytes
+	pub size: u64,
+	/// SHA-256 hash of the file (only with --manifest-hashes)
+	#[serde(skip_serializing_if = "Option::is_none")]
+	#[schemars(regex(pattern = "^[0-9a-f]{64}$"))]
+	pub sha256: Option<String>,
+}
+
+#[derive(Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct ManifestMissingFile {
+	/// Content category, e.g. "materials" or "models"
+	pub category: String,
+	/// Expected path of the file local to a source path, with "/" separators
+	pub path: String,
+	/// What references the file
+	pub reason: String,
+}
+
+// JSON Schema of the manifest written by content_manifest_json (see --print-schema)
+pub fn manifest_json_schema() -> String {
+	let schema = schemars::schema_for!(ContentManifest);
+	return serde_json::to_string_pretty(&schema).unwrap();
+}
+
+pub fn content_manifest_json(vmf: &PathBuf, copied_files: &Vec<(&str, CopiedFile)>, missing_content: &Vec<(&str, &HashMap<String, String>)>) -> String {
+
+	let files: Vec<ManifestFile> = copied_files
+		.iter()
+		.sorted_by_key(|(_, copied_file)| copied_file.local_path.to_lowercase())
+		.map(|(category, copied_file)| ManifestFile {
+			category: category.to_string(),
+			path: copied_file.local_path.replace("\\", "/"),
+			size: copied_file.size,
+			sha256: copied_file.sha256.to_owned(),
+		})
+		.collect();
+
+	let missing: Vec<ManifestMissingFile> = missing_content
+		.iter()
+		.flat_map(|(category, files)| files.iter().sorted().map(move |(path, reason)| ManifestMissingFile {
+			category: category.to_string(),
+			path: path.replace("\\", "/"),
+			reason: reason.to_owned(),
+		}))
+		.collect();
+
+	let manifest = ContentManifest {
+		vmf: vmf.to_string_lossy().to_string(),
+		files,
+		missing,
+	};
+
+	return format!("{}\n", serde_json::to_string_pretty(&manifest).unwrap());
+
+}
+
+// Human-readable log of a collection, written into the output directory by --write-log
+pub fn content_log_text(vmf: &PathBuf, source_paths: &Vec<PathBuf>, source_files_count: usize, categories: &Vec<SummaryCategory>, copied_files: &Vec<(&str, CopiedFile)>, missing_content: &Vec<(&str, &HashMap<String, String>)>) -> String {
+
+	let mut lines: Vec<String> = vec!();
+
+	lines.push(String::from("gmod-developer-cli content collector log"));
+	lines.push(format!("Date: {}", format_timestamp_utc(SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0))));
+	lines.push(format!("Map: {}", vmf.display()));
+	lines.push(format!("Command: gcli {}", std::env::args().skip(1).join(" ")));
+	lines.push(String::from("Source paths:"));
+	for source_path in source_paths {
+		lines.push(format!("\t{}", source_path.display()));
+	}
+
+	lines.push(String::new());
+	lines.push(String::from("SUMMARY"));
+	lines.push(format!("\tSource files: {}", source_files_count));
+	for category in categories {
+		lines.push(format!("\t{}: Found {}; Missing {}; Size {}", category.name, category.found, category.missing, format_size(category.size)));
+	}
+
+	lines.push(String::new());
+	lines.push(format!("COPIED ({} files, {})", copied_files.len(), format_size(copied_files.iter().map(|(_, copied_file)| copied_file.size).sum())));
+	for (category, copied_file) in copied_files.iter().sorted_by_key(|(_, copied_file)| copied_file.local_path.to_lowercase()) {
+		lines.push(format!("\t+ {} ({}, {})", copied_file.local_path.replace("\\", "/"), category, format_size(copied_file.size)));
+	}
+
+	let missing_count: usize = missing_content.iter().map(|(_, files)| files.len()).sum();
+
+	lines.push(String::new());
+	lines.push(format!("MISSING ({} files)", missing_count));
+	for (category, files) in missing_content {
+		for (path, reason) in files.iter().sorted() {
+			lines.push(format!("\t- {} ({}): {}", path.replace("\\", "/"), category, reason));
+		}
+	}
+
+	return lines.join("\n") + "\n";
+
+}
+
+// Formats seconds since the unix epoch as "YYYY-MM-DD HH:MM:SS UTC"
+pub fn format_timestamp_utc(timestamp: u64) -> String {
+
+	let days = (timestamp / 86_400) as i64;
+	let seconds_of_day = timestamp % 86_400;
+
+	// Converts days since 1970-01-01 to a civil date (https://howardhinnant.github.io/date_algorithms.html#civil_from_days)
+	let z = days + 719_468;
+	let era = z.div_euclid(146_097);
+	let day_of_era = z.rem_euclid(146_097);
+	let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+	let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+	let month_index = (5 * day_of_year + 2) / 153;
+	let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+	let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+	let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+	return format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC", year, month, day, seconds_of_day / 3_600, seconds_of_day % 3_600 / 60, seconds_of_day % 60);
+
+}
+
+// Replaces files with identical content by hardlinks to the first of them and returns the number of replaced files and saved bytes
+pub fn dedupe_output_files(output_files: &Vec<PathBuf>) -> Result<(usize, u64), SimpleError> {
+
+	// Only files with the same size can be identical, so only those need to be hashed
+	let mut files_by_size: HashMap<u64, Vec<&PathBuf>> = HashMap::new();
+	for output_file in output_files.iter().unique() {
+		match fs::metadata(output_file) {
+			Ok(metadata) => files_by_size.entry(metadata.len()).or_default().push(output_file),
+			Err(err) => warn!("Failed to read metadata of \"{}\": {}", output_file.display(), err.to_string()),
+		}
+	}
+
+	let mut linked_files = 0;
+	let mut saved_bytes: u64 = 0;
+
+	for (size, files) in files_by_size.iter().sorted_by_key(|(size, _)| **size) {
+
+		if *size == 0 || files.len() < 2 {
+			continue;
+		}
+
+		let mut files_by_hash: HashMap<Vec<u8>, &PathBuf> = HashMap::new();
+		for file in files.iter().sorted() {
+
+			let hash = match hash_file(file) {
+				Ok(hash) => hash,
+				Err(err) => {
+					warn!("Failed to hash \"{}\": {}", file.display(), err.to_string());
+					continue;
+				}
+			};
+
+			let original = match files_by_hash.get(&hash) {
+				Some(original) => *original,
+				None => {
+					files_by_hash.insert(hash, file);
+					continue;
+				}
+			};
+
+			// Link to a temporary path first so the duplicate is only replaced if linking works
+			let temporary_path = file.with_extension("gcli-dedupe");
+			if let Err(err) = fs::hard_link(original, &temporary_path) {
+				bail!("Failed to create hardlink \"{}\" (hardlinks may not be supported by the file system of the output path): {}", temporary_path.display(), err.to_string());
+			}
+
+			if let Err(err) = fs::rename(&temporary_path, file) {
+				let _ = fs::remove_file(&temporary_path);
+				bail!("Failed to replace \"{}\" with hardlink: {}", file.display(), err.to_string());
+			}
+
+			linked_files += 1;
+			saved_bytes += size;
+
+		}
+
+	}
+
+	return Ok((linked_files, saved_bytes));
+
+}
+
+pub fn hash_file(path: &Path) -> std::io::Result<Vec<u8>> {
+	let mut hasher = Sha256::new();
+	std::io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+	return Ok(hasher.finalize().to_vec());
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+	use super::super::test_utils::*;
+
+	#[test]
+	fn stale_files_are_found_in_the_directories_of_all_categories() {
+
+		let output_path = test_directory("stale_files");
+		write_files(&output_path, &[
+			("materials/used.vmt", ""),
+			("materials/old.vmt", ""),
+			("models/old.mdl", ""),
+			("sound/old.wav", ""),
+			("scripts/soundscapes_old.txt", ""),
+			("particles/old.pcf", ""),
+			("maps/my_map.bsp", ""),
+			("maps/old.nav", ""),
+			("lua/autorun/keep.lua", ""),
+		]);
+
+		let used_materials: HashMap<String, SourceContentFile> = HashMap::from([source_file(&output_path, "materials/used.vmt")]);
+		let collected_content: Vec<(&str, &HashMap<String, SourceContentFile>, Option<&Vec<&str>>)> = vec![("materials", &used_materials, None)];
+
+		let subdirectories = output_content_subdirectories(&collected_content);
+		for subdirectory in ["materials", "maps", "models", "particles", "scripts", "sound"] {
+			assert!(subdirectories.contains(&subdirectory.to_string()), "{}", subdirectory);
+		}
+
+		// --clean only removes stale materials and models, unless all categories are cleaned
+		assert_eq!(clean_subdirectories(&subdirectories, false), vec!["materials", "models"]);
+		assert_eq!(clean_subdirectories(&subdirectories, true), subdirectories);
+		let stale_files: Vec<String> = find_stale_output_files(&output_path, &clean_subdirectories(&subdirectories, false), &collected_local_paths(&collected_content))
+			.iter()
+			.map(|stale_file| stale_file.replace("\\", "/"))
+			.collect();
+		assert_eq!(stale_files, vec!["materials/old.vmt", "models/old.mdl"]);
+
+		let stale_files: Vec<String> = find_stale_output_files(&output_path, &subdirectories, &collected_local_paths(&collected_content))
+			.iter()
+			.map(|stale_file| stale_file.replace("\\", "/"))
+			.collect();
+
+		assert_eq!(stale_files, vec!["maps/old.nav", "materials/old.vmt", "models/old.mdl", "particles/old.pcf", "scripts/soundscapes_old.txt", "sound/old.wav"]);
+
+		// Empty directories are removed once their stale files are gone, others are kept
+		for stale_file in &stale_files {
+			fs::remove_file(output_path.join(stale_file)).unwrap();
+		}
+		remove_empty_directories(&output_path, &subdirectories);
+		assert!(!output_path.join("sound").exists());
+		assert!(output_path.join("materials/used.vmt").is_file());
+		assert!(output_path.join("maps/my_map.bsp").is_file());
+		assert!(output_path.join("lua/autorun/keep.lua").is_file());
+
+		fs::remove_dir_all(&output_path).unwrap();
+
+	}
+
+	#[test]
+	fn manifest_lists_copied_and_missing_files() {
+
+		let copied_files = vec!(
+			("models", CopiedFile { local_path: String::from("models\\props\\crate.mdl"), output_path: PathBuf::new(), size: 120, sha256: None, resumed: false }),
+			("materials", CopiedFile { local_path: String::from("materials\\Brick\\wall.vmt"), output_path: PathBuf::new(), size: 64, sha256: Some("ab".repeat(32)), resumed: false }),
+		);
+		let missing_materials = HashMap::from([(String::from("materials\\dev\\missing.vmt"), String::from("Used by brush \"wall\""))]);
+		let missing_content: Vec<(&str, &HashMap<String, String>)> = vec!(("materials", &missing_materials));
+
+		let manifest = content_manifest_json(&PathBuf::from("maps/test.vmf"), &copied_files, &missing_content);
+
+		let manifest_value: serde_json::Value = serde_json::from_str(&manifest).unwrap();
+		assert_eq!(manifest_value, serde_json::json!({
+			"vmf": "maps/test.vmf",
+			"files": [
+				{"category": "materials", "path": "materials/Brick/wall.vmt", "size": 64, "sha256": "ab".repeat(32)},
+				{"category": "models", "path": "models/props/crate.mdl", "size": 120},
+			],
+			"missing": [
+				{"category": "materials", "path": "materials/dev/missing.vmt", "reason": "Used by brush \"wall\""},
+			],
+		}));
+
+	}
+
+	#[test]
+	fn manifest_schema_describes_all_fields() {
+
+		let schema: serde_json::Value = serde_json::from_str(&manifest_json_schema()).unwrap();
+
+		assert_eq!(schema["title"], "gmod-developer-cli content manifest");
+		assert_eq!(schema["required"], serde_json::json!(["files", "missing", "vmf"]));
+		assert_eq!(schema["definitions"]["ManifestFile"]["required"], serde_json::json!(["category", "path", "size"]));
+		assert_eq!(schema["definitions"]["ManifestMissingFile"]["required"], serde_json::json!(["category", "path", "reason"]));
+
+		let sha256 = &schema["definitions"]["ManifestFile"]["properties"]["sha256"];
+		assert_eq!(sha256["description"], "SHA-256 hash of the file (only with --manifest-hashes)");
+		assert_eq!(sha256["pattern"], "^[0-9a-f]{64}$");
+
+	}
+
+	#[test]
+	fn relative_symlinks_point_at_the_source_files() {
+
+		let directory = test_directory("relative_symlink");
+		write_files(&directory, &[("content/materials/brick/wall.vmt", "LightmappedGeneric { }")]);
+
+		// Local paths of the source files use the separators of the OS
+		let local_path = PathBuf::from_iter(["materials", "brick", "wall.vmt"]).to_string_lossy().to_string();
+		let source_files: HashMap<String, SourceContentFile> = HashMap::from([(
+			local_path.to_owned(),
+			SourceContentFile { full_path: directory.join("content").join(&local_path).to_string_lossy().to_string(), local_path },
+		)]);
+		let output_path = directory.join("output");
+		fs::create_dir_all(&output_path).unwrap();
+
+		let copied_files = copy_files_to_output(&source_files, &output_path, None, false, false, true, false);
+		assert_eq!(copied_files.len(), 1);
+
+		// Creating symlinks is not permitted everywhere (e.g. on Windows without developer mode), then the file is copied instead
+		let output_file_path = output_path.join("materials/brick/wall.vmt");
+		if let Ok(link) = fs::read_link(&output_file_path) {
+			assert_eq!(link, PathBuf::from_iter(["..", "..", "..", "content", "materials", "brick", "wall.vmt"]));
+		}
+		assert_eq!(fs::read_to_string(&output_file_path).unwrap(), "LightmappedGeneric { }");
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn available_space_of_existing_directory() {
+		assert!(available_space(&std::env::temp_dir()).unwrap() > 0);
+		assert!(available_space(Path::new("/gcli/does/not/exist")).is_err());
+	}
+
+	#[test]
+	fn content_subdirectories_include_directories_of_collected_files() {
+
+		let directory = test_directory("content_subdirectories");
+		let used_files: HashMap<String, SourceContentFile> = HashMap::from([source_file(&directory, "custom/file.txt"), source_file(&directory, "root_file.txt")]);
+		let collected_content: Vec<(&str, &HashMap<String, SourceContentFile>, Option<&Vec<&str>>)> = vec![("files", &used_files, None)];
+
+		let subdirectories = output_content_subdirectories(&collected_content);
+		assert!(subdirectories.contains(&String::from("custom")));
+		assert!(!subdirectories.iter().any(|subdirectory| subdirectory.ends_with(".txt")));
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn flatten_case_lowercases_only_the_destination() {
+
+		let source_path = test_directory("flatten_case_source");
+		let output_path = test_directory("flatten_case_output");
+		write_files(&source_path, &[("Materials/Brick/BrickWall01.VMT", "LightmappedGeneric { }")]);
+
+		let source_files: HashMap<String, SourceContentFile> = HashMap::from([source_file(&source_path, "Materials/Brick/BrickWall01.VMT")]);
+		let copied_files = copy_files_to_output(&source_files, &output_path, None, true, false, false, false);
+
+		assert_eq!(copied_files.len(), 1);
+		assert_eq!(copied_files[0].output_path, output_path.join("materials\\brick\\brickwall01.vmt"));
+		assert_eq!(fs::read_to_string(&copied_files[0].output_path).unwrap(), "LightmappedGeneric { }");
+
+		// The manifest keeps the original name of the source file
+		assert_eq!(copied_files[0].local_path, "Materials\\Brick\\BrickWall01.VMT");
+
+		fs::remove_dir_all(&source_path).unwrap();
+		fs::remove_dir_all(&output_path).unwrap();
+
+	}
+
+	#[test]
+	fn companion_files_are_copied_if_present() {
+
+		let source_path = test_directory("companions_source");
+		let output_path = test_directory("companions_output");
+		write_files(&source_path, &[
+			("models/props/barrel.mdl", "MDL"),
+			("models/props/barrel.vvd", "VVD"),
+			("models/props/barrel.phy", "PHY"),
+			("models/props/sign.mdl", "MDL"),
+			("models/props/sign.vvd", "VVD"),
+		]);
+
+		let models: HashMap<String, SourceContentFile> = HashMap::from([
+			source_file(&source_path, "models/props/barrel.mdl"),
+			source_file(&source_path, "models/props/sign.mdl"),
+		]);
+		let companion_extensions = vec!("vvd", "phy");
+
+		// The sign has no .phy, which is not a copy failure
+		let mut copied_local_paths: Vec<String> = copy_files_to_output(&models, &output_path, Some(&companion_extensions), false, false, false, false)
+			.into_iter()
+			.map(|copied_file| copied_file.local_path)
+			.collect();
+		copied_local_paths.sort();
+
+		assert_eq!(copied_local_paths, vec!("models\\props\\barrel.mdl", "models\\props\\barrel.phy", "models\\props\\barrel.vvd", "models\\props\\sign.mdl", "models\\props\\sign.vvd"));
+		assert!(!output_path.join("models\\props\\sign.phy").exists());
+
+		fs::remove_dir_all(&source_path).unwrap();
+		fs::remove_dir_all(&output_path).unwrap();
+
+	}
+
+	#[test]
+	#[cfg(windows)]
+	fn files_are_copied_to_paths_over_max_path() {
+
+		let source_path = test_directory("long_path_source");
+		let output_path = test_directory("long_path_output");
+		write_files(&source_path, &[("materials/mymap/wall.vmt", "LightmappedGeneric { }")]);
+
+		// Deep enough that the output file path exceeds MAX_PATH
+		let deep_local_path = format!("materials\\{}\\wall.vmt", ["very_long_directory_name_of_a_content_pack"; 8].join("\\"));
+		let source_files: HashMap<String, SourceContentFile> = HashMap::from([(deep_local_path.to_owned(), SourceContentFile {
+			full_path: source_path.join("materials/mymap/wall.vmt").to_string_lossy().to_string(),
+			local_path: deep_local_path.to_owned(),
+		})]);
+		assert!(output_path.join(&deep_local_path).as_os_str().len() > WINDOWS_MAX_PATH);
+
+		let copied_files = copy_files_to_output(&source_files, &output_path, None, false, false, false, false);
+
+		assert_eq!(copied_files.len(), 1);
+		assert!(copied_files[0].output_path.to_string_lossy().starts_with("\\\\?\\"));
+		assert_eq!(fs::read_to_string(&copied_files[0].output_path).unwrap(), "LightmappedGeneric { }");
+
+		fs::remove_dir_all(&source_path).unwrap();
+		fs::remove_dir_all(&output_path).unwrap();
+
+	}
+
+	#[test]
+	fn short_paths_are_not_extended() {
+		let path = std::env::temp_dir().join("materials").join("wall.vmt");
+		assert_eq!(extended_length_path(&path), path);
+	}
+
+	#[test]
+	fn resume_copies_only_missing_and_partial_files() {
+
+		let source_path = test_directory("resume_source");
+		let output_path = test_directory("resume_output");
+		write_files(&source_path, &[
+			("maps/rp_harbor.bsp", "VBSP map which was copied completely"),
+			("models/harbor/crane.mdl", "IDST model which was being copied"),
+			("sound/harbor/gulls.wav", "RIFF sound which was never copied"),
+		]);
+
+		// State of the output after an interrupted run, the complete file is marked to tell whether it is copied again
+		write_files(&output_path, &[
+			("maps/rp_harbor.bsp", "VBSP map which was copied COMPLETELY"),
+			("models/harbor/crane.mdl", "IDST model wh"),
+		]);
+
+		// Local paths of the source files use the separators of the OS
+		let source_files: HashMap<String, SourceContentFile> = ["maps/rp_harbor.bsp", "models/harbor/crane.mdl", "sound/harbor/gulls.wav"]
+			.iter()
+			.map(|local_path| {
+				let local_path = PathBuf::from_iter(local_path.split('/')).to_string_lossy().to_string();
+				return (local_path.to_lowercase(), SourceContentFile { full_path: source_path.join(&local_path).to_string_lossy().to_string(), local_path });
+			})
+			.collect();
+		let copied_files = copy_files_to_output(&source_files, &output_path, None, true, false, false, true);
+
+		let resumed: Vec<(PathBuf, bool)> = copied_files.iter().map(|copied_file| (PathBuf::from(&copied_file.local_path), copied_file.resumed)).sorted().collect();
+		assert_eq!(resumed, vec!(
+			(PathBuf::from_iter(["maps", "rp_harbor.bsp"]), true),
+			(PathBuf::from_iter(["models", "harbor", "crane.mdl"]), false),
+			(PathBuf::from_iter(["sound", "harbor", "gulls.wav"]), false),
+		));
+
+		assert_eq!(fs::read_to_string(output_path.join("maps/rp_harbor.bsp")).unwrap(), "VBSP map which was copied COMPLETELY");
+		assert_eq!(fs::read_to_string(output_path.join("models/harbor/crane.mdl")).unwrap(), "IDST model which was being copied");
+		assert_eq!(fs::read_to_string(output_path.join("sound/harbor/gulls.wav")).unwrap(), "RIFF sound which was never copied");
+
+		fs::remove_dir_all(&source_path).unwrap();
+		fs::remove_dir_all(&output_path).unwrap();
+
+	}
+
+}
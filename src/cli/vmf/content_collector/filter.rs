@@ -0,0 +1,519 @@
+use std::{collections::{HashMap, HashSet}, path::Path};
+use crate::library;
+use crate::library::collector::{SourceContentFile, SourceMaterialData};
+use crate::library::log::{info, success, warn};
+use plumber_core::fs::OpenFileSystem;
+use itertools::Itertools;
+use rayon::prelude::*;
+use super::{CollectContext, ResolvedContent, SoundscapeData, SummaryCategory, TEXTURE_SOURCE_EXTENSIONS};
+use super::report::{files_size, log_missing_files_hashmap};
+
+// Removes the missing content which is part of the game from every category, logs the remaining missing content and returns the summary of all categories
+pub fn filter_content(context: &CollectContext, content: &mut ResolvedContent) -> Vec<SummaryCategory> {
+
+	let options = context.options;
+
+	let models_summary = finish_category(context, "Models", "models", &content.used_models, &mut content.missing_models, Some(&context.model_companion_extensions), context.game_fs);
+
+	let materials_summary = finish_category(context, "Materials", "materials", &content.used_materials, &mut content.missing_materials, None, context.game_fs);
+
+	let textures_summary = match options.materials_only_vmt {
+		true => None,
+		false => Some(finish_textures(context, &mut content.materials_data)),
+	};
+
+	let soundscapes_summary = match content.used_soundscape_names.len() > 0 {
+		true => Some(finish_soundscapes(context, &mut content.soundscape_data)),
+		false => None,
+	};
+
+	let sounds_summary = match content.used_sounds.len() > 0 || content.missing_sounds.len() > 0 {
+		true => Some(finish_category(context, "Sounds", "sounds", &content.used_sounds, &mut content.missing_sounds, None, context.game_fs)),
+		false => None,
+	};
+
+	// Vehicle scripts and weapon scripts of the game (e.g. of the Half-Life 2 jeep and weapons) are found in the game files
+	let vehicle_scripts_summary = match content.used_vehicle_scripts.len() > 0 || content.missing_vehicle_scripts.len() > 0 {
+		true => Some(finish_category(context, "Vehicle scripts", "vehicle scripts", &content.used_vehicle_scripts, &mut content.missing_vehicle_scripts, None, context.game_fs)),
+		false => None,
+	};
+
+	let weapon_scripts_summary = match options.include_weapon_scripts {
+		true => Some(finish_category(context, "Weapon scripts", "weapon scripts", &content.used_weapon_scripts, &mut content.missing_weapon_scripts, None, context.game_fs)),
+		false => None,
+	};
+
+	let particles_summary = match content.used_particles.len() > 0 || content.missing_particles.len() > 0 {
+		true => Some(finish_category(context, "Particles", "particles", &content.used_particles, &mut content.missing_particles, None, context.game_fs)),
+		false => None,
+	};
+
+	// Scenes of the game are not looked up, since they are only part of the game's scenes.image
+	let scenes_summary = match options.include_scenes {
+		true => Some(finish_category(context, "Scenes", "scenes", &content.used_scenes, &mut content.missing_scenes, None, None)),
+		false => None,
+	};
+
+	// Layout files of the game (e.g. of the default VGUI panels) are not missing
+	let layout_files_summary = match options.scan_lua {
+		true => Some(finish_category(context, "GUI layout files", "GUI layout files", &content.used_layout_files, &mut content.missing_layout_files, None, context.game_fs)),
+		false => None,
+	};
+
+	// The navigation mesh and node graph are specific to the map, so they are never part of the game
+	let nav_files_summary = match options.include_nav {
+		true => Some(SummaryCategory { name: "Navigation / AI node graph", found: content.used_nav_files.len(), missing: content.missing_nav_files.len(), size: files_size(&content.used_nav_files, None) }),
+		false => None,
+	};
+
+	return [
+		Some(materials_summary),
+		Some(models_summary),
+		textures_summary,
+		soundscapes_summary,
+		sounds_summary,
+		vehicle_scripts_summary,
+		weapon_scripts_summary,
+		particles_summary,
+		scenes_summary,
+		layout_files_summary,
+		nav_files_summary,
+	]
+		.into_iter()
+		.flatten()
+		.collect();
+
+}
+
+// Removes the files found in the game (if a game file system is given) from the missing files, logs the missing files and returns the summary of the category
+fn finish_category(context: &CollectContext, name: &'static str, log_name: &str, used_files: &HashMap<String, SourceContentFile>, missing_files: &mut HashMap<String, String>, companion_extensions: Option<&Vec<&str>>, game_fs: Option<&OpenFileSystem>) -> SummaryCategory {
+
+	remove_game_content_logged(log_name, missing_files, game_fs, context.options.concurrency_limit, context.options.exclude_found_in_game_report);
+
+	if missing_files.len() > 0 {
+		log_missing_files_hashmap(log_name, missing_files);
+	} else if !context.options.summary_only_missing {
+		success!("<green>No {} missing in source files!</>", log_name);
+	}
+
+	return SummaryCategory { name, found: used_files.len(), missing: missing_files.len(), size: files_size(used_files, companion_extensions) };
+
+}
+
+// Resolves missing textures found in the game, as normal map variants or as uncompiled source images, logs the missing textures and returns the summary of the textures
+fn finish_textures(context: &CollectContext, used_materials_data: &mut SourceMaterialData) -> SummaryCategory {
+
+	// Find textures included in the game and remove them from missing_textures
+	remove_game_content_logged("textures", &mut used_materials_data.missing_textures, context.game_fs, context.options.concurrency_limit, context.options.exclude_found_in_game_report);
+
+	// Look for the naming variants of missing normal maps
+	if context.options.fuzzy_textures && used_materials_data.missing_normal_maps.len() > 0 {
+		let variant_textures = hashmap_resolve_normal_map_variants(&used_materials_data.missing_normal_maps, &mut used_materials_data.missing_textures, &mut used_materials_data.used_textures, context.source_files);
+		if variant_textures > 0 {
+			warn!("Collected <cyan>{}</> normal map variants under the names of the missing normal maps", variant_textures);
+		}
+	}
+
+	// Look for source images of textures that were never compiled to .vtf
+	if context.options.detect_uncompiled && used_materials_data.missing_textures.len() > 0 {
+		let uncompiled_textures = hashmap_detect_uncompiled_textures(&mut used_materials_data.missing_textures, context.source_files);
+		if uncompiled_textures > 0 {
+			warn!("Found <red>{}</> missing textures whose source image is present but not compiled to .vtf. Did you forget to run vtex?", uncompiled_textures);
+		}
+	}
+
+	// The textures found in the game were already removed above
+	return finish_category(context, "Textures", "textures", &used_materials_data.used_textures, &mut used_materials_data.missing_textures, None, None);
+
+}
+
+// Removes the soundscapes which are part of the game from the missing soundscapes, logs the missing soundscapes and returns the summary of the soundscape scripts
+fn finish_soundscapes(context: &CollectContext, soundscape_data: &mut SoundscapeData) -> SummaryCategory {
+
+	// Soundscapes which are not defined in the source paths are usually part of the game (e.g. Half-Life 2 soundscapes)
+	remove_game_soundscapes_logged(&mut soundscape_data.missing_soundscapes, context.game_fs, context.options.exclude_found_in_game_report);
+
+	if soundscape_data.missing_soundscapes.len() > 0 {
+		log_missing_files_hashmap("soundscapes", &soundscape_data.missing_soundscapes);
+	} else if !context.options.summary_only_missing {
+		success!("<green>No soundscapes missing in source files!</>");
+	}
+
+	return SummaryCategory { name: "Soundscape scripts", found: soundscape_data.used_scripts.len(), missing: soundscape_data.missing_soundscapes.len(), size: files_size(&soundscape_data.used_scripts, None) };
+
+}
+
+// Removes the content which is part of the game from the missing files and logs how much of it was found, unless quiet is set
+pub fn remove_game_content_logged(name: &str, missing_files: &mut HashMap<String, String>, fs: Option<&OpenFileSystem>, concurrency_limit: usize, quiet: bool) {
+
+	let missing_files_len = missing_files.len();
+	if fs.is_none() || missing_files_len == 0 {
+		return;
+	}
+
+	if !quiet {
+		info!("Looking for <red>{}</> currently missing {} in game files...", missing_files_len, name);
+	}
+
+	let found_missing_files = hashmap_remove_game_content(missing_files, fs, concurrency_limit);
+
+	if !quiet {
+		info!("Found <green>{}</>/<red>{}</> currently missing {} in game files", found_missing_files, missing_files_len, name);
+	}
+
+}
+
+// The maps passed to this only contain content which is missing in the source paths, so content of the source paths is never removed.
+// Nothing is removed without a game file system (--skip-game-check).
+pub fn hashmap_remove_game_content(map: &mut HashMap<String, String>, fs: Option<&OpenFileSystem>, concurrency_limit: usize) -> i32 {
+
+	let fs = match fs {
+		Some(fs) => fs,
+		None => return 0,
+	};
+
+	let found_file_local_paths = find_game_content(map.keys().collect(), fs, concurrency_limit);
+
+	map.retain(|file_local_path, _| !found_file_local_paths.contains(file_local_path));
+
+	return found_file_local_paths.len() as i32;
+
+}
+
+// Returns the paths which exist in the game files
+pub fn find_game_content(file_local_paths: Vec<&String>, fs: &OpenFileSystem, concurrency_limit: usize) -> HashSet<String> {
+
+	// Opening files in the game vpks one by one is slow for thousands of entries, so the lookups run in parallel.
+	// A limit of 0 lets rayon use one thread per CPU core.
+	let thread_pool = match rayon::ThreadPoolBuilder::new().num_threads(concurrency_limit).build() {
+		Ok(thread_pool) => thread_pool,
+		Err(err) => {
+			warn!("Failed to create thread pool for game file lookups: {}", err.to_string());
+			return HashSet::new();
+		}
+	};
+
+	// Messages of the lookups are printed after all lookups are done to keep the output readable
+	let buffered_log = library::log::BufferedLog::new();
+
+	let found_file_local_paths: HashSet<String> = thread_pool.install(|| {
+		file_local_paths
+			.par_iter()
+			.filter(|file_local_path| game_file_exists(file_local_path, fs, &buffered_log))
+			.map(|file_local_path| file_local_path.to_string())
+			.collect()
+	});
+
+	buffered_log.flush();
+
+	return found_file_local_paths;
+
+}
+
+fn game_file_exists(file_local_path: &str, fs: &OpenFileSystem, buffered_log: &library::log::BufferedLog) -> bool {
+
+	// plumber_core only allows "/" slashes and lowercase characters
+	let game_file_location = file_local_path.replace("\\", "/").to_lowercase();
+
+	// We need to use plumber_core::vpk::Path because only this way plumber_core looks in the *game* file system instead of the OS file system
+	// It checks if a std library Path is provided or its custom one.
+	let game_file_path = match plumber_core::vpk::Path::try_from_str(&game_file_location.as_str()) {
+		Some(path) => path,
+		None => {
+			buffered_log.warn(format!("Failed to create game file path for \"{}\"", file_local_path));
+			return false;
+		}
+	};
+
+	// Try to open file in game file system
+	// The path is all lowercase but that is working and explicitly allowed (and required above) by plumber_core
+	return fs.open_file(game_file_path).is_ok();
+
+}
+
+// Reads a file of the game file system, e.g. a script inside of a vpk
+fn read_game_file(file_local_path: &str, fs: &OpenFileSystem) -> Option<String> {
+
+	let game_file_location = file_local_path.replace("\\", "/").to_lowercase();
+	let game_file_path = plumber_core::vpk::Path::try_from_str(&game_file_location.as_str())?;
+
+	let mut content: Vec<u8> = vec!();
+	match fs.open_file(game_file_path) {
+		Ok(mut file) => std::io::Read::read_to_end(&mut file, &mut content).ok()?,
+		Err(_) => return None,
+	};
+
+	return Some(String::from_utf8_lossy(&content).to_string());
+
+}
+
+// Returns the lowercase names of all soundscapes the game defines in the scripts listed by its scripts/soundscapes_manifest.txt
+pub fn read_game_soundscape_names(fs: &OpenFileSystem) -> HashSet<String> {
+
+	let mut names: HashSet<String> = HashSet::new();
+
+	let manifest = match read_game_file("scripts/soundscapes_manifest.txt", fs).map(|content| library::keyvalues::parse(&content)) {
+		Some(Ok(manifest)) => manifest,
+		Some(Err(err)) => {
+			warn!("Failed to parse the soundscape manifest of the game: {}", err.to_string());
+			return names;
+		},
+		None => return names,
+	};
+
+	let script_paths: Vec<&str> = manifest
+		.iter()
+		.flat_map(|(_, files)| match files {
+			library::keyvalues::KeyValue::Block(children) => children.iter().filter(|(key, _)| key.eq_ignore_ascii_case("file")).filter_map(|(_, value)| value.as_str()).collect(),
+			library::keyvalues::KeyValue::Value(_) => vec!(),
+		})
+		.collect();
+
+	for script_path in script_paths {
+		match read_game_file(script_path, fs).map(|content| library::keyvalues::parse(&content)) {
+			Some(Ok(script)) => names.extend(script.into_iter().map(|(name, _)| name.to_lowercase())),
+			Some(Err(err)) => warn!("Failed to parse soundscape script \"{}\" of the game: {}", script_path, err.to_string()),
+			None => warn!("Failed to read soundscape script \"{}\" of the game", script_path),
+		}
+	}
+
+	return names;
+
+}
+
+// Like remove_game_content_logged, but soundscapes are names defined inside of the game's soundscape scripts instead of files
+pub fn remove_game_soundscapes_logged(missing_soundscapes: &mut HashMap<String, String>, fs: Option<&OpenFileSystem>, quiet: bool) {
+
+	let missing_soundscapes_len = missing_soundscapes.len();
+	let fs = match fs {
+		Some(fs) if missing_soundscapes_len > 0 => fs,
+		_ => return,
+	};
+
+	if !quiet {
+		info!("Looking for <red>{}</> currently missing soundscapes in game files...", missing_soundscapes_len);
+	}
+
+	let game_soundscape_names = read_game_soundscape_names(fs);
+	missing_soundscapes.retain(|name, _| !game_soundscape_names.contains(name));
+
+	if !quiet {
+		info!("Found <green>{}</>/<red>{}</> currently missing soundscapes in game files", missing_soundscapes_len - missing_soundscapes.len(), missing_soundscapes_len);
+	}
+
+}
+
+// Looks for the "_ssbump" / "_normal" variant of every missing normal map (e.g. "wall_ssbump.vtf" for "wall_normal.vtf" or "wall.vtf") and moves the
+// missing normal maps whose variant is part of the source paths to used_textures, where they are copied from the variant to the referenced name
+pub fn hashmap_resolve_normal_map_variants(missing_normal_maps: &HashSet<String>, missing_textures: &mut HashMap<String, String>, used_textures: &mut HashMap<String, SourceContentFile>, source_files: &HashMap<String, SourceContentFile>) -> usize {
+
+	let mut variant_count = 0;
+
+	for normal_map in missing_normal_maps.iter().sorted() {
+
+		let texture_base_path = match normal_map.strip_suffix(".vtf") {
+			Some(path) => path,
+			None => continue,
+		};
+
+		let variants = match (texture_base_path.strip_suffix("_ssbump"), texture_base_path.strip_suffix("_normal")) {
+			(Some(base_path), _) => vec!(format!("{}_normal.vtf", base_path), format!("{}.vtf", base_path)),
+			(_, Some(base_path)) => vec!(format!("{}_ssbump.vtf", base_path), format!("{}.vtf", base_path)),
+			_ => vec!(format!("{}_ssbump.vtf", texture_base_path), format!("{}_normal.vtf", texture_base_path)),
+		};
+
+		let reason = match missing_textures.get(normal_map) {
+			Some(reason) => reason.to_owned(),
+			// Found in the game files
+			None => continue,
+		};
+
+		if let Some(variant_source_file) = variants.iter().find_map(|variant| source_files.get(variant)) {
+
+			// The variant is in the same directory, so only the file name is replaced, since the material only loads the referenced name
+			let normal_map_file_name = normal_map.rsplit('\\').next().unwrap_or(normal_map);
+			let normal_map_local_path = Path::new(&variant_source_file.local_path).with_file_name(normal_map_file_name).to_string_lossy().to_string();
+
+			warn!("Normal map \"{}\" is missing, collecting its variant \"<cyan>{}</>\" under the missing name ({})", normal_map, variant_source_file.local_path, reason);
+			used_textures.insert(normal_map.to_owned(), SourceContentFile { full_path: variant_source_file.full_path.to_owned(), local_path: normal_map_local_path });
+			missing_textures.remove(normal_map);
+			variant_count += 1;
+
+		}
+
+	}
+
+	return variant_count;
+
+}
+
+pub fn hashmap_detect_uncompiled_textures(map: &mut HashMap<String, String>, source_files: &HashMap<String, SourceContentFile>) -> i32 {
+
+	let mut uncompiled_count = 0;
+
+	for (file_local_path, error_message) in map.iter_mut() {
+
+		let texture_base_path = match file_local_path.strip_suffix(".vtf") {
+			Some(path) => path,
+			None => continue,
+		};
+
+		// Look for a source image next to where the compiled texture is expected
+		for extension in TEXTURE_SOURCE_EXTENSIONS {
+			if let Some(source_file) = source_files.get(&format!("{}.{}", texture_base_path, extension)) {
+				warn!("Texture source \"{}\" present but not compiled to .vtf", source_file.local_path);
+				error_message.push_str(&format!(" (texture source \"{}\" present but not compiled to .vtf)", source_file.local_path));
+				uncompiled_count += 1;
+				break;
+			}
+		}
+
+	}
+
+	return uncompiled_count;
+
+}
+
+#[cfg(test)]
+mod tests {
+
+	use std::{fs, path::PathBuf};
+	use crate::library::collector;
+	use super::*;
+	use super::super::{build_source_files_map, read_material_data};
+	use super::super::resolve::resolve_content;
+	use super::super::test_utils::*;
+
+	#[test]
+	fn collection_without_game_keeps_all_missing_content() {
+
+		// --offline collects without the game directory like --skip-game-check
+		assert!(test_options(&["--offline"]).skip_game_check);
+
+		let directory = test_directory("without_game");
+		write_files(&directory, &[
+			("materials/mymap/floor.vmt", "LightmappedGeneric\n{\n\t$basetexture mymap/floor\n\t$bumpmap mymap/floor_normal\n}\n"),
+			("materials/mymap/floor.vtf", "VTF"),
+		]);
+		let source_files: HashMap<String, SourceContentFile> = HashMap::from([
+			source_file(&directory, "materials/mymap/floor.vmt"),
+			source_file(&directory, "materials/mymap/floor.vtf"),
+		]);
+
+		// Without a game directory (--skip-game-check) materials are still read, only without the game files
+		let open_fs = collector::open_file_system(None).unwrap();
+		let mut material_data = read_material_data(&source_files["materials\\mymap\\floor.vmt"].full_path, &source_files, &open_fs).unwrap();
+		assert_eq!(material_data.used_textures.keys().collect::<Vec<_>>(), vec!("materials\\mymap\\floor.vtf"));
+		assert_eq!(material_data.missing_textures.keys().collect::<Vec<_>>(), vec!("materials\\mymap\\floor_normal.vtf"));
+
+		// Nothing can be found in the game, so nothing is removed from the missing content
+		assert_eq!(hashmap_remove_game_content(&mut material_data.missing_textures, None, 4), 0);
+		assert_eq!(material_data.missing_textures.len(), 1);
+
+		let mut missing_soundscapes = HashMap::from([(String::from("d1_canals.util_drip"), String::from("Used by entity 12 with class env_soundscape"))]);
+		remove_game_soundscapes_logged(&mut missing_soundscapes, None, true);
+		assert_eq!(missing_soundscapes.len(), 1);
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn materials_found_while_reading_textures_are_looked_up_in_game() {
+
+		let source_directory = test_directory("late_material_source");
+		let game_directory = test_directory("late_material_game");
+		write_files(&source_directory, &[
+			("materials/mymap/harbor_water.vmt", "\"Water\"\n{\n\t\"$bottommaterial\" \"nature/water_canals03_beneath\"\n\t\"$normalmap\" \"mymap/harbor_water_normal\"\n}\n"),
+			("materials/mymap/harbor_water_normal.vtf", "VTF"),
+		]);
+		write_files(&game_directory, &[("materials/nature/water_canals03_beneath.vmt", "\"UnlitGeneric\" { }")]);
+
+		let source_files: HashMap<String, SourceContentFile> = HashMap::from([
+			source_file(&source_directory, "materials/mymap/harbor_water.vmt"),
+			source_file(&source_directory, "materials/mymap/harbor_water_normal.vtf"),
+		]);
+		let game_fs = (plumber_core::fs::FileSystem { name: String::from("Test game"), search_paths: vec!(plumber_core::fs::SearchPath::Directory(game_directory.to_owned())) }).open().unwrap();
+
+		// The brush materials were already known before reading, so only the lookup after reading finds the bottom material
+		let mut used_materials: HashMap<String, SourceContentFile> = HashMap::from([source_file(&source_directory, "materials/mymap/harbor_water.vmt")]);
+		let mut missing_materials: HashMap<String, String> = HashMap::from([(String::from("materials\\mymap\\pier_planks.vmt"), String::from("Used by brush side 42"))]);
+
+		let mut material_data = collector::collect_materials_data(&mut used_materials, false, |source_file| read_material_data(&source_file.full_path, &source_files, &game_fs)).unwrap();
+		missing_materials.extend(material_data.missing_materials.drain());
+
+		assert_eq!(hashmap_remove_game_content(&mut missing_materials, Some(&game_fs), 2), 1);
+		assert_eq!(missing_materials.keys().collect::<Vec<_>>(), vec!("materials\\mymap\\pier_planks.vmt"));
+		assert!(material_data.used_textures.contains_key("materials\\mymap\\harbor_water_normal.vtf"));
+
+		fs::remove_dir_all(&source_directory).unwrap();
+		fs::remove_dir_all(&game_directory).unwrap();
+
+	}
+
+	#[test]
+	fn game_materials_referenced_by_brush_materials_are_not_missing() {
+
+		let source_path = test_directory("game_materials_collect_source");
+		let game_directory = test_directory("game_materials_collect_game");
+		write_files(&source_path, &[
+			("materials/mymap/harbor_water.vmt", "Water\n{\n\t$bottommaterial nature/water_canals03_beneath\n\t$normalmap mymap/harbor_water_normal\n}\n"),
+			("materials/mymap/harbor_water_normal.vtf", "VTF"),
+		]);
+		write_files(&game_directory, &[("materials/nature/water_canals03_beneath.vmt", "UnlitGeneric { }")]);
+		let vmf = write_map(&source_path, "rp_harbor", &[], &["mymap/harbor_water", "mymap/pier_planks"], &[]);
+
+		let game_fs = (plumber_core::fs::FileSystem { name: String::from("Test game"), search_paths: vec!(plumber_core::fs::SearchPath::Directory(game_directory.to_owned())) }).open().unwrap();
+		let source_files = build_source_files_map(&vec!(source_path.to_owned()), None, false, None);
+		let options = test_options(&["--exclude-found-in-game-report"]);
+		let context = CollectContext { game_fs: Some(&game_fs), ..test_context(&source_files, &game_fs, &options) };
+
+		// The bottom material is only known after reading the brush materials, it is looked up in the game with the other missing materials
+		let mut content = resolve_content(&context, &vmf).unwrap();
+		assert_eq!(content.missing_materials.keys().sorted().collect::<Vec<_>>(), vec!("materials\\mymap\\pier_planks.vmt", "materials\\nature\\water_canals03_beneath.vmt"));
+
+		let summary_categories = filter_content(&context, &mut content);
+
+		assert_eq!(summary_counts(&summary_categories, "Materials"), (1, 1));
+		assert_eq!(content.missing_materials.keys().collect::<Vec<_>>(), vec!("materials\\mymap\\pier_planks.vmt"));
+		assert_eq!(summary_counts(&summary_categories, "Textures"), (1, 0));
+
+		fs::remove_dir_all(&source_path).unwrap();
+		fs::remove_dir_all(&game_directory).unwrap();
+
+	}
+
+	#[test]
+	fn missing_normal_maps_are_replaced_by_their_variants() {
+
+		let source_files: HashMap<String, SourceContentFile> = ["materials/brick/wall_ssbump.vtf", "materials/metal/plate_normal.vtf", "materials/concrete/floor_ssbump.vtf"]
+			.iter()
+			.map(|local_path| (local_path.replace("/", "\\"), SourceContentFile { full_path: format!("/addon/{}", local_path), local_path: PathBuf::from_iter(local_path.split('/')).to_string_lossy().to_string() }))
+			.collect();
+
+		// The ssbump of the floor is in the source paths, but its base texture is no normal map
+		let missing_normal_maps: HashSet<String> = HashSet::from([
+			String::from("materials\\brick\\wall_normal.vtf"),
+			String::from("materials\\metal\\plate.vtf"),
+			String::from("materials\\glass\\window_normal.vtf"),
+			String::from("materials\\dev\\found_in_game_normal.vtf"),
+		]);
+		let mut missing_textures: HashMap<String, String> = [
+			"materials\\brick\\wall_normal.vtf",
+			"materials\\metal\\plate.vtf",
+			"materials\\glass\\window_normal.vtf",
+			"materials\\concrete\\floor.vtf",
+		].iter().map(|texture| (texture.to_string(), String::from("Used by material in parameter $bumpmap"))).collect();
+		let mut used_textures: HashMap<String, SourceContentFile> = HashMap::new();
+
+		assert_eq!(hashmap_resolve_normal_map_variants(&missing_normal_maps, &mut missing_textures, &mut used_textures, &source_files), 2);
+
+		// The variants are copied to the missing names
+		assert_eq!(PathBuf::from(&used_textures["materials\\brick\\wall_normal.vtf"].local_path), Path::new("materials").join("brick").join("wall_normal.vtf"));
+		let used_textures: Vec<(&str, &str)> = used_textures.iter().map(|(key, source_file)| (key.as_str(), source_file.full_path.as_str())).sorted().collect();
+		assert_eq!(used_textures, vec!(("materials\\brick\\wall_normal.vtf", "/addon/materials/brick/wall_ssbump.vtf"), ("materials\\metal\\plate.vtf", "/addon/materials/metal/plate_normal.vtf")));
+		assert_eq!(missing_textures.keys().sorted().collect::<Vec<_>>(), vec!("materials\\concrete\\floor.vtf", "materials\\glass\\window_normal.vtf"));
+
+	}
+
+}
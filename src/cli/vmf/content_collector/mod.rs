@@ -0,0 +1,3323 @@
+use std::{collections::{HashMap, HashSet}, fs, path::{Path, PathBuf}, time::Instant};
+use clap::{Args, ValueEnum};
+use crate::library::validation::validate_size;
+use crate::cli::model;
+use crate::library;
+use crate::library::collector::{self, SourceContentFile, SourceMaterialData};
+use crate::library::log::{error, info, success, warn};
+use plumber_core::{fs::OpenFileSystem, uncased::UncasedStr};
+use regex::Regex;
+use walkdir::WalkDir;
+use itertools::Itertools;
+use rayon::prelude::*;
+use simple_error::{bail, SimpleError};
+
+pub mod copy;
+pub mod filter;
+pub mod report;
+pub mod resolve;
+#[cfg(test)]
+mod test_utils;
+
+#[derive(Args, Debug, Clone)]
+pub struct CollectOptions {
+	#[arg(long, help = "Also collect the map's navigation mesh (`maps/<name>.nav`) and AI node graph (`maps/graphs/<name>.ain`) from the source paths.")]
+	pub include_nav: bool,
+	#[arg(long, help = "Also collect choreography scenes (`.vcd`) referenced by entities (e.g. `logic_choreographed_scene`) as well as `scenes/scenes.image`.")]
+	pub include_scenes: bool,
+	#[arg(long, help = "Also collect the weapon scripts (`scripts/<class>.txt`) of weapon entities (`weapon_*`) together with the models, HUD icon sprites and sound files they reference. Only Half-Life 2 style scripts with a `WeaponData` block are supported, Lua weapons (SWEPs) do not have a weapon script.")]
+	pub include_weapon_scripts: bool,
+	#[arg(long, help = "Also collect sound files (`.wav` / `.mp3` / `.ogg`) played by animation events of the collected models, e.g. custom footsteps of NPCs. Events which play soundscripts can not be resolved.")]
+	pub include_model_sounds: bool,
+	#[arg(long, help = "For missing textures, check if a source image (`.tga` / `.png`) with the same name exists and warn that it was not compiled to a `.vtf` yet.")]
+	pub detect_uncompiled: bool,
+	#[arg(long, help = "For missing normal maps (`$bumpmap` / `$normalmap`), look for the `_ssbump` / `_normal` naming variant of the texture (e.g. `wall_ssbump.vtf` for `wall_normal.vtf`) and copy it to the missing name instead of reporting the texture as missing.")]
+	pub fuzzy_textures: bool,
+	#[arg(long, help = "Remove files inside the `materials/` and `models/` directories of the output path which are not used by the map before copying, so no stale content from previous runs remains. By default, content is added to the output path and existing files are overwritten.")]
+	pub clean: bool,
+	#[arg(long, requires = "clean", help = "Together with `--clean`, also remove files which are not used by the map inside the directories of all other content categories (e.g. `sound/`, `scripts/` or `particles/`). Compiled maps (`maps/*.bsp`) are kept.")]
+	pub clean_all_categories: bool,
+	#[arg(long, help = "After copying, list files in the content directories of the output path (e.g. `materials/`, `models/` or `sound/`) that are not used by the map anymore. Nothing is deleted.")]
+	pub list_stale: bool,
+	#[arg(long, value_name = "PATH", help = "Directory to look for instances (`func_instance`) in if they are not found relative to the vmf using them.")]
+	pub instances_dir: Option<PathBuf>,
+	#[arg(long, help = "Also collect materials referenced in Lua files of the source paths via `Material(\"...\")` and `surface.GetTextureID(\"...\")` as well as GUI layout files (`.res`) loaded via e.g. `LoadControlsFromFile(\"...\")`.")]
+	pub scan_lua: bool,
+	#[arg(long, help = "Also collect the spawn menu icons (`materials/vgui/entities/<class>.vmt` / `materials/entities/<class>.png`) of all entity classes used by the map.")]
+	pub include_spawnicons: bool,
+	#[arg(long, help = "Also collect all console / loading screen backgrounds (`materials/console/background*.vmt`, e.g. `background01_widescreen.vmt`) which are inside of the source paths, e.g. of an addon.")]
+	pub include_backgrounds: bool,
+	#[arg(long, help = "Separately report content which is only used by entities inside the 3D skybox, i.e. near the `sky_camera` entity.")]
+	pub report_skybox: bool,
+	#[arg(long, value_name = "UNITS", default_value_t = 2048.0, help = "Maximum distance on each axis from the `sky_camera` entity for an entity to be considered part of the 3D skybox. Used by `--report-skybox`.")]
+	pub skybox_radius: f64,
+	#[arg(long, help = "Check that all six face materials of the 2D skybox (the `skyname` of the map) are part of the source paths or the game, and warn about missing faces.")]
+	pub verify_skybox: bool,
+	#[arg(long, help = "Report texture parameters of the collected materials whose value differs in case from the texture file it resolves to, e.g. `MyTexture` for `mytexture.vtf`, which can fail to load on case-sensitive file systems.")]
+	pub materials_case_report: bool,
+	#[arg(long, help = "Report references in materials (texture parameters) and Lua files (with `--scan-lua`) which contain a drive letter (`C:\\...`), a leading slash or backslashes, which can break on other machines even if they resolve locally.")]
+	pub strict_paths: bool,
+	#[arg(long, help = "Separately report content which is only used by entities spawned at runtime by a `point_template` (e.g. through an `env_entity_maker`).")]
+	pub report_templated: bool,
+	#[arg(long, help = "Debugging aid: Report the content referenced by each entity (id and class) as a tree, marking every file as found, missing or part of the game, e.g. to find out why a specific prop is broken.")]
+	pub report_by_entity: bool,
+	#[arg(long, help = "Write all copied files and directories in lowercase, regardless of the casing in the source paths. Recommended for content which is used on Linux (dedicated) servers.")]
+	pub flatten_case: bool,
+	#[arg(short, long, help = "Print additional information useful for debugging, e.g. which material of which model was looked up in which cdmaterials path.")]
+	pub verbose: bool,
+	#[arg(long = "model-ext", value_name = "EXTENSION", default_values = MODEL_COMPANION_EXTENSIONS, help = "Extension of a companion file which is copied alongside every `.mdl` file. This option can be used multiple times and replaces the default set of `dx90.vtx`, `phy` and `vvd`.")]
+	pub model_ext: Vec<String>,
+	#[arg(long, value_name = "GMA_PATH", help = "Pack all collected content directly into a `.gma` file at the specified path. If no output path is provided, no loose files are written.")]
+	pub output_gma: Option<PathBuf>,
+	#[arg(long, value_name = "NAME", requires = "output_gma", help = "Author written into the `.gma` of `--output-gma`. Defaults to the `author` of the `addon.json` of a source path, if there is one.")]
+	pub gma_author: Option<String>,
+	#[arg(long, conflicts_with = "format", help = "Print the content summary as a single JSON object to stdout. All other output is written to stderr. Can not be combined with `--format`, which outputs the summary as a record instead.")]
+	pub summary_json: bool,
+	#[arg(long, help = "Print the content summary as a bordered table which also includes the size of the found content.")]
+	pub summary_table: bool,
+	#[arg(long, conflicts_with = "summary_table", help = "Only print the categories of the content summary which have missing content, without the messages about categories which are complete. Exits with a nonzero exit code if any content is missing.")]
+	pub summary_only_missing: bool,
+	#[arg(long, value_enum, value_name = "FORMAT", help = "Write a report of all missing content in the specified format, e.g. to paste it into an issue. The report is printed to stdout unless `--report-path` is provided, in which case all other output is written to stderr.")]
+	pub report_format: Option<ReportFormat>,
+	#[arg(long, value_name = "REPORT_PATH", requires = "report_format", help = "Path to write the report of `--report-format` to instead of stdout.")]
+	pub report_path: Option<PathBuf>,
+	#[arg(long, help = "Abort the whole collection with a nonzero exit code if any material (vmt) fails to be read or parsed, instead of skipping it with a warning.")]
+	pub strict: bool,
+	#[arg(long, value_name = "THREADS", default_value_t = 0, help = "Maximum number of threads used to read models and to look up missing content in the game files. Defaults to one thread per CPU core.")]
+	pub concurrency_limit: usize,
+	#[arg(long, help = "After copying, replace files in the output path which are byte-identical to another copied file with hardlinks to save space.")]
+	pub dedupe_output: bool,
+	#[arg(long, value_name = "NAME", help = "Name of a preset in `presets.toml` inside the gmod-developer-cli config directory which supplies default values for these options. Explicitly provided options override the preset values.")]
+	pub preset: Option<String>,
+	#[arg(long, value_name = "MANIFEST_PATH", requires = "output_path", help = "Write a JSON manifest of all copied and missing files to the specified path.")]
+	pub manifest: Option<PathBuf>,
+	#[arg(long, requires = "manifest", help = "Include the SHA-256 hash of every copied file in the manifest. The hashes are computed while copying.")]
+	pub manifest_hashes: bool,
+	#[arg(long, value_name = "BASELINE_MANIFEST", help = "Compare the content the map requires (collected and missing files) with a manifest written by `--manifest` in a previous run, and list the files which are newly required or no longer required.")]
+	pub version_check: Option<PathBuf>,
+	#[arg(long, help = "Print the JSON Schema of the manifest written by `--manifest` and exit.")]
+	pub print_schema: bool,
+	#[arg(long, help = "Print all options as they are used, i.e. including the values of the preset and the default values, as a JSON object before collecting, e.g. to paste them into a bug report.")]
+	pub print_config: bool,
+	#[arg(long, conflicts_with_all = ["output_gma", "manifest", "write_log"], help = "Only report missing content without copying anything, so no output path is required. Exits with a nonzero exit code if any content is missing.")]
+	pub only_missing: bool,
+	#[arg(long, help = "Only collect the `.vmt` files of materials without reading them, so no textures (`.vtf`) are collected or copied. Useful if the textures are compiled separately. Materials referenced by materials (e.g. patch material sources) are not collected either.")]
+	pub materials_only_vmt: bool,
+	#[arg(long, help = "Print the directory structure of the collected content, as it is created inside the output path, as a tree before copying. Combine with `--only-missing` to only print the tree without copying anything.")]
+	pub print_tree: bool,
+	#[arg(long, value_name = "N", help = "After collecting, list the N largest collected files (including model companion files) by their size, e.g. to find content worth optimizing before uploading.")]
+	pub report_largest: Option<usize>,
+	#[arg(long, help = "If another run is collecting into the same output path, wait until it is done instead of exiting with an error.")]
+	pub wait: bool,
+	#[arg(long, value_name = "DIR", help = "Additional directory, relative to the source paths, which is searched like `materials/`. E.g. with `content/materials`, `content/materials/brick/wall.vmt` is found as `materials/brick/wall.vmt` and copied there. This option can be used multiple times.")]
+	pub materials_search_extra: Vec<String>,
+	#[arg(long = "exclude-class", value_name = "CLASS", help = "Skip the content of all entities of this class, e.g. `prop_static` or `npc_*`. Content which is only used by these entities is neither collected nor reported as missing. This option can be used multiple times.")]
+	pub exclude_class: Vec<String>,
+	#[arg(long, conflicts_with = "input_list", help = "After collecting, keep running and collect again whenever the vmf or a file inside of the source paths changes, until the process is interrupted. Best combined with `--relative-symlink` for a fast local loop.")]
+	pub watch: bool,
+	#[arg(long, value_name = "FGD_PATH", help = "Derive which entity keyvalues reference content (materials, sprites, decals, models, sounds and scenes) from a Forge Game Data file instead of the built-in rules. Classes which are not defined in the fgd use the built-in rules.")]
+	pub fgd: Option<PathBuf>,
+	#[arg(long, help = "Content found in the source paths is always collected, even if the game contains a file with the same path. With this flag, the collected files which override game content are listed, e.g. to confirm that intentionally overridden engine materials are shipped.")]
+	pub prefer_source_over_game: bool,
+	#[arg(long, conflicts_with = "dedupe_output", help = "Create symlinks to the files in the source paths instead of copying them, e.g. to quickly test the content locally by mounting the output path. The symlinks are relative to the directory they are in. Falls back to copying if creating symlinks is not permitted. Do not use this for output which is shipped.")]
+	pub relative_symlink: bool,
+	#[arg(long, help = "Do not check whether the output path has enough free space for the collected content before copying.")]
+	pub no_space_check: bool,
+	#[arg(long, conflicts_with = "clean", help = "Continue an interrupted copy into the same output path: Files which already exist in the output path with the same size as in the source paths are not copied again. All other files, including a partially written file of the interrupted run, are copied.")]
+	pub resume: bool,
+	#[arg(long, help = "Do not log how much of the missing content was found in the game files. The summary still only counts content which is missing in both.")]
+	pub exclude_found_in_game_report: bool,
+	#[arg(long, value_name = "SIZE", value_parser = validate_size, conflicts_with_all = ["clean", "list_stale"], help = "Split the content into chunks of approximately SIZE (e.g. `2GB`), which are copied into `output_1/`, `output_2/`, ... inside of the output path, e.g. to split a map pack across multiple Workshop addons. Models are kept together with their materials and textures.")]
+	pub chunk_output: Option<u64>,
+	#[arg(long, value_name = "N", help = "Debugging aid: Stop reading the source paths after N files. The collection is incomplete, use this only to quickly test the tool itself.")]
+	pub limit: Option<usize>,
+	#[arg(long, value_name = "GAME_DIR", help = "Path to the Garry's Mod install directory (which contains `garrysmod/`), if it can not be found via Steam.")]
+	pub game_dir: Option<PathBuf>,
+	#[arg(long, visible_alias = "offline", conflicts_with = "game_dir", help = "Collect without the game files, e.g. on machines without Garry's Mod installed. Content which is part of the game can not be distinguished and is reported as missing.")]
+	pub skip_game_check: bool,
+	#[arg(long, requires = "output_path", help = "Write a human-readable log of the copied and missing files and the run parameters to `_content_collector_log.txt` in the output path.")]
+	pub write_log: bool,
+	#[arg(long, value_name = "CACHE_PATH", help = "Cache the files found in the source paths in the specified file. On the next run, source paths whose directory tree did not change are read from the cache instead of walking them again.")]
+	pub cache: Option<PathBuf>,
+	#[arg(long, help = "Walk all source paths again and rebuild the cache of `--cache`.")]
+	pub refresh_cache: bool,
+	#[arg(long, help = "Ignore `--cache`, e.g. if it is provided by a preset.")]
+	pub no_cache: bool,
+}
+
+#[derive(ValueEnum, Debug, Clone)]
+pub enum ReportFormat {
+	Markdown,
+}
+
+pub struct SummaryCategory {
+	pub name: &'static str,
+	pub found: usize,
+	pub missing: usize,
+	// Size of all found files in bytes
+	pub size: u64,
+}
+
+// The source files, game files and options every content category is collected with
+pub struct CollectContext<'a> {
+	pub source_files: &'a HashMap<String, SourceContentFile>,
+	// None if the game check was skipped
+	pub game_fs: Option<&'a OpenFileSystem>,
+	// Models and materials are always read through the game file system, even if the game check was skipped
+	pub open_fs: &'a OpenFileSystem,
+	// Extensions of the companion files which are copied alongside every .mdl file
+	pub model_companion_extensions: Vec<&'a str>,
+	pub options: &'a CollectOptions,
+}
+
+// All content referenced by the map, split into the files found in the source paths (used) and the files which are missing in them, keyed by their source path
+pub struct ResolvedContent {
+	pub used_materials: HashMap<String, SourceContentFile>,
+	pub missing_materials: HashMap<String, String>,
+	pub used_models: HashMap<String, SourceContentFile>,
+	pub missing_models: HashMap<String, String>,
+	// Textures used by materials and entities, as well as materials referenced by materials
+	pub materials_data: SourceMaterialData,
+	// Particle systems played by entities, their particle files are resolved with the other particle files
+	pub used_particle_systems: HashMap<String, String>,
+	pub used_soundscape_names: HashMap<String, String>,
+	pub soundscape_data: SoundscapeData,
+	pub used_sounds: HashMap<String, SourceContentFile>,
+	pub missing_sounds: HashMap<String, String>,
+	pub used_vehicle_scripts: HashMap<String, SourceContentFile>,
+	pub missing_vehicle_scripts: HashMap<String, String>,
+	pub used_weapon_scripts: HashMap<String, SourceContentFile>,
+	pub missing_weapon_scripts: HashMap<String, String>,
+	pub used_particles: HashMap<String, SourceContentFile>,
+	pub missing_particles: HashMap<String, String>,
+	pub used_scenes: HashMap<String, SourceContentFile>,
+	pub missing_scenes: HashMap<String, String>,
+	pub used_nav_files: HashMap<String, SourceContentFile>,
+	pub missing_nav_files: HashMap<String, String>,
+	pub used_layout_files: HashMap<String, SourceContentFile>,
+	pub missing_layout_files: HashMap<String, String>,
+	// (Lua file local path, function, reference, problem) of the references which are not portable, reported with --strict-paths
+	pub non_portable_lua_references: Vec<(String, &'static str, String, &'static str)>,
+	// The 2D skybox (skyname of the world) and the source paths of its six face materials
+	pub skyname: Option<String>,
+	pub skybox_face_source_paths: Vec<String>,
+	// Content referenced inside and outside of the 3D skybox, used for the 3D skybox report
+	pub sky_camera_origin: Option<[f64; 3]>,
+	pub skybox_entities_count: usize,
+	pub skybox_solids_count: usize,
+	pub skybox_references: HashSet<String>,
+	pub other_references: HashSet<String>,
+	// Content referenced by entities spawned by a point_template and by all other entities, used for the templated entities report
+	pub point_templates_count: usize,
+	pub templated_entities_count: usize,
+	pub templated_references: HashSet<String>,
+	pub untemplated_references: HashSet<String>,
+	// (entity id, entity class, referenced content) of all entities referencing content, used for the entity report
+	pub entity_reports: Vec<(String, String, Vec<String>)>,
+}
+
+impl ResolvedContent {
+	pub fn new() -> Self {
+		Self {
+			used_materials: HashMap::new(),
+			missing_materials: HashMap::new(),
+			used_models: HashMap::new(),
+			missing_models: HashMap::new(),
+			materials_data: SourceMaterialData::new(),
+			used_particle_systems: HashMap::new(),
+			used_soundscape_names: HashMap::new(),
+			soundscape_data: SoundscapeData::new(),
+			used_sounds: HashMap::new(),
+			missing_sounds: HashMap::new(),
+			used_vehicle_scripts: HashMap::new(),
+			missing_vehicle_scripts: HashMap::new(),
+			used_weapon_scripts: HashMap::new(),
+			missing_weapon_scripts: HashMap::new(),
+			used_particles: HashMap::new(),
+			missing_particles: HashMap::new(),
+			used_scenes: HashMap::new(),
+			missing_scenes: HashMap::new(),
+			used_nav_files: HashMap::new(),
+			missing_nav_files: HashMap::new(),
+			used_layout_files: HashMap::new(),
+			missing_layout_files: HashMap::new(),
+			non_portable_lua_references: vec!(),
+			skyname: None,
+			skybox_face_source_paths: vec!(),
+			sky_camera_origin: None,
+			skybox_entities_count: 0,
+			skybox_solids_count: 0,
+			skybox_references: HashSet::new(),
+			other_references: HashSet::new(),
+			point_templates_count: 0,
+			templated_entities_count: 0,
+			templated_references: HashSet::new(),
+			untemplated_references: HashSet::new(),
+			entity_reports: vec!(),
+		}
+	}
+
+	// All missing content by category
+	pub fn missing_content(&self) -> Vec<(&str, &HashMap<String, String>)> {
+		return vec![
+			("materials", &self.missing_materials),
+			("models", &self.missing_models),
+			("textures", &self.materials_data.missing_textures),
+			("soundscapes", &self.soundscape_data.missing_soundscapes),
+			("sounds", &self.missing_sounds),
+			("vehicle scripts", &self.missing_vehicle_scripts),
+			("weapon scripts", &self.missing_weapon_scripts),
+			("particles", &self.missing_particles),
+			("scenes", &self.missing_scenes),
+			("navigation / AI node graph files", &self.missing_nav_files),
+			("GUI layout files", &self.missing_layout_files),
+		];
+	}
+
+	// All collected content by category, with the companion extensions to include for each file
+	pub fn collected_content<'a>(&'a self, context: &'a CollectContext) -> Vec<(&'a str, &'a HashMap<String, SourceContentFile>, Option<&'a Vec<&'a str>>)> {
+
+		let options = context.options;
+
+		let mut collected_content: Vec<(&str, &HashMap<String, SourceContentFile>, Option<&Vec<&str>>)> = vec![
+			("materials", &self.used_materials, None),
+			("models", &self.used_models, Some(&context.model_companion_extensions)),
+		];
+
+		if !options.materials_only_vmt {
+			collected_content.push(("textures", &self.materials_data.used_textures, None));
+		}
+
+		if self.used_soundscape_names.len() > 0 {
+			collected_content.push(("soundscape scripts", &self.soundscape_data.used_scripts, None));
+		}
+
+		if self.used_sounds.len() > 0 {
+			collected_content.push(("sounds", &self.used_sounds, None));
+		}
+
+		if self.used_vehicle_scripts.len() > 0 {
+			collected_content.push(("vehicle scripts", &self.used_vehicle_scripts, None));
+		}
+
+		if options.include_weapon_scripts {
+			collected_content.push(("weapon scripts", &self.used_weapon_scripts, None));
+		}
+
+		if self.used_particles.len() > 0 {
+			collected_content.push(("particles", &self.used_particles, None));
+		}
+
+		if options.include_scenes {
+			collected_content.push(("scenes", &self.used_scenes, None));
+		}
+
+		if options.include_nav {
+			collected_content.push(("navigation / AI node graph files", &self.used_nav_files, None));
+		}
+
+		if options.scan_lua {
+			collected_content.push(("GUI layout files", &self.used_layout_files, None));
+		}
+
+		return collected_content;
+
+	}
+}
+
+// Collects the content of a single vmf and returns its content summary, or None if the collection failed.
+// The content referenced by the map is resolved in the source paths (see resolve), content which is part of the game is removed from the missing content (see filter),
+// the collected and missing content is reported (see report) and finally copied to the output path or packed into a gma (see copy).
+pub fn collect_content(vmf: &PathBuf, source_path_strings: Vec<String>, output_path: &Option<PathBuf>, options: &CollectOptions) -> Option<Vec<SummaryCategory>> {
+
+	// Keep stdout clean for the summary json and the structured output of --format
+	library::log::set_log_to_stderr(library::output::is_structured() || options.summary_json || (options.report_format.is_some() && options.report_path.is_none()));
+
+	if let Some(preset) = &options.preset {
+		info!("Using preset <cyan>{}</>", preset);
+	}
+
+	// Nothing is copied in the audit mode, even if an output path is provided
+	let output_path = match options.only_missing {
+		true => {
+			info!("Only reporting missing content (<cyan>--only-missing</>), nothing is copied");
+			&None
+		},
+		false => output_path,
+	};
+
+	//
+	// Resolve and validate output_path
+	//
+	let output_path = &match output_path {
+		Some(output_path) => Some(collector::prepare_output_path(output_path, vmf)?),
+		None => None,
+	};
+
+	//
+	// Validate source_paths
+	//
+	let source_paths = collector::validate_source_paths(source_path_strings)?;
+
+	//
+	// Locate game install
+	//
+	let game_dir = match collector::locate_game(options.skip_game_check, options.game_dir.as_ref()) {
+		Ok(game_dir) => game_dir,
+		Err(err) => {
+			error!("{}", err.to_string());
+			return None;
+		}
+	};
+
+	//
+	// Lock output_path until the collection is done, so concurrent runs can not write into it at the same time.
+	// The lock is released when it is dropped, i.e. on every return.
+	//
+	let _output_lock = match output_path {
+		Some(output_path) => match library::lock::DirectoryLock::acquire(output_path, options.wait) {
+			Ok(lock) => Some(lock),
+			Err(err) => {
+				error!("{}", err);
+				return None;
+			}
+		},
+		None => None,
+	};
+
+	//
+	// Create a hashmap with all source path files
+	//
+	let cache_path = if options.no_cache { None } else { options.cache.as_ref() };
+	let mut source_files = build_source_files_map(&source_paths, cache_path, options.refresh_cache, options.limit);
+
+	info!("Found <cyan>{}</> files in all source paths", source_files.len());
+
+	if options.materials_search_extra.len() > 0 {
+		let material_root_files = add_material_root_files(&mut source_files, &options.materials_search_extra);
+		info!("Found <cyan>{}</> materials / textures in additional material roots", material_root_files);
+	}
+
+	// Catch source paths pointing at the wrong directory, e.g. the addon root instead of its content
+	if source_files.len() > 0 && !source_files.keys().any(|key| is_in_content_directory(key)) {
+		warn!("None of the files in the source paths are inside a recognized content directory ({})", SOURCE_CONTENT_DIRECTORIES.join(", "));
+		warn!("Make sure that the source paths (<cyan>-s</>) point at directories which directly contain e.g. <cyan>materials/</> and <cyan>models/</>");
+	}
+
+	// Models and materials are read through the game file system
+	let game_fs_open = collector::open_file_system(game_dir.as_ref())?;
+
+	let context = CollectContext {
+		source_files: &source_files,
+		// Content missing in the source paths is only looked up in the game files if there is a game
+		game_fs: game_dir.as_ref().map(|_| &game_fs_open),
+		open_fs: &game_fs_open,
+		model_companion_extensions: options.model_ext
+			.iter()
+			.map(|extension| extension.trim_start_matches('.'))
+			.filter(|extension| !extension.is_empty())
+			.collect(),
+		options,
+	};
+
+	//
+	// Resolve all content referenced by the map in the source paths
+	//
+	let mut content = resolve::resolve_content(&context, vmf)?;
+
+	//
+	// Remove missing content which is part of the game
+	//
+	let summary_categories = filter::filter_content(&context, &mut content);
+
+	//
+	// Report the collected and missing content
+	//
+	let missing_content = content.missing_content();
+	let collected_content = content.collected_content(&context);
+	report::report_content(&context, vmf, &content, &summary_categories, &missing_content, &collected_content);
+
+	//
+	// Copy all content to output directory
+	//
+	if let Some(output_path) = output_path {
+		copy::copy_content(&context, vmf, &source_paths, output_path, &summary_categories, &missing_content, &collected_content)?;
+	}
+
+	//
+	// Pack all content into a gma
+	//
+	if let Some(gma_path) = &options.output_gma {
+		copy::pack_gma(vmf, &source_paths, gma_path, &options.gma_author, &collected_content)?;
+	}
+
+	success!("Done!");
+
+	return Some(summary_categories);
+
+}
+
+// Collects the content of a single vmf, then collects it again whenever the vmf or a file inside of the source paths changes, until the process is interrupted
+pub fn watch_content(vmf: &PathBuf, source_path_strings: Vec<String>, output_path: &Option<PathBuf>, options: &CollectOptions) {
+
+	collect_content(vmf, source_path_strings.to_owned(), output_path, options);
+
+	let mut watched_paths: Vec<PathBuf> = vec!(vmf.to_owned());
+	watched_paths.extend(source_path_strings.iter().map(PathBuf::from).filter(|path| path.is_dir()));
+
+	// Copying into the output path must not cause another run, even if it is inside of a source path
+	let ignored_paths: Vec<PathBuf> = output_path.iter().map(|output_path| library::validation::expand_output_path(output_path, vmf)).collect();
+
+	info!("Watching <cyan>{}</> and <cyan>{}</> source paths for changes, press <cyan>Ctrl+C</> to stop...", vmf.display(), watched_paths.len() - 1);
+
+	let result = library::watch::watch(&watched_paths, &ignored_paths, |changed_paths| {
+
+		match changed_paths.len() {
+			1 => info!("<magenta>CHANGED:</> {}", changed_paths[0].display()),
+			count => info!("<magenta>CHANGED:</> {} and <cyan>{}</> more", changed_paths[0].display(), count - 1),
+		}
+
+		let start = Instant::now();
+
+		match collect_content(vmf, source_path_strings.to_owned(), output_path, options) {
+			Some(summary_categories) => {
+				let found: usize = summary_categories.iter().map(|category| category.found).sum();
+				let missing: usize = summary_categories.iter().map(|category| category.missing).sum();
+				success!("Collected <cyan>{}</> files (<cyan>{}</> missing) in <cyan>{:.1}s</>, waiting for changes...", found, missing, start.elapsed().as_secs_f64());
+			},
+			None => error!("Collection failed, waiting for changes..."),
+		}
+
+	});
+
+	if let Err(err) = result {
+		error!("{}", err.to_string());
+	}
+
+}
+
+// Collects the content of every vmf and model listed in a newline-delimited file, e.g. for batch jobs across a map pool.
+// Returns whether all maps and models are complete, or None if any of them failed to be collected.
+pub fn collect_content_list(input_list: &PathBuf, source_path_strings: Vec<String>, output_path: &Option<PathBuf>, shared_output: bool, options: &CollectOptions) -> Option<bool> {
+
+	let inputs = collector::read_input_list(input_list, &["vmf", "mdl"])?;
+
+	info!("Collecting content of <cyan>{}</> maps and models listed in \"<green>{}</>\"...", inputs.len(), input_list.display());
+
+	// Models are collected like by `model collect-content`, with the options it shares with this command
+	let model_options = model::content_collector::CollectOptions::from(options);
+
+	return collector::collect_input_list(&inputs, output_path, shared_output, |input, input_output_path| {
+
+		let is_model = input.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("mdl"));
+
+		let summary_categories = match (is_model, input_output_path) {
+			(false, _) => collect_content(input, source_path_strings.to_owned(), input_output_path, options),
+			(true, Some(input_output_path)) => model::content_collector::collect_content(input, source_path_strings.to_owned(), input_output_path, &model_options),
+			(true, None) => {
+				error!("Models are always copied, so they can only be collected with an output path (<cyan>-o</>)");
+				None
+			},
+		};
+
+		return summary_categories.map(|summary_categories| summary_categories.iter().map(|category| category.missing).sum());
+
+	});
+
+}
+
+pub fn read_material_data(full_path: &str, source_files: &HashMap<String, SourceContentFile>, open_fs: &plumber_core::fs::OpenFileSystem)
+	-> Result<SourceMaterialData, SimpleError> 
+{
+
+	// Read material
+	let material_file = match fs::read(full_path) {
+		Ok(material_file) => material_file,
+		Err(err) => {
+			bail!("Failed to read material file \"{}\": {}", full_path, err.to_string());
+		}
+	};
+
+	// Parse material
+	let material_parsed = match plumber_core::vmt::from_bytes(&material_file) {
+		Ok(material_parsed) => material_parsed,
+		Err(err) => {
+			bail!("Failed to parse material file \"{}\": {}", full_path, err.to_string());
+		}
+	};
+
+	let mut collection = get_material_data(material_parsed, source_files, open_fs, full_path)?;
+	collection.extend(get_material_proxy_data(&material_file, source_files, full_path));
+	collection.extend(get_material_patch_data(&material_file, source_files, full_path));
+
+	return Ok(collection);
+
+}
+
+pub fn get_material_data(vmt: plumber_core::vmt::Vmt, source_files: &HashMap<String, SourceContentFile>, open_fs: &plumber_core::fs::OpenFileSystem, logging_reference_material: &str)
+	-> Result<SourceMaterialData, SimpleError>
+{
+
+	let mut collection = SourceMaterialData::new();
+
+	// Into shader
+	let material_shader: plumber_core::vmt::Shader = match vmt.resolve_shader_os(open_fs, |patch_path_local| {
+		
+		//
+		// SPECIAL CASE: Patch material
+		// Try to find the material this patch material is patching
+		//
+
+		let mut patch_source_file_path = patch_path_local
+			.replace("/", "\\")
+			.to_lowercase();
+
+		if !patch_source_file_path.ends_with(".vmt") {
+			patch_source_file_path.push_str(".vmt");
+		}
+
+		// Get patched material source file
+		match source_files.get(&patch_source_file_path) {
+			Some(source_file) => {
+
+				// Add patch material *itself* to the collection
+				collection.used_materials.insert(patch_source_file_path, source_file.to_owned());
+
+				// Read patch material and add its data to the collection
+				// This is necessary since plumber_core will actually apply the patch, while the engine still needs the material to patch it itself
+				let patch_source_data = read_material_data(&source_file.full_path, source_files, open_fs)
+					.map_err(|err| plumber_core::vmt::ShaderResolveError::Io { path: String::from(&source_file.full_path), error: format!("[Patch material] {}", err.to_string()) })?;
+
+				collection.extend(patch_source_data);
+
+				return Ok(PathBuf::from(&source_file.full_path));
+
+			},
+			None => {
+				return Err(plumber_core::vmt::ShaderResolveError::Io { path: String::from(patch_path_local), error: String::from("Did not find source file for material to be patched") });
+			}
+		}
+
+		//
+		// END SPECIAL CASE: Patch material
+		//
+
+	}) {
+		Ok(material_shader) => material_shader,
+		Err(err) => {
+			bail!("Failed to parse shader: {}", err.to_string());
+		}
+	};
+
+	// Iterate material parameters and add their value to used_textures / missing_textures if it is a texture parameter
+	for (param_key, param_value) in material_shader.parameters {
+
+		//
+		// SPECIAL CASE: $bottommaterial
+		// This is a material parameter that takes a material as input, so we need to add it to the material collection
+		//
+		if &param_key == UncasedStr::new("$bottommaterial") {
+
+			let source_file_path = make_material_path(&param_value.to_string());
+
+			match source_files.get(&source_file_path) {
+				Some(source_file) => {
+					collection.used_materials.insert(source_file_path, source_file.to_owned());
+				},
+				None => {
+					collection.missing_materials.insert(source_file_path, format!("Used by material \"{}\" in material parameter \"$bottommaterial\"", logging_reference_material));
+				}
+			};
+
+			continue;
+
+		}
+		//	
+		// END SPECIAL CASE: $bottommaterial
+		//
+
+		// Case-insensitive since parameter names like "$AmbientOcclTexture" are commonly written in mixed case
+		let param_key_string = param_key.to_string();
+		if !VMT_TEXTURE_PARAMETERS.iter().any(|parameter| parameter.eq_ignore_ascii_case(&param_key_string)) {
+			continue;
+		}
+
+		let source_file_path = make_texture_path(&param_value.to_string());
+
+		// Special case: $envmap can be set to "env_cubemap" which will be replaced dynamically by a built cubemap by the engine.
+		// Any other $envmap is a real cubemap texture which has to be collected.
+		if source_file_path == VMT_ENVMAP_DEFAULT_SOURCE_PATH {
+			continue;
+		}
+
+		// Check if source file exists and add it to used_textures or missing_textures accordingly
+		match source_files.get(&source_file_path) {
+			Some(source_file) => {
+				collection.used_textures.insert(source_file_path, source_file.to_owned());
+			},
+			None => {
+				if NORMAL_MAP_PARAMETERS.iter().any(|parameter| parameter.eq_ignore_ascii_case(&param_key_string)) {
+					collection.missing_normal_maps.insert(source_file_path.to_owned());
+				}
+				collection.missing_textures.insert(source_file_path, format!("Used by material \"{}\" in texture parameter {}", logging_reference_material, param_key));
+			}
+		};
+
+	}
+
+	return Ok(collection);
+
+}
+
+// Collects materials and textures referenced by the proxies ("Proxies" block) of a material, which plumber_core does not expose.
+// Proxy parameters are usually material variables ("$...") or numbers, so any other value which resolves to a texture or material in
+// the source paths is collected. Only parameters known to take a texture are reported as missing.
+pub fn get_material_proxy_data(material_file: &[u8], source_files: &HashMap<String, SourceContentFile>, logging_reference_material: &str) -> SourceMaterialData {
+
+	let mut collection = SourceMaterialData::new();
+
+	// Materials which fail to parse here were already parsed by plumber_core, so they are just skipped
+	let material_parsed = match library::keyvalues::parse(&String::from_utf8_lossy(material_file)) {
+		Ok(material_parsed) => material_parsed,
+		Err(_) => return collection,
+	};
+
+	for (_, shader) in &material_parsed {
+
+		let proxies = match shader.get("proxies") {
+			Some(library::keyvalues::KeyValue::Block(proxies)) => proxies,
+			_ => continue,
+		};
+
+		for (proxy_name, proxy) in proxies {
+
+			let proxy_parameters = match proxy {
+				library::keyvalues::KeyValue::Block(proxy_parameters) => proxy_parameters,
+				library::keyvalues::KeyValue::Value(_) => continue,
+			};
+
+			for (param_key, param_value) in proxy_parameters {
+
+				let param_value = match param_value.as_str() {
+					Some(value) if !value.trim().is_empty() && !value.starts_with('$') && value.trim().parse::<f64>().is_err() => value.trim(),
+					_ => continue,
+				};
+
+				let texture_source_path = make_texture_path(param_value);
+				let material_source_path = make_material_path(param_value);
+
+				if let Some(source_file) = source_files.get(&texture_source_path) {
+					collection.used_textures.insert(texture_source_path, source_file.to_owned());
+				} else if let Some(source_file) = source_files.get(&material_source_path) {
+					collection.used_materials.insert(material_source_path, source_file.to_owned());
+				} else if VMT_PROXY_TEXTURE_PARAMETERS.iter().any(|parameter| parameter.eq_ignore_ascii_case(param_key)) {
+					collection.missing_textures.insert(texture_source_path, format!("Used by material \"{}\" in parameter \"{}\" of proxy \"{}\"", logging_reference_material, param_key, proxy_name));
+				}
+
+			}
+
+		}
+
+	}
+
+	return collection;
+
+}
+
+// Collects the textures of the parameters a patch material adds or replaces ("insert" / "replace" blocks) directly from the patch material,
+// so they are collected even if they do not end up in the shader resolved by plumber_core.
+pub fn get_material_patch_data(material_file: &[u8], source_files: &HashMap<String, SourceContentFile>, logging_reference_material: &str) -> SourceMaterialData {
+
+	let mut collection = SourceMaterialData::new();
+
+	// Materials which fail to parse here were already parsed by plumber_core, so they are just skipped
+	let material_parsed = match library::keyvalues::parse(&String::from_utf8_lossy(material_file)) {
+		Ok(material_parsed) => material_parsed,
+		Err(_) => return collection,
+	};
+
+	for (shader_name, shader) in &material_parsed {
+
+		if !shader_name.eq_ignore_ascii_case("patch") {
+			continue;
+		}
+
+		for block_name in ["insert", "replace"] {
+
+			let parameters = match shader.get(block_name) {
+				Some(library::keyvalues::KeyValue::Block(parameters)) => parameters,
+				_ => continue,
+			};
+
+			for (param_key, param_value) in parameters {
+
+				let param_value = match param_value.as_str() {
+					Some(value) if !value.trim().is_empty() => value.trim(),
+					_ => continue,
+				};
+
+				if !VMT_TEXTURE_PARAMETERS.iter().any(|parameter| parameter.eq_ignore_ascii_case(param_key)) {
+					continue;
+				}
+
+				let texture_source_path = make_texture_path(param_value);
+				if texture_source_path == VMT_ENVMAP_DEFAULT_SOURCE_PATH {
+					continue;
+				}
+
+				match source_files.get(&texture_source_path) {
+					Some(source_file) => {
+						collection.used_textures.insert(texture_source_path, source_file.to_owned());
+					},
+					None => {
+						if NORMAL_MAP_PARAMETERS.iter().any(|parameter| parameter.eq_ignore_ascii_case(param_key)) {
+							collection.missing_normal_maps.insert(texture_source_path.to_owned());
+						}
+						collection.missing_textures.insert(texture_source_path, format!("Used by patch material \"{}\" in texture parameter {} of its \"{}\" block", logging_reference_material, param_key, block_name));
+					}
+				}
+
+			}
+
+		}
+
+	}
+
+	return collection;
+
+}
+
+// Makes the files inside of additional material roots available as if they were inside of materials\, e.g. with the root "content\materials"
+// the file "content\materials\brick\wall.vmt" is found as "materials\brick\wall.vmt" and copied there. Files which are inside of materials\ already take precedence.
+// Returns the amount of added files.
+pub fn add_material_root_files(source_files: &mut HashMap<String, SourceContentFile>, material_roots: &Vec<String>) -> usize {
+
+	let mut material_root_files: Vec<(String, SourceContentFile)> = vec!();
+
+	for material_root in material_roots {
+
+		let material_root = material_root.replace("/", "\\").trim_matches('\\').to_lowercase();
+		if material_root.is_empty() || material_root == "materials" {
+			continue;
+		}
+		let material_root_prefix = format!("{}\\", material_root);
+		let material_root_depth = material_root.split('\\').count();
+
+		for (source_file_path, source_file) in source_files.iter() {
+
+			let material_path = match source_file_path.strip_prefix(&material_root_prefix) {
+				Some(path) => path,
+				None => continue,
+			};
+
+			let local_path = Path::new("materials")
+				.join(Path::new(&source_file.local_path).components().skip(material_root_depth).collect::<PathBuf>())
+				.to_string_lossy()
+				.to_string();
+
+			material_root_files.push((format!("materials\\{}", material_path), SourceContentFile { full_path: source_file.full_path.to_owned(), local_path }));
+
+		}
+
+	}
+
+	let mut added_files = 0;
+	for (source_file_path, source_file) in material_root_files {
+		if !source_files.contains_key(&source_file_path) {
+			source_files.insert(source_file_path, source_file);
+			added_files += 1;
+		}
+	}
+
+	return added_files;
+
+}
+
+// Files of a source path in the source files cache (see --cache), with the modification time of every directory of its tree when it was walked
+#[derive(Debug, Clone)]
+pub struct CachedSourcePath {
+	pub directories: Vec<(String, u128)>,
+	pub files: Vec<SourceContentFile>,
+}
+
+// Key is the lowercased path local to the source path, this is the "standardized" path used throughout the command
+// If a cache path is provided, the files of source paths whose directory tree did not change since the last run are read from the cache
+// If a limit is provided, the walk stops once that many files are indexed and the cache is not used (debugging aid, see --limit)
+pub fn build_source_files_map(source_paths: &Vec<PathBuf>, cache_path: Option<&PathBuf>, refresh_cache: bool, limit: Option<usize>) -> HashMap<String, SourceContentFile> {
+
+	let mut source_files: HashMap<String, SourceContentFile> = HashMap::new();
+
+	// A truncated map must never end up in the cache
+	let cache_path = match limit {
+		Some(_) => None,
+		None => cache_path,
+	};
+
+	let mut cache: HashMap<String, CachedSourcePath> = match cache_path {
+		Some(cache_path) if !refresh_cache && cache_path.is_file() => match read_source_files_cache(cache_path) {
+			Ok(cache) => cache,
+			Err(err) => {
+				warn!("Ignoring source files cache \"{}\": {}", cache_path.display(), err.to_string());
+				HashMap::new()
+			}
+		},
+		_ => HashMap::new(),
+	};
+
+	for source_path in source_paths {
+
+		let remaining_limit = limit.map(|limit| limit.saturating_sub(source_files.len()));
+		if remaining_limit == Some(0) {
+			break;
+		}
+
+		let source_path_string = source_path.to_string_lossy().to_string();
+
+		let source_path_files = match cache.get(&source_path_string) {
+			Some(cached_source_path) if is_cached_source_path_unchanged(cached_source_path) => {
+				info!("Using cached files of source path \"<green>{}</>\"", &source_path.display());
+				cached_source_path.files.to_owned()
+			},
+			_ => {
+				let (files, directories) = read_source_path_files(source_path, remaining_limit);
+				if cache_path.is_some() {
+					cache.insert(source_path_string, CachedSourcePath { directories, files: files.to_owned() });
+				}
+				files
+			}
+		};
+
+		for source_file in source_path_files {
+
+			// Skip duplicates
+			let hashmap_key = source_file.local_path.replace("/", "\\").to_lowercase();
+			if source_files.contains_key(&hashmap_key) {
+				continue;
+			}
+
+			// Insert into source_files
+			source_files.insert(hashmap_key, source_file);
+
+		}
+
+	}
+
+	if let Some(limit) = limit {
+		if source_files.len() >= limit {
+			warn!("<red>Stopped reading the source paths after {} files because of --limit, the results of this collection are incomplete!</>", limit);
+		}
+	}
+
+	if let Some(cache_path) = cache_path {
+		match write_source_files_cache(cache_path, &cache) {
+			Ok(_) => info!("Updated source files cache \"<green>{}</>\"", cache_path.display()),
+			Err(err) => warn!("Failed to write source files cache \"{}\": {}", cache_path.display(), err.to_string()),
+		}
+	}
+
+	return source_files;
+
+}
+
+// Returns the files of a source path, as well as its directories with their modification time for the source files cache
+pub fn read_source_path_files(source_path: &PathBuf, limit: Option<usize>) -> (Vec<SourceContentFile>, Vec<(String, u128)>) {
+
+	let mut source_path_files: Vec<SourceContentFile> = vec!();
+	let mut source_path_directories: Vec<(String, u128)> = vec!();
+
+	info!("Reading source path \"<green>{}</>\"...", &source_path.display());
+
+	for entry in WalkDir::new(&source_path).follow_links(true) {
+
+		// Get entry
+		let entry = match entry {
+			Ok(entry) => entry,
+			Err(err) => {
+				error!("Failed to read entry in source path \"{}\": {}", &source_path.display(), err.to_string());
+				continue;
+			}
+		};
+
+		// Directories are only remembered for the cache
+		if entry.file_type().is_dir() {
+			if let Some(modified) = directory_modified(entry.path()) {
+				source_path_directories.push((entry.path().to_string_lossy().to_string(), modified));
+			}
+			continue;
+		}
+
+		// Get full path
+		let entry_path = entry.path();
+		let entry_path_string = match entry_path.to_str() {
+			Some(path) => path.to_string(),
+			None => {
+				error!("Failed to get full path to entry \"{}\" in source path \"{}\"", entry_path.display(), &source_path.display());
+				continue;
+			}
+		};
+
+		// Get local / relative path
+		let local_path = match entry_path.strip_prefix(&source_path) {
+			Ok(path) => path,
+			Err(err) => {
+				error!("Failed to make local path for entry \"{}\" in source path \"{}\": {}", entry_path.display(), &source_path.display(), err.to_string());
+				continue;
+			}
+		};
+
+		let local_path_string = match local_path.to_str() {
+			Some(path) => path.to_string(),
+			None => {
+				error!("Failed to get local path to entry \"{}\" in source path \"{}\"", entry_path.display(), &source_path.display());
+				continue;
+			}
+		};
+
+		source_path_files.push(SourceContentFile {
+			full_path: entry_path_string,
+			local_path: local_path_string,
+		});
+
+		if limit.is_some_and(|limit| source_path_files.len() >= limit) {
+			break;
+		}
+
+	}
+
+	return (source_path_files, source_path_directories);
+
+}
+
+pub fn directory_modified(directory_path: &Path) -> Option<u128> {
+	let modified = fs::metadata(directory_path).ok()?.modified().ok()?;
+	return Some(modified.duration_since(std::time::UNIX_EPOCH).ok()?.as_nanos());
+}
+
+// Adding, removing or renaming a file or directory changes the modification time of its parent directory, so a source path is unchanged
+// if all of its directories still have the modification time of the last walk. This only reads the metadata of the directories instead of walking the tree.
+pub fn is_cached_source_path_unchanged(cached_source_path: &CachedSourcePath) -> bool {
+	return !cached_source_path.directories.is_empty() && cached_source_path.directories
+		.par_iter()
+		.all(|(directory_path, modified)| directory_modified(Path::new(directory_path)) == Some(*modified));
+}
+
+// The cache is a text file with a "source<TAB>path" line per source path, followed by a "directory<TAB>full path<TAB>modification time" line per directory
+// and a "full path<TAB>local path" line per file
+pub fn read_source_files_cache(cache_path: &PathBuf) -> Result<HashMap<String, CachedSourcePath>, SimpleError> {
+
+	let content = match fs::read_to_string(cache_path) {
+		Ok(content) => content,
+		Err(err) => bail!("Failed to read cache: {}", err.to_string()),
+	};
+
+	let mut lines = content.lines();
+	if lines.next() != Some(SOURCE_FILES_CACHE_HEADER) {
+		bail!("Unsupported cache format");
+	}
+
+	let mut cache: HashMap<String, CachedSourcePath> = HashMap::new();
+	let mut current_source_path: Option<String> = None;
+
+	for (line_index, line) in lines.enumerate() {
+
+		let columns: Vec<&str> = line.split('\t').collect();
+
+		match (columns.as_slice(), &current_source_path) {
+			(["source", source_path], _) => {
+				cache.insert(source_path.to_string(), CachedSourcePath { directories: vec!(), files: vec!() });
+				current_source_path = Some(source_path.to_string());
+			},
+			(["directory", directory_path, modified], Some(source_path)) => {
+				let modified = match modified.parse::<u128>() {
+					Ok(modified) => modified,
+					Err(_) => bail!("Invalid modification time in line {}", line_index + 2),
+				};
+				if let Some(cached_source_path) = cache.get_mut(source_path) {
+					cached_source_path.directories.push((directory_path.to_string(), modified));
+				}
+			},
+			([full_path, local_path], Some(source_path)) => {
+				if let Some(cached_source_path) = cache.get_mut(source_path) {
+					cached_source_path.files.push(SourceContentFile { full_path: full_path.to_string(), local_path: local_path.to_string() });
+				}
+			},
+			_ => bail!("Invalid line {}", line_index + 2),
+		}
+
+	}
+
+	return Ok(cache);
+
+}
+
+pub fn write_source_files_cache(cache_path: &PathBuf, cache: &HashMap<String, CachedSourcePath>) -> Result<(), SimpleError> {
+
+	let mut content = format!("{}\n", SOURCE_FILES_CACHE_HEADER);
+
+	for (source_path, CachedSourcePath { directories, files }) in cache.iter().sorted_by_key(|(source_path, _)| source_path.to_owned()) {
+
+		// Paths containing tabs or line breaks can not be represented, such source paths are never cached
+		if source_path.contains(['\t', '\n']) || directories.iter().any(|(directory_path, _)| directory_path.contains(['\t', '\n'])) || files.iter().any(|file| file.full_path.contains(['\t', '\n'])) {
+			continue;
+		}
+
+		content.push_str(&format!("source\t{}\n", source_path));
+		for (directory_path, modified) in directories {
+			content.push_str(&format!("directory\t{}\t{}\n", directory_path, modified));
+		}
+		for file in files {
+			content.push_str(&format!("{}\t{}\n", file.full_path, file.local_path));
+		}
+
+	}
+
+	if let Some(parent) = cache_path.parent() {
+		if !parent.as_os_str().is_empty() {
+			if let Err(err) = fs::create_dir_all(parent) {
+				bail!("Failed to create cache directory: {}", err.to_string());
+			}
+		}
+	}
+
+	if let Err(err) = fs::write(cache_path, content) {
+		bail!("{}", err.to_string());
+	}
+
+	return Ok(());
+
+}
+
+// Constructs the path of a material local to the source paths (lowercased, \ instead of /, prefixed with materials\ and .vmt added if there is no extension), everything to match source_files keys
+pub fn make_material_path(material: &str) -> String {
+
+	let material = material
+		.trim()
+		.replace("/", "\\")
+		.to_lowercase();
+
+	// Material paths are usually relative to materials\, but some references include it
+	let mut material_source_path = match material.trim_start_matches('\\').strip_prefix("materials\\") {
+		Some(path) => format!("materials\\{}", path),
+		None => format!("materials\\{}", material.trim_start_matches('\\')),
+	};
+
+	// Material() in Lua can also load .png / .jpg images directly
+	if !MATERIAL_EXTENSIONS.iter().any(|extension| material_source_path.ends_with(extension)) {
+		material_source_path.push_str(".vmt");
+	}
+
+	return material_source_path;
+
+}
+
+// Constructs the path of a brush side material local to the source paths (to_lowercase, replace / with \, add materials\ and add .vmt, everything to match source_files keys).
+// Returns None for tool materials (e.g. tools/toolsnodraw) which are not part of the source paths, since they are part of the game and mostly removed by vbsp.
+pub fn make_side_material_path(material: &str, source_files: &HashMap<String, SourceContentFile>) -> Option<String> {
+
+	let side_material_source_path = format!("materials\\{}.vmt", material.replace("/", "\\").to_lowercase());
+
+	if side_material_source_path.starts_with(TOOL_MATERIALS_DIRECTORY) && !source_files.contains_key(&side_material_source_path) {
+		return None;
+	}
+
+	return Some(side_material_source_path);
+
+}
+
+// Returns None for brush model references ("*<index>") which are not files
+pub fn make_model_path(model: &str) -> Option<String> {
+
+	let model = model
+		.trim()
+		.replace("/", "\\")
+		.to_lowercase();
+
+	if model.is_empty() || model.starts_with('*') {
+		return None;
+	}
+
+	// Model paths usually include models\, but not always
+	let mut model_source_path = match model.trim_start_matches('\\').strip_prefix("models\\") {
+		Some(path) => format!("models\\{}", path),
+		None => format!("models\\{}", model.trim_start_matches('\\')),
+	};
+
+	if !model_source_path.ends_with(".mdl") {
+		model_source_path.push_str(".mdl");
+	}
+
+	return Some(model_source_path);
+
+}
+
+// Material paths of the six faces of the 2D skybox
+pub fn make_skybox_face_paths(skyname: &str) -> Vec<String> {
+	return SKYBOX_FACES.iter().map(|face| make_material_path(&format!("skybox/{}{}", skyname, face))).collect();
+}
+
+// Faces of the 2D skybox which are still missing once the materials found in the game were removed from the missing materials
+pub fn missing_skybox_faces<'a>(skybox_face_source_paths: &'a Vec<String>, missing_materials: &HashMap<String, String>) -> Vec<&'a String> {
+	return skybox_face_source_paths.iter().filter(|face_source_path| missing_materials.contains_key(*face_source_path)).collect();
+}
+
+pub fn make_scene_path(scene: &str) -> String {
+
+	let scene = scene
+		.trim()
+		.replace("/", "\\")
+		.to_lowercase();
+
+	// Scene paths usually include scenes\, but not always
+	let mut scene_source_path = match scene.trim_start_matches('\\').strip_prefix("scenes\\") {
+		Some(path) => format!("scenes\\{}", path),
+		None => format!("scenes\\{}", scene.trim_start_matches('\\')),
+	};
+
+	if !scene_source_path.ends_with(".vcd") {
+		scene_source_path.push_str(".vcd");
+	}
+
+	return scene_source_path;
+
+}
+
+// Returns the texture parameters of the materials whose value only resolves to a texture in the source paths because the lookup is case-insensitive,
+// e.g. "$basetexture" "brick/MyTexture" for "materials/brick/mytexture.vtf". Returned as (material local path, parameter, value, texture local path).
+pub fn find_material_case_mismatches(materials: &HashMap<String, SourceContentFile>, source_files: &HashMap<String, SourceContentFile>) -> Vec<(String, String, String, String)> {
+
+	let mut mismatches: Vec<(String, String, String, String)> = vec!();
+
+	for (source_file_path, source_file) in materials.iter().sorted_by_key(|(source_file_path, _)| *source_file_path) {
+
+		if !source_file_path.ends_with(".vmt") {
+			continue;
+		}
+
+		// Materials which fail to be read or parsed were already reported while collecting their textures
+		let material_file = match fs::read(&source_file.full_path) {
+			Ok(material_file) => material_file,
+			Err(_) => continue,
+		};
+
+		let material_parsed = match library::keyvalues::parse(&String::from_utf8_lossy(&material_file)) {
+			Ok(material_parsed) => material_parsed,
+			Err(_) => continue,
+		};
+
+		let mut texture_parameters: Vec<(&String, &str)> = vec!();
+		for (_, shader) in &material_parsed {
+			collect_texture_parameters(shader, &mut texture_parameters);
+		}
+
+		for (param_key, param_value) in texture_parameters {
+
+			let texture_source_file = match source_files.get(&make_texture_path(param_value)) {
+				Some(texture_source_file) => texture_source_file,
+				None => continue,
+			};
+
+			if strip_texture_path(param_value) != strip_texture_path(&texture_source_file.local_path) {
+				mismatches.push((source_file.local_path.to_owned(), param_key.to_owned(), param_value.to_owned(), texture_source_file.local_path.to_owned()));
+			}
+
+		}
+
+	}
+
+	return mismatches;
+
+}
+
+// Returns the texture parameters of the materials whose value is not portable (see path_portability_problem), as (material local path, parameter, value, problem)
+pub fn find_non_portable_material_references(materials: &HashMap<String, SourceContentFile>) -> Vec<(String, String, String, &'static str)> {
+
+	let mut non_portable_references: Vec<(String, String, String, &'static str)> = vec!();
+
+	for (source_file_path, source_file) in materials.iter().sorted_by_key(|(source_file_path, _)| *source_file_path) {
+
+		if !source_file_path.ends_with(".vmt") {
+			continue;
+		}
+
+		// Materials which fail to be read or parsed were already reported while collecting their textures
+		let material_file = match fs::read(&source_file.full_path) {
+			Ok(material_file) => material_file,
+			Err(_) => continue,
+		};
+
+		let material_parsed = match library::keyvalues::parse(&String::from_utf8_lossy(&material_file)) {
+			Ok(material_parsed) => material_parsed,
+			Err(_) => continue,
+		};
+
+		let mut texture_parameters: Vec<(&String, &str)> = vec!();
+		for (_, shader) in &material_parsed {
+			collect_texture_parameters(shader, &mut texture_parameters);
+		}
+
+		for (param_key, param_value) in texture_parameters {
+			if let Some(problem) = path_portability_problem(param_value) {
+				non_portable_references.push((source_file.local_path.to_owned(), param_key.to_owned(), param_value.to_owned(), problem));
+			}
+		}
+
+	}
+
+	return non_portable_references;
+
+}
+
+// References are resolved relative to the game / addon directories with forward slashes, which the engine and the file systems of all platforms support.
+// Returns why the reference as written is not portable, if it is not.
+pub fn path_portability_problem(reference: &str) -> Option<&'static str> {
+
+	let reference = reference.trim();
+	let mut chars = reference.chars();
+
+	if let (Some(drive), Some(':')) = (chars.next(), chars.next()) {
+		if drive.is_ascii_alphabetic() {
+			return Some("absolute path with a drive letter");
+		}
+	}
+
+	if reference.starts_with('/') || reference.starts_with('\\') {
+		return Some("leading slash");
+	}
+
+	if reference.contains('\\') {
+		return Some("backslash instead of a forward slash");
+	}
+
+	return None;
+
+}
+
+// Adds all texture parameters of a material block and its nested blocks (e.g. fallback shaders or the "insert" block of patch materials)
+fn collect_texture_parameters<'a>(block: &'a library::keyvalues::KeyValue, texture_parameters: &mut Vec<(&'a String, &'a str)>) {
+
+	let children = match block {
+		library::keyvalues::KeyValue::Block(children) => children,
+		library::keyvalues::KeyValue::Value(_) => return,
+	};
+
+	for (key, value) in children {
+		match value {
+			library::keyvalues::KeyValue::Block(_) => collect_texture_parameters(value, texture_parameters),
+			library::keyvalues::KeyValue::Value(value) => {
+				if !value.trim().is_empty() && VMT_TEXTURE_PARAMETERS.iter().any(|parameter| parameter.eq_ignore_ascii_case(key)) {
+					texture_parameters.push((key, value.trim()));
+				}
+			},
+		}
+	}
+
+}
+
+// Like make_texture_path, but keeps the case and strips "materials\" and ".vtf", so a reference and the local path of its texture can be compared
+fn strip_texture_path(texture: &str) -> String {
+
+	let texture = texture
+		.trim()
+		.replace("/", "\\");
+	let texture = texture.trim_start_matches('\\');
+
+	let texture = match texture.get(..10).is_some_and(|prefix| prefix.eq_ignore_ascii_case("materials\\")) {
+		true => &texture[10..],
+		false => texture,
+	};
+
+	return match texture.len() >= 4 && texture.get(texture.len() - 4..).is_some_and(|extension| extension.eq_ignore_ascii_case(".vtf")) {
+		true => texture[..texture.len() - 4].to_owned(),
+		false => texture.to_owned(),
+	};
+
+}
+
+pub fn make_texture_path(texture: &str) -> String {
+
+	let texture = texture
+		.trim()
+		.replace("/", "\\")
+		.to_lowercase();
+
+	// Texture paths are relative to materials\, but some references include it (e.g. "$envmap" "materials/cubemaps/x")
+	let mut texture_source_path = match texture.trim_start_matches('\\').strip_prefix("materials\\") {
+		Some(path) => format!("materials\\{}", path),
+		None => format!("materials\\{}", texture.trim_start_matches('\\')),
+	};
+
+	if !texture_source_path.ends_with(".vtf") {
+		texture_source_path.push_str(".vtf");
+	}
+
+	return texture_source_path;
+
+}
+
+pub fn is_in_content_directory(source_file_path: &str) -> bool {
+	return match source_file_path.split_once('\\') {
+		Some((directory, _)) => SOURCE_CONTENT_DIRECTORIES.contains(&directory),
+		None => false,
+	};
+}
+
+// Collects the materials of all .mdl files in models. Every model is parsed completely, which is slow for maps with many props, so the models are read in parallel.
+pub fn collect_models_materials(models: &HashMap<String, SourceContentFile>, source_files: &HashMap<String, SourceContentFile>, open_fs: &OpenFileSystem, verbose: bool, concurrency_limit: usize)
+	-> (HashMap<String, SourceContentFile>, HashMap<String, String>)
+{
+
+	let mut used_materials: HashMap<String, SourceContentFile> = HashMap::new();
+	let mut missing_materials: HashMap<String, String> = HashMap::new();
+
+	// Only .mdl files (no vtx / phy / vvd)
+	let model_files: Vec<&SourceContentFile> = models
+		.values()
+		.filter(|content_file| content_file.full_path.ends_with(".mdl"))
+		.collect();
+
+	// A limit of 0 lets rayon use one thread per CPU core
+	let models_materials: Vec<(HashMap<String, SourceContentFile>, HashMap<String, String>)> = match rayon::ThreadPoolBuilder::new().num_threads(concurrency_limit).build() {
+		Ok(thread_pool) => thread_pool.install(|| {
+			model_files
+				.par_iter()
+				.map(|content_file| collect_model_materials(content_file, source_files, open_fs, verbose))
+				.collect()
+		}),
+		Err(err) => {
+			warn!("Failed to create thread pool for reading models, reading them one by one: {}", err.to_string());
+			model_files
+				.iter()
+				.map(|content_file| collect_model_materials(content_file, source_files, open_fs, verbose))
+				.collect()
+		}
+	};
+
+	for (model_used_materials, model_missing_materials) in models_materials {
+		used_materials.extend(model_used_materials);
+		missing_materials.extend(model_missing_materials);
+	}
+
+	return (used_materials, missing_materials);
+
+}
+
+pub fn collect_model_materials(content_file: &SourceContentFile, source_files: &HashMap<String, SourceContentFile>, open_fs: &OpenFileSystem, verbose: bool)
+	-> (HashMap<String, SourceContentFile>, HashMap<String, String>)
+{
+
+	// Read model
+	let model = match plumber_core::mdl::Model::read(Path::new(&content_file.full_path), open_fs) {
+		Ok(model) => model,
+		Err(err) => {
+			warn!("Failed to read model \"{}\": {}", content_file.full_path, err.to_string());
+			return collect_model_materials_fallback(content_file, source_files);
+		}
+	};
+
+	// Verify model
+	let model_verified = match model.verify() {
+		Ok(model) => model,
+		Err(err) => {
+			warn!("Failed to verify model \"{}\": {}", content_file.full_path, err.to_string());
+			return collect_model_materials_fallback(content_file, source_files);
+		}
+	};
+
+	// Get materials
+	let materials = match model_verified.mdl_header.iter_textures() {
+		Ok(materials) => materials,
+		Err(err) => {
+			warn!("Failed to get materials of model \"{}\": {}", content_file.full_path, err.to_string());
+			return collect_model_materials_fallback(content_file, source_files);
+		}
+	};
+
+	let mut material_names: Vec<String> = vec!();
+	for material in materials {
+		match material.name() {
+			Ok(name) => material_names.push(name.to_owned()),
+			Err(err) => warn!("Failed to get name of a material of model \"{}\": {}", content_file.full_path, err.to_string()),
+		}
+	}
+
+	// A prop can use any of its skins, so the materials of all skin families are collected, not only the ones of the default skin
+	match fs::read(&content_file.full_path).map_err(|err| SimpleError::new(err.to_string())).and_then(|mdl| library::mdl::skin_families(&mdl)) {
+		Ok(skin_families) => material_names.extend(skin_families.into_iter().flatten()),
+		Err(err) => warn!("Failed to read skin families of model \"{}\": {}", content_file.full_path, err.to_string()),
+	}
+
+	// Get cdmaterials / texture_paths
+	let cdmaterials_list: Vec<String> = match model_verified.mdl_header.texture_paths() {
+		Ok(texture_paths) => texture_paths.iter().map(|texture_path| normalize_cdmaterials_path(texture_path)).collect(),
+		Err(err) => {
+			// The material names are still known, so they are looked up in the model directory fallback only
+			warn!("Failed to get texture paths / cdmaterials of model \"{}\": {}", content_file.full_path, err.to_string());
+			vec!()
+		}
+	};
+
+	return resolve_model_materials(content_file, &material_names, &cdmaterials_list, source_files, verbose);
+
+}
+
+// Looks for the materials of a model in its cdmaterials paths and the model directory fallback
+pub fn resolve_model_materials(content_file: &SourceContentFile, material_names: &Vec<String>, cdmaterials_list: &Vec<String>, source_files: &HashMap<String, SourceContentFile>, verbose: bool)
+	-> (HashMap<String, SourceContentFile>, HashMap<String, String>)
+{
+
+	let mut used_materials: HashMap<String, SourceContentFile> = HashMap::new();
+	let mut missing_materials: HashMap<String, String> = HashMap::new();
+
+	// Skin families mostly repeat the materials of the default skin
+	for material_name in material_names.iter().unique_by(|material_name| material_name.to_lowercase()) {
+
+		// Missing paths are only reported if the material is not found via the fallback path below either
+		let mut material_missing_paths: Vec<String> = vec!();
+
+		// Try to find material in source_files in any of its cdmaterials paths
+		for cdmaterials in cdmaterials_list {
+
+			let source_file_path = format!("materials\\{}{}.vmt", cdmaterials, material_name)
+				.replace("/", "\\")
+				.to_lowercase();
+
+			// Add material to used_materials or missing_materials depending on whether it exists in source_files
+			match source_files.get(&source_file_path) {
+				Some(source_file) => {
+					if verbose {
+						info!("\t<cyan>{}</>: {} -> {} <green>found</>", content_file.local_path, cdmaterials, material_name);
+					}
+					// Add to used_materials
+					used_materials.insert(source_file_path, source_file.to_owned());
+				},
+				None => {
+					if verbose {
+						info!("\t<cyan>{}</>: {} -> {} <red>missing</>", content_file.local_path, cdmaterials, material_name);
+					}
+					material_missing_paths.push(source_file_path);
+				}
+			}
+
+		}
+
+		// If none of the cdmaterials paths contain the material, the engine also looks in materials\models\<model directory>
+		if material_missing_paths.len() == cdmaterials_list.len() {
+			if let Some(fallback_source_file_path) = make_model_fallback_material_path(&content_file.local_path, material_name) {
+				if let Some(source_file) = source_files.get(&fallback_source_file_path) {
+					if verbose {
+						info!("\t<cyan>{}</>: (model directory fallback) -> {} <green>found</>", content_file.local_path, material_name);
+					}
+					used_materials.insert(fallback_source_file_path, source_file.to_owned());
+					continue;
+				}
+			}
+		}
+
+		// Add to missing_materials
+		for source_file_path in material_missing_paths {
+			missing_materials.insert(source_file_path, format!("Used by model \"{}\"", content_file.full_path));
+		}
+
+	}
+
+	return (used_materials, missing_materials);
+
+}
+
+#[derive(Debug)]
+pub struct SoundscapeData {
+	pub used_scripts: HashMap<String, SourceContentFile>,
+	pub missing_soundscapes: HashMap<String, String>,
+	pub used_sounds: HashMap<String, SourceContentFile>,
+	pub missing_sounds: HashMap<String, String>,
+}
+
+impl SoundscapeData {
+	pub fn new() -> Self {
+		Self {
+			used_scripts: HashMap::new(),
+			missing_soundscapes: HashMap::new(),
+			used_sounds: HashMap::new(),
+			missing_sounds: HashMap::new(),
+		}
+	}
+}
+
+// Reads all soundscape definitions of the scripts\soundscapes_*.txt files in the source paths, keyed by their lowercase name
+pub fn read_soundscape_definitions(source_files: &HashMap<String, SourceContentFile>) -> HashMap<String, (String, library::keyvalues::KeyValue)> {
+
+	let mut definitions: HashMap<String, (String, library::keyvalues::KeyValue)> = HashMap::new();
+
+	// The manifest only lists the other script files
+	let script_paths = source_files
+		.keys()
+		.filter(|key| key.starts_with("scripts\\soundscapes_") && key.ends_with(".txt") && key.as_str() != "scripts\\soundscapes_manifest.txt")
+		.sorted();
+
+	for script_path in script_paths {
+
+		let script_content = match fs::read(&source_files[script_path].full_path) {
+			Ok(content) => String::from_utf8_lossy(&content).to_string(),
+			Err(err) => {
+				warn!("Failed to read soundscape script \"{}\": {}", source_files[script_path].full_path, err.to_string());
+				continue;
+			}
+		};
+
+		let script_parsed = match library::keyvalues::parse(&script_content) {
+			Ok(parsed) => parsed,
+			Err(err) => {
+				warn!("Failed to parse soundscape script \"{}\": {}", source_files[script_path].full_path, err.to_string());
+				continue;
+			}
+		};
+
+		// Like the engine, the first definition of a soundscape wins
+		for (name, definition) in script_parsed {
+			definitions.entry(name.to_lowercase()).or_insert((script_path.to_owned(), definition));
+		}
+
+	}
+
+	return definitions;
+
+}
+
+// Returns the particle files (.pcf) listed in a particle manifest (maps/<map name>_particles.txt)
+pub fn read_particle_manifest(manifest_source_file: &SourceContentFile) -> Vec<String> {
+
+	let content = match fs::read(&manifest_source_file.full_path) {
+		Ok(content) => String::from_utf8_lossy(&content).to_string(),
+		Err(err) => {
+			warn!("Failed to read particle manifest \"{}\": {}", manifest_source_file.full_path, err.to_string());
+			return vec!();
+		}
+	};
+
+	let manifest = match library::keyvalues::parse(&content) {
+		Ok(manifest) => manifest,
+		Err(err) => {
+			warn!("Failed to parse particle manifest \"{}\": {}", manifest_source_file.full_path, err.to_string());
+			return vec!();
+		}
+	};
+
+	return manifest
+		.iter()
+		.filter_map(|(_, block)| match block {
+			library::keyvalues::KeyValue::Block(children) => Some(children),
+			library::keyvalues::KeyValue::Value(_) => None,
+		})
+		.flatten()
+		.filter(|(key, _)| key.eq_ignore_ascii_case("file"))
+		.filter_map(|(_, value)| value.as_str())
+		// A leading "!" marks particle files which are precached
+		.map(|particle_path| particle_path.trim().trim_start_matches('!').replace("/", "\\").to_lowercase())
+		.filter(|particle_path| !particle_path.is_empty())
+		.unique()
+		.collect();
+
+}
+
+// Returns all values of a vehicle script which reference a model, material or sound file.
+// Sounds referenced by soundscript names (most of the vehicle sounds) can not be resolved.
+pub fn read_vehicle_script_references(vehicle_script_source_file: &SourceContentFile) -> Vec<String> {
+
+	let content = match fs::read(&vehicle_script_source_file.full_path) {
+		Ok(content) => String::from_utf8_lossy(&content).to_string(),
+		Err(err) => {
+			warn!("Failed to read vehicle script \"{}\": {}", vehicle_script_source_file.full_path, err.to_string());
+			return vec!();
+		}
+	};
+
+	let vehicle_script = match library::keyvalues::parse(&content) {
+		Ok(vehicle_script) => vehicle_script,
+		Err(err) => {
+			warn!("Failed to parse vehicle script \"{}\": {}", vehicle_script_source_file.full_path, err.to_string());
+			return vec!();
+		}
+	};
+
+	let mut references: Vec<String> = vec!();
+	let mut pending_blocks: Vec<&Vec<(String, library::keyvalues::KeyValue)>> = vec![&vehicle_script];
+
+	while let Some(block) = pending_blocks.pop() {
+		for (_, value) in block {
+			match value {
+				library::keyvalues::KeyValue::Block(children) => pending_blocks.push(children),
+				library::keyvalues::KeyValue::Value(value) => {
+					let value = value.trim().to_lowercase();
+					if value.ends_with(".mdl") || value.ends_with(".vmt") || SOUND_EXTENSIONS.iter().any(|extension| value.ends_with(extension)) {
+						references.push(value);
+					}
+				},
+			}
+		}
+	}
+
+	return references.into_iter().unique().collect();
+
+}
+
+// Returns the models (the "faceposermodel" of actors) and sound files (the parameter of "speak" events) referenced by a choreography scene (.vcd).
+// Sounds referenced by soundscript names (most of the speak events) can not be resolved.
+pub fn read_scene_references(scene_source_file: &SourceContentFile) -> Vec<String> {
+
+	let content = match fs::read(&scene_source_file.full_path) {
+		Ok(content) => String::from_utf8_lossy(&content).to_string(),
+		Err(err) => {
+			warn!("Failed to read scene \"{}\": {}", scene_source_file.full_path, err.to_string());
+			return vec!();
+		}
+	};
+
+	let model_regex = Regex::new(r#"(?i)\bfaceposermodel\s+"([^"]+)""#).unwrap();
+	// The parameter directly follows the time of the event, before any nested block
+	let speak_regex = Regex::new(r#"(?is)\bevent\s+speak\s+"[^"]*"\s*\{[^{}]*?\bparam\s+"([^"]+)""#).unwrap();
+
+	let mut references: Vec<String> = vec!();
+
+	for captures in model_regex.captures_iter(&content) {
+		let model = captures[1].trim().to_lowercase();
+		if model.ends_with(".mdl") {
+			references.push(model);
+		}
+	}
+
+	for captures in speak_regex.captures_iter(&content) {
+		let sound = captures[1].trim().to_lowercase();
+		if SOUND_EXTENSIONS.iter().any(|extension| sound.ends_with(extension)) {
+			references.push(sound);
+		}
+	}
+
+	return references.into_iter().unique().collect();
+
+}
+
+// Adds the gib models of the models in used_models to used_models / missing_models, including the gibs of gibs.
+// Returns the amount of gib models which were found in the source files.
+pub fn collect_break_models(used_models: &mut HashMap<String, SourceContentFile>, missing_models: &mut HashMap<String, String>, source_files: &HashMap<String, SourceContentFile>) -> usize {
+
+	let mut found_break_models = 0;
+
+	// Every model is only read once, so models which are gibs of each other do not cause an endless loop
+	let mut visited_models: HashSet<String> = used_models.keys().cloned().collect();
+	let mut pending_models: Vec<SourceContentFile> = used_models
+		.iter()
+		.filter(|(source_file_path, _)| source_file_path.ends_with(".mdl"))
+		.map(|(_, source_file)| source_file.to_owned())
+		.collect();
+
+	while let Some(content_file) = pending_models.pop() {
+		for break_model in read_model_break_models(&content_file) {
+
+			let model_source_path = match make_model_path(&break_model) {
+				Some(path) => path,
+				None => continue,
+			};
+
+			if !visited_models.insert(model_source_path.to_owned()) {
+				continue;
+			}
+
+			match source_files.get(&model_source_path) {
+				Some(source_file) => {
+					used_models.insert(model_source_path, source_file.to_owned());
+					pending_models.push(source_file.to_owned());
+					found_break_models += 1;
+				},
+				None => {
+					missing_models.insert(model_source_path, format!("Used as gib by model \"{}\"", content_file.local_path));
+				}
+			}
+
+		}
+	}
+
+	return found_break_models;
+
+}
+
+// Returns the models of the "break" blocks which a model embeds via $keyvalues (stored in the .mdl) and $collisiontext (stored in the .phy)
+pub fn read_model_break_models(model_source_file: &SourceContentFile) -> Vec<String> {
+
+	let mut keyvalues_texts: Vec<String> = vec!();
+
+	match fs::read(&model_source_file.full_path) {
+		Ok(content) => match library::mdl::keyvalues_text(&content) {
+			Ok(Some(text)) => keyvalues_texts.push(text),
+			Ok(None) => {},
+			Err(err) => warn!("Failed to read keyvalues of model \"{}\": {}", model_source_file.full_path, err.to_string()),
+		},
+		Err(err) => {
+			warn!("Failed to read model \"{}\": {}", model_source_file.full_path, err.to_string());
+			return vec!();
+		}
+	};
+
+	// Most models do not have a .phy, so it not existing is not an error
+	let phy_path = format!("{}.phy", model_source_file.full_path.strip_suffix(".mdl").unwrap_or(&model_source_file.full_path));
+	if let Ok(content) = fs::read(&phy_path) {
+		match library::mdl::phy_keyvalues_text(&content) {
+			Ok(text) => keyvalues_texts.push(text),
+			Err(err) => warn!("Failed to read collision text of \"{}\": {}", phy_path, err.to_string()),
+		}
+	}
+
+	let mut break_models: Vec<String> = vec!();
+
+	for text in keyvalues_texts {
+
+		let keyvalues = match library::keyvalues::parse(&text) {
+			Ok(keyvalues) => keyvalues,
+			Err(err) => {
+				warn!("Failed to parse keyvalues of model \"{}\": {}", model_source_file.full_path, err.to_string());
+				continue;
+			}
+		};
+
+		let mut pending_blocks: Vec<&Vec<(String, library::keyvalues::KeyValue)>> = vec![&keyvalues];
+		while let Some(block) = pending_blocks.pop() {
+			for (key, value) in block {
+				if let library::keyvalues::KeyValue::Block(children) = value {
+					if key.eq_ignore_ascii_case("break") {
+						if let Some(model) = value.get("model").and_then(|model| model.as_str()) {
+							break_models.push(model.to_owned());
+						}
+					}
+					pending_blocks.push(children);
+				}
+			}
+		}
+
+	}
+
+	return break_models.into_iter().unique().collect();
+
+}
+
+// Collects the sound files played by animation events of all .mdl files in models
+pub fn collect_models_sounds(models: &HashMap<String, SourceContentFile>, source_files: &HashMap<String, SourceContentFile>)
+	-> (HashMap<String, SourceContentFile>, HashMap<String, String>)
+{
+
+	let mut used_sounds: HashMap<String, SourceContentFile> = HashMap::new();
+	let mut missing_sounds: HashMap<String, String> = HashMap::new();
+
+	for (_, content_file) in models.iter().filter(|(source_file_path, _)| source_file_path.ends_with(".mdl")) {
+		for sound in read_model_sound_events(content_file) {
+
+			let sound_source_path = make_sound_path(&sound);
+
+			match source_files.get(&sound_source_path) {
+				Some(source_file) => {
+					used_sounds.insert(sound_source_path, source_file.to_owned());
+				},
+				None => {
+					missing_sounds.insert(sound_source_path, format!("Played by an animation event of model \"{}\"", content_file.local_path));
+				}
+			}
+
+		}
+	}
+
+	return (used_sounds, missing_sounds);
+
+}
+
+// Returns the sound files named in the options of the animation events of a model's sequences (e.g. footsteps of NPCs / playermodels).
+// Sound events usually name soundscripts, only sound files can be collected. Sequences of models included via $includemodel are not read.
+pub fn read_model_sound_events(model_source_file: &SourceContentFile) -> Vec<String> {
+
+	let content = match fs::read(&model_source_file.full_path) {
+		Ok(content) => content,
+		Err(err) => {
+			warn!("Failed to read model \"{}\": {}", model_source_file.full_path, err.to_string());
+			return vec!();
+		}
+	};
+
+	let event_options = match library::mdl::sequence_event_options(&content) {
+		Ok(event_options) => event_options,
+		Err(err) => {
+			warn!("Failed to read animation events of model \"{}\": {}", model_source_file.full_path, err.to_string());
+			return vec!();
+		}
+	};
+
+	return event_options
+		.into_iter()
+		.map(|options| options.trim().to_lowercase())
+		.filter(|options| SOUND_EXTENSIONS.iter().any(|extension| options.ends_with(extension)))
+		.unique()
+		.collect();
+
+}
+
+// Returns the models a model includes via $includemodel, usually shared animations (e.g. "models/m_anm.mdl" of playermodels)
+pub fn read_model_include_models(model_source_file: &SourceContentFile) -> Vec<String> {
+
+	let content = match fs::read(&model_source_file.full_path) {
+		Ok(content) => content,
+		Err(err) => {
+			warn!("Failed to read model \"{}\": {}", model_source_file.full_path, err.to_string());
+			return vec!();
+		}
+	};
+
+	let include_models = match library::mdl::include_models(&content) {
+		Ok(include_models) => include_models,
+		Err(err) => {
+			warn!("Failed to read included models of model \"{}\": {}", model_source_file.full_path, err.to_string());
+			return vec!();
+		}
+	};
+
+	return include_models
+		.into_iter()
+		.map(|name| name.trim().to_lowercase())
+		.filter(|name| !name.is_empty())
+		.unique()
+		.collect();
+
+}
+
+// Weapon scripts ("WeaponData") reference the view / world model, the sprite materials of the HUD icons ("TextureData") and sounds ("SoundData").
+// Sounds are usually soundscript names, only sound files can be collected.
+pub fn read_weapon_script_references(weapon_script_source_file: &SourceContentFile) -> Vec<String> {
+
+	let content = match fs::read(&weapon_script_source_file.full_path) {
+		Ok(content) => String::from_utf8_lossy(&content).to_string(),
+		Err(err) => {
+			warn!("Failed to read weapon script \"{}\": {}", weapon_script_source_file.full_path, err.to_string());
+			return vec!();
+		}
+	};
+
+	let weapon_script = match library::keyvalues::parse(&content) {
+		Ok(weapon_script) => weapon_script,
+		Err(err) => {
+			warn!("Failed to parse weapon script \"{}\": {}", weapon_script_source_file.full_path, err.to_string());
+			return vec!();
+		}
+	};
+
+	let weapon_data = match weapon_script.iter().find(|(key, _)| key.eq_ignore_ascii_case("WeaponData")) {
+		Some((_, weapon_data)) => weapon_data,
+		None => {
+			warn!("Weapon script \"{}\" has no \"WeaponData\" block and is skipped", weapon_script_source_file.full_path);
+			return vec!();
+		}
+	};
+
+	let mut references: Vec<String> = vec!();
+
+	for key in ["viewmodel", "playermodel"] {
+		if let Some(model) = weapon_data.get(key).and_then(|model| model.as_str()) {
+			if !model.trim().is_empty() {
+				references.push(model.trim().to_lowercase());
+			}
+		}
+	}
+
+	// HUD icons either use a sprite material ("file") or a character of a font ("font"), which is part of the game
+	if let Some(library::keyvalues::KeyValue::Block(icons)) = weapon_data.get("TextureData") {
+		for (_, icon) in icons {
+			if icon.get("font").is_some() {
+				continue;
+			}
+			if let Some(file) = icon.get("file").and_then(|file| file.as_str()) {
+				references.push(format!("{}.vmt", file.trim().to_lowercase().trim_end_matches(".vmt")));
+			}
+		}
+	}
+
+	if let Some(library::keyvalues::KeyValue::Block(sounds)) = weapon_data.get("SoundData") {
+		for (_, sound) in sounds {
+			if let Some(sound) = sound.as_str() {
+				let sound = sound.trim().to_lowercase();
+				if SOUND_EXTENSIONS.iter().any(|extension| sound.ends_with(extension)) {
+					references.push(sound);
+				}
+			}
+		}
+	}
+
+	return references.into_iter().unique().collect();
+
+}
+
+// Particle files are binary DMX files which store the materials of their particle systems as plain ".vmt" strings
+pub fn read_particle_materials(particle_source_file: &SourceContentFile) -> Vec<String> {
+
+	let content = match fs::read(&particle_source_file.full_path) {
+		Ok(content) => content,
+		Err(err) => {
+			warn!("Failed to read particle file \"{}\": {}", particle_source_file.full_path, err.to_string());
+			return vec!();
+		}
+	};
+
+	let material_regex = Regex::new(r"(?i)[\w\-/\\.]+\.vmt").unwrap();
+
+	return material_regex
+		.find_iter(&String::from_utf8_lossy(&content))
+		.map(|material| make_material_path(material.as_str()))
+		.unique()
+		.collect();
+
+}
+
+// Returns the particle file (.pcf) of the source paths which defines each of the particle systems, as (particle system, particle file).
+// Particle files store the names of their particle systems as null-terminated strings, so the files are searched for them.
+pub fn find_particle_system_files(particle_systems: &HashMap<String, String>, source_files: &HashMap<String, SourceContentFile>) -> Vec<(String, String)> {
+
+	let mut particle_system_files: Vec<(String, String)> = vec!();
+	let mut remaining_particle_systems: Vec<&String> = particle_systems.keys().sorted().collect();
+
+	for (particle_source_path, particle_source_file) in source_files.iter().sorted_by_key(|(source_path, _)| *source_path) {
+
+		if remaining_particle_systems.is_empty() {
+			break;
+		}
+
+		if !particle_source_path.starts_with("particles\\") || !particle_source_path.ends_with(".pcf") {
+			continue;
+		}
+
+		let content = match fs::read(&particle_source_file.full_path) {
+			Ok(content) => content,
+			Err(err) => {
+				warn!("Failed to read particle file \"{}\": {}", particle_source_file.full_path, err.to_string());
+				continue;
+			}
+		};
+
+		remaining_particle_systems.retain(|particle_system| {
+			let needle = [particle_system.as_bytes(), &[0]].concat();
+			if content.windows(needle.len()).any(|window| window.eq_ignore_ascii_case(&needle)) {
+				particle_system_files.push((particle_system.to_string(), particle_source_path.to_owned()));
+				return false;
+			}
+			return true;
+		});
+
+	}
+
+	return particle_system_files;
+
+}
+
+// Resolves the used soundscapes (including soundscapes played by other soundscapes via "playsoundscape") to their scripts and sounds
+pub fn collect_soundscape_data(soundscape_names: &HashMap<String, String>, source_files: &HashMap<String, SourceContentFile>) -> SoundscapeData {
+
+	let definitions = read_soundscape_definitions(source_files);
+	let mut data = SoundscapeData::new();
+
+	let mut queue: Vec<(String, String)> = soundscape_names.iter().map(|(name, reason)| (name.to_owned(), reason.to_owned())).collect();
+	let mut visited_soundscapes: HashSet<String> = HashSet::new();
+
+	while let Some((name, reason)) = queue.pop() {
+
+		if !visited_soundscapes.insert(name.to_owned()) {
+			continue;
+		}
+
+		let (script_path, definition) = match definitions.get(&name) {
+			Some(definition) => definition,
+			None => {
+				data.missing_soundscapes.insert(name, format!("{}, but not defined in any scripts\\soundscapes_*.txt of the source paths", reason));
+				continue;
+			}
+		};
+
+		data.used_scripts.insert(script_path.to_owned(), source_files[script_path].to_owned());
+
+		let mut waves: Vec<String> = vec!();
+		let mut nested_soundscapes: Vec<String> = vec!();
+		collect_soundscape_references(definition, &mut waves, &mut nested_soundscapes);
+
+		for wave in waves {
+			let sound_path = make_sound_path(&wave);
+			match source_files.get(&sound_path) {
+				Some(source_file) => {
+					data.used_sounds.insert(sound_path, source_file.to_owned());
+				},
+				None => {
+					data.missing_sounds.insert(sound_path, format!("Played by soundscape \"{}\" in \"{}\"", name, script_path));
+				}
+			}
+		}
+
+		for nested_soundscape in nested_soundscapes {
+			queue.push((nested_soundscape.to_lowercase(), format!("Played by soundscape \"{}\"", name)));
+		}
+
+	}
+
+	return data;
+
+}
+
+// Collects all "wave" values (playlooping, playrandom -> rndwave) and all "playsoundscape" names of a soundscape definition
+pub fn collect_soundscape_references(definition: &library::keyvalues::KeyValue, waves: &mut Vec<String>, nested_soundscapes: &mut Vec<String>) {
+
+	let children = match definition {
+		library::keyvalues::KeyValue::Block(children) => children,
+		library::keyvalues::KeyValue::Value(_) => return,
+	};
+
+	for (key, value) in children {
+
+		if key.eq_ignore_ascii_case("wave") {
+			if let Some(wave) = value.as_str() {
+				waves.push(wave.to_owned());
+			}
+			continue;
+		}
+
+		if key.eq_ignore_ascii_case("playsoundscape") {
+			if let Some(nested_soundscape) = value.get("name").and_then(|name| name.as_str()) {
+				nested_soundscapes.push(nested_soundscape.to_owned());
+			}
+			continue;
+		}
+
+		collect_soundscape_references(value, waves, nested_soundscapes);
+
+	}
+
+}
+
+// Constructs the path of a sound local to the source paths, without the leading sound characters (e.g. "*" for streamed sounds)
+pub fn make_sound_path(sound: &str) -> String {
+	return format!(
+		"sound\\{}",
+		sound
+			.trim_start_matches(|char| SOUND_CHARACTERS.contains(char))
+			.replace("/", "\\")
+			.to_lowercase()
+	);
+}
+
+// Called if a model could not be parsed, so its materials are not silently lost.
+// Tries to collect the materials from the model's .qc if it is part of the source paths.
+pub fn collect_model_materials_fallback(content_file: &SourceContentFile, source_files: &HashMap<String, SourceContentFile>)
+	-> (HashMap<String, SourceContentFile>, HashMap<String, String>)
+{
+
+	warn!("<red>The materials of model \"{}\" could not be resolved automatically, make sure to include them manually!</>", content_file.local_path);
+
+	let model_source_path = content_file.local_path.replace("/", "\\").to_lowercase();
+	let qc_source_path = format!("{}.qc", model_source_path.strip_suffix(".mdl").unwrap_or(&model_source_path));
+
+	return match source_files.get(&qc_source_path) {
+		Some(qc_source_file) => {
+			info!("Collecting materials of model \"{}\" from \"<green>{}</>\" instead...", content_file.local_path, qc_source_file.local_path);
+			collect_qc_materials(qc_source_file, content_file, source_files)
+		},
+		None => (HashMap::new(), HashMap::new()),
+	};
+
+}
+
+// Collects the materials named in $texturegroup / $renamematerial of a .qc in all of its $cdmaterials paths.
+// Materials which are only named in the .smd files of the model can not be found this way.
+pub fn collect_qc_materials(qc_source_file: &SourceContentFile, content_file: &SourceContentFile, source_files: &HashMap<String, SourceContentFile>)
+	-> (HashMap<String, SourceContentFile>, HashMap<String, String>)
+{
+
+	let mut used_materials: HashMap<String, SourceContentFile> = HashMap::new();
+	let mut missing_materials: HashMap<String, String> = HashMap::new();
+
+	let qc_content = match fs::read(&qc_source_file.full_path) {
+		Ok(content) => String::from_utf8_lossy(&content).to_string(),
+		Err(err) => {
+			warn!("Failed to read \"{}\": {}", qc_source_file.full_path, err.to_string());
+			return (used_materials, missing_materials);
+		}
+	};
+
+	let cdmaterials_regex = Regex::new(r#"(?i)\$cdmaterials\s+"?([^"\r\n]*)"?"#).unwrap();
+	let texturegroup_regex = Regex::new(r#"(?is)\$texturegroup\s+"?[^"\s{]+"?\s*\{(.*?)\}\s*\}"#).unwrap();
+	let renamematerial_regex = Regex::new(r#"(?i)\$renamematerial\s+"[^"]*"\s+"([^"]*)""#).unwrap();
+	let quoted_string_regex = Regex::new(r#""([^"]+)""#).unwrap();
+
+	let cdmaterials_list: Vec<String> = cdmaterials_regex
+		.captures_iter(&qc_content)
+		.map(|captures| normalize_cdmaterials_path(&captures[1]))
+		.collect();
+
+	let mut material_names: HashSet<String> = HashSet::new();
+	for captures in texturegroup_regex.captures_iter(&qc_content) {
+		for material_captures in quoted_string_regex.captures_iter(&captures[1]) {
+			material_names.insert(material_captures[1].to_string());
+		}
+	}
+	for captures in renamematerial_regex.captures_iter(&qc_content) {
+		material_names.insert(captures[1].to_string());
+	}
+
+	if material_names.len() == 0 {
+		warn!("\"{}\" does not name any materials via $texturegroup / $renamematerial", qc_source_file.local_path);
+	}
+
+	for material_name in &material_names {
+		for cdmaterials in &cdmaterials_list {
+
+			let source_file_path = format!("materials\\{}{}.vmt", cdmaterials, material_name)
+				.replace("/", "\\")
+				.to_lowercase();
+
+			match source_files.get(&source_file_path) {
+				Some(source_file) => {
+					used_materials.insert(source_file_path, source_file.to_owned());
+				},
+				None => {
+					missing_materials.insert(source_file_path, format!("Used by model \"{}\" (according to \"{}\")", content_file.full_path, qc_source_file.local_path));
+				}
+			}
+
+		}
+	}
+
+	return (used_materials, missing_materials);
+
+}
+
+// Constructs the materials\models\<model directory>\<material>.vmt path the engine falls back to for model materials
+pub fn make_model_fallback_material_path(model_local_path: &str, material_name: &str) -> Option<String> {
+
+	let model_local_path = model_local_path
+		.replace("/", "\\")
+		.to_lowercase();
+
+	let model_directory = match model_local_path.strip_prefix("models\\").and_then(|path| path.rsplit_once('\\')) {
+		Some((model_directory, _)) => model_directory.to_owned(),
+		None => return None,
+	};
+
+	return Some(
+		format!("materials\\models\\{}\\{}.vmt", model_directory, material_name)
+			.replace("/", "\\")
+			.to_lowercase()
+	);
+
+}
+
+// Makes sure a cdmaterials path ends with a separator so the material name can be appended to it, since some
+// models are compiled with e.g. "$cdmaterials models/props" instead of "models/props/"
+pub fn normalize_cdmaterials_path(cdmaterials: &str) -> String {
+
+	let cdmaterials = cdmaterials
+		.trim()
+		.replace("\\", "/")
+		.trim_start_matches('/')
+		.to_owned();
+
+	return match cdmaterials.is_empty() || cdmaterials.ends_with('/') {
+		true => cdmaterials,
+		false => format!("{}/", cdmaterials),
+	};
+
+}
+
+// Blend materials (e.g. WorldVertexTransition on displacements) use the "2" variants for their second layer
+pub const VMT_TEXTURE_PARAMETERS: [&str; 26] = [
+	"$basetexture",
+	"$basetexture2",
+	"$detail",
+	"$detail1",
+	"$detail2",
+	"$bumpmap",
+	"$bumpmap2",
+	"$bumpmask",
+	"$selfillummask",
+	"$selfillumtexture",
+	"$AmbientOcclTexture",
+	"$lightmap",
+	"$phongexponenttexture",
+	"$phongwarptexture",
+	"$envmap",
+	"$envmapmask",
+	"$tintmasktexture",
+	"$blendmodulatetexture",
+	"$normalmap",
+	// Shader specific: UnlitTwoTexture, SpriteCard, Refract / Water, VertexLitGeneric, Eyes / EyeRefract
+	"$texture2",
+	"$ramptexture",
+	"$dudvmap",
+	"$refracttinttexture",
+	"$lightwarptexture",
+	"$iris",
+	"$corneatexture",
+];
+
+// Texture parameters of normal maps, whose "_ssbump" / "_normal" variants are used by --fuzzy-textures
+pub const NORMAL_MAP_PARAMETERS: [&str; 3] = ["$bumpmap", "$bumpmap2", "$normalmap"];
+
+#[derive(Debug, Clone, Copy)]
+pub enum KeyValueContentKind {
+	Material,
+	// Textures (.vtf) referenced without a material
+	Texture,
+	Model,
+	Sound,
+	Scene,
+	// Color correction lookup files (.raw)
+	Raw,
+	// Vehicle scripts (scripts/vehicles/*.txt) and the content they reference
+	VehicleScript,
+}
+
+// Entity keyvalues which reference content, and what kind of content they reference
+pub const ENTITY_KEYVALUE_RULES: [(&str, KeyValueContentKind); 29] = [
+	("material", KeyValueContentKind::Material),
+	("texture", KeyValueContentKind::Material),
+	// move_rope / keyframe_rope
+	("ropematerial", KeyValueContentKind::Material),
+	// prop_dynamic / prop_physics, a single material which replaces all materials of the model (reskins)
+	("materialoverride", KeyValueContentKind::Material),
+	("scenefile", KeyValueContentKind::Scene),
+	// color_correction
+	("filename", KeyValueContentKind::Raw),
+	// prop_vehicle_jeep / prop_vehicle_airboat / prop_vehicle_prisoner_pod
+	("vehiclescript", KeyValueContentKind::VehicleScript),
+	// env_projectedtexture (cookie texture)
+	("texturename", KeyValueContentKind::Texture),
+	// env_screenoverlay
+	("overlayname1", KeyValueContentKind::Material),
+	("overlayname2", KeyValueContentKind::Material),
+	("overlayname3", KeyValueContentKind::Material),
+	("overlayname4", KeyValueContentKind::Material),
+	("overlayname5", KeyValueContentKind::Material),
+	("overlayname6", KeyValueContentKind::Material),
+	("overlayname7", KeyValueContentKind::Material),
+	("overlayname8", KeyValueContentKind::Material),
+	("overlayname9", KeyValueContentKind::Material),
+	("overlayname10", KeyValueContentKind::Material),
+	// ambient_generic / func_rotating
+	("message", KeyValueContentKind::Sound),
+	// func_door / func_door_rotating / prop_door_rotating
+	("noise1", KeyValueContentKind::Sound),
+	("noise2", KeyValueContentKind::Sound),
+	("startclosesound", KeyValueContentKind::Sound),
+	("closesound", KeyValueContentKind::Sound),
+	// func_button / func_door (numeric sound presets are skipped since they are not sound files)
+	("locked_sound", KeyValueContentKind::Sound),
+	("unlocked_sound", KeyValueContentKind::Sound),
+	// func_movelinear / func_tracktrain / func_platrot
+	("startsound", KeyValueContentKind::Sound),
+	("stopsound", KeyValueContentKind::Sound),
+	("movesound", KeyValueContentKind::Sound),
+	("movepingsound", KeyValueContentKind::Sound),
+];
+
+pub const DEFAULT_PROJECTED_TEXTURES: [&str; 1] = [
+	"materials\\effects\\flashlight001.vtf",
+];
+
+// Suffixes of the six face materials of a 2D skybox (materials/skybox/<skyname><suffix>.vmt)
+pub const SKYBOX_FACES: [&str; 6] = ["rt", "lf", "bk", "ft", "up", "dn"];
+
+// Console / loading screen backgrounds, e.g. background01.vmt and background01_widescreen.vmt
+pub const BACKGROUND_MATERIALS_PREFIX: &str = "materials\\console\\background";
+
+pub const DEFAULT_DETAIL_MATERIALS: [&str; 1] = [
+	"materials\\detail\\detailsprites.vmt",
+];
+
+pub const DEFAULT_ROPE_MATERIALS: [&str; 3] = [
+	"materials\\cable\\cable.vmt",
+	"materials\\cable\\rope.vmt",
+	"materials\\cable\\chain.vmt",
+];
+
+// World models of the Half-Life 2 item_ / weapon_ entities, which are defined by their class instead of a "model" keyvalue
+pub const ENTITY_CLASS_MODELS: [(&str, &str); 35] = [
+	("item_healthkit", "models/items/healthkit.mdl"),
+	("item_healthvial", "models/healthvial.mdl"),
+	("item_battery", "models/items/battery.mdl"),
+	("item_suit", "models/items/hevsuit.mdl"),
+	("item_ammo_pistol", "models/items/boxsrounds.mdl"),
+	("item_ammo_pistol_large", "models/items/largeboxsrounds.mdl"),
+	("item_ammo_smg1", "models/items/boxmrounds.mdl"),
+	("item_ammo_smg1_large", "models/items/largeboxmrounds.mdl"),
+	("item_ammo_smg1_grenade", "models/items/ar2_grenade.mdl"),
+	("item_ammo_ar2", "models/items/combine_rifle_cartridge01.mdl"),
+	("item_ammo_ar2_large", "models/items/combine_rifle_cartridge01.mdl"),
+	("item_ammo_ar2_altfire", "models/items/combine_rifle_ammo01.mdl"),
+	("item_ammo_357", "models/items/357ammo.mdl"),
+	("item_ammo_357_large", "models/items/357ammobox.mdl"),
+	("item_ammo_crossbow", "models/items/crossbowrounds.mdl"),
+	("item_box_buckshot", "models/items/boxbuckshot.mdl"),
+	("item_rpg_round", "models/weapons/w_missile_closed.mdl"),
+	("item_item_crate", "models/items/item_item_crate.mdl"),
+	("item_healthcharger", "models/props_combine/health_charger001.mdl"),
+	("item_suitcharger", "models/props_combine/suit_charger001.mdl"),
+	("weapon_crowbar", "models/weapons/w_crowbar.mdl"),
+	("weapon_stunstick", "models/weapons/w_stunbaton.mdl"),
+	("weapon_physcannon", "models/weapons/w_physics.mdl"),
+	("weapon_physgun", "models/weapons/w_physics.mdl"),
+	("weapon_pistol", "models/weapons/w_pistol.mdl"),
+	("weapon_357", "models/weapons/w_357.mdl"),
+	("weapon_smg1", "models/weapons/w_smg1.mdl"),
+	("weapon_ar2", "models/weapons/w_irifle.mdl"),
+	("weapon_shotgun", "models/weapons/w_shotgun.mdl"),
+	("weapon_crossbow", "models/weapons/w_crossbow.mdl"),
+	("weapon_frag", "models/weapons/w_grenade.mdl"),
+	("weapon_rpg", "models/weapons/w_rocket_launcher.mdl"),
+	("weapon_slam", "models/weapons/w_slam.mdl"),
+	("weapon_bugbait", "models/weapons/w_bugbait.mdl"),
+	("weapon_annabelle", "models/weapons/w_annabelle.mdl"),
+];
+
+pub const TOOL_MATERIALS_DIRECTORY: &str = "materials\\tools\\";
+
+pub const SCENES_IMAGE_SOURCE_PATH: &str = "scenes\\scenes.image";
+
+// Material proxy parameters which take a texture instead of a material variable
+pub const VMT_PROXY_TEXTURE_PARAMETERS: [&str; 1] = [
+	"camopatterntexture",
+];
+
+pub const SOURCE_CONTENT_DIRECTORIES: [&str; 5] = ["materials", "models", "sound", "maps", "particles"];
+
+pub const MATERIAL_EXTENSIONS: [&str; 3] = [".vmt", ".png", ".jpg"];
+
+pub const MODEL_COMPANION_EXTENSIONS: [&str; 3] = ["dx90.vtx", "phy", "vvd"];
+
+pub const WINDOWS_MAX_PATH: usize = 260;
+
+pub const CONTENT_LOG_FILE_NAME: &str = "_content_collector_log.txt";
+
+// Directories the content categories copy into (e.g. "scripts" for soundscapes, vehicle and weapon scripts, "maps" for particle manifests and nav files)
+pub const OUTPUT_CONTENT_SUBDIRECTORIES: [&str; 8] = ["materials", "maps", "models", "particles", "resource", "scenes", "scripts", "sound"];
+
+// Directories --clean removes stale files from, the other content directories are only cleaned with --clean-all-categories
+pub const CLEAN_SUBDIRECTORIES: [&str; 2] = ["materials", "models"];
+
+// First line of the source files cache, a cache with another version is ignored and rebuilt
+pub const SOURCE_FILES_CACHE_HEADER: &str = "gcli source files cache v2";
+
+// Characters which may prefix a sound path to control how the engine plays it
+pub const SOUND_CHARACTERS: &str = "*#@><^)(}$!?&~`";
+
+// Extensions of sound files, other values of sound keyvalues are soundscript names
+pub const SOUND_EXTENSIONS: [&str; 3] = [".wav", ".mp3", ".ogg"];
+
+pub const TEXTURE_SOURCE_EXTENSIONS: [&str; 2] = ["tga", "png"];
+
+pub const VMT_ENVMAP_DEFAULT_SOURCE_PATH: &str = "materials\\env_cubemap.vtf";
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+	use super::resolve::*;
+	use super::test_utils::*;
+
+	// Collects the content of the map like `vmf collect-content` with the source path, output path and arguments, without the game files
+	fn collect_map(vmf: &PathBuf, source_path: &Path, output_path: Option<&Path>, args: &[&str]) -> Vec<SummaryCategory> {
+		let options = test_options(&[&["--skip-game-check"], args].concat());
+		return collect_content(vmf, vec!(source_path.to_string_lossy().to_string()), &output_path.map(Path::to_path_buf), &options).unwrap();
+	}
+
+	// Local paths (with forward slashes) of all files inside of the directory
+	fn directory_files(directory: &Path) -> Vec<String> {
+		return WalkDir::new(directory)
+			.into_iter()
+			.map(|entry| entry.unwrap())
+			.filter(|entry| entry.file_type().is_file())
+			.map(|entry| entry.path().strip_prefix(directory).unwrap().to_string_lossy().replace("\\", "/"))
+			.sorted()
+			.collect();
+	}
+
+	#[test]
+	fn source_files_cache_is_used_until_a_directory_changes() {
+
+		let source_path = test_directory("source_files_cache_source");
+		let cache_path = test_directory("source_files_cache").join("cache.txt");
+		write_files(&source_path, &[("materials/brick/wall.vmt", "LightmappedGeneric { }")]);
+		let source_paths = vec!(source_path.to_owned());
+
+		let source_files = build_source_files_map(&source_paths, Some(&cache_path), false, None);
+		assert_eq!(source_files.keys().collect::<Vec<_>>(), vec!("materials\\brick\\wall.vmt"));
+
+		// A file which only exists in the cache shows that the unchanged source path was not walked again
+		let cache = fs::read_to_string(&cache_path).unwrap();
+		let cached_file = format!("{}\tmaterials/brick/cached.vmt\n", source_path.join("materials/brick/cached.vmt").display());
+		fs::write(&cache_path, format!("{}{}", cache, cached_file)).unwrap();
+		let source_files = build_source_files_map(&source_paths, Some(&cache_path), false, None);
+		assert_eq!(source_files.keys().sorted().collect::<Vec<_>>(), vec!("materials\\brick\\cached.vmt", "materials\\brick\\wall.vmt"));
+
+		// Adding a file changes the modification time of its directory
+		std::thread::sleep(std::time::Duration::from_millis(20));
+		write_files(&source_path, &[("materials/brick/floor.vmt", "LightmappedGeneric { }")]);
+		let source_files = build_source_files_map(&source_paths, Some(&cache_path), false, None);
+		assert_eq!(source_files.keys().sorted().collect::<Vec<_>>(), vec!("materials\\brick\\floor.vmt", "materials\\brick\\wall.vmt"));
+
+		fs::remove_dir_all(&source_path).unwrap();
+		fs::remove_dir_all(cache_path.parent().unwrap()).unwrap();
+
+	}
+
+	#[test]
+	fn blend_material_collects_both_base_textures() {
+
+		let directory = test_directory("blend");
+		write_files(&directory, &[
+			("materials/nature/blend_grass_dirt.vmt", "\"WorldVertexTransition\"\n{\n\t\"$basetexture\" \"nature/grass01\"\n\t\"$BaseTexture2\" \"nature/dirt01\"\n\t\"$surfaceprop\" \"dirt\"\n}\n"),
+			("materials/nature/grass01.vtf", "VTF"),
+			("materials/nature/dirt01.vtf", "VTF"),
+		]);
+
+		let source_files: HashMap<String, SourceContentFile> = HashMap::from([
+			source_file(&directory, "materials/nature/blend_grass_dirt.vmt"),
+			source_file(&directory, "materials/nature/grass01.vtf"),
+			source_file(&directory, "materials/nature/dirt01.vtf"),
+		]);
+
+		let open_fs = collector::open_empty_file_system().unwrap();
+		let material_data = read_material_data(&source_files["materials\\nature\\blend_grass_dirt.vmt"].full_path, &source_files, &open_fs).unwrap();
+
+		assert_eq!(material_data.used_textures.keys().sorted().collect::<Vec<_>>(), vec!("materials\\nature\\dirt01.vtf", "materials\\nature\\grass01.vtf"));
+		assert!(material_data.missing_textures.is_empty());
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn model_materials_include_non_default_skins() {
+
+		// Model with the textures "crate" / "crate_burnt", where only skin 1 uses "crate_burnt"
+		let mut mdl = vec!(0u8; 240);
+		mdl[..4].copy_from_slice(b"IDST");
+		for (offset, value) in [(204, 2), (208, 240), (220, 1), (224, 2), (228, 368)] {
+			mdl[offset..offset + 4].copy_from_slice(&(value as i32).to_le_bytes());
+		}
+		mdl.extend(vec!(0u8; 128));
+		mdl.extend([0u8, 0, 1, 0]);
+		for (texture, name) in ["crate", "crate_burnt"].iter().enumerate() {
+			let texture_offset = 240 + texture * 64;
+			let name_offset = (mdl.len() - texture_offset) as i32;
+			mdl[texture_offset..texture_offset + 4].copy_from_slice(&name_offset.to_le_bytes());
+			mdl.extend(name.as_bytes());
+			mdl.push(0);
+		}
+
+		let directory = test_directory("skins");
+		write_files(&directory, &[
+			("materials/models/props/crate.vmt", "VertexLitGeneric { }"),
+			("materials/models/props/crate_burnt.vmt", "VertexLitGeneric { }"),
+		]);
+		fs::create_dir_all(directory.join("models/props")).unwrap();
+		fs::write(directory.join("models/props/crate.mdl"), &mdl).unwrap();
+
+		let (_, model) = source_file(&directory, "models/props/crate.mdl");
+		let source_files: HashMap<String, SourceContentFile> = HashMap::from([
+			source_file(&directory, "materials/models/props/crate.vmt"),
+			source_file(&directory, "materials/models/props/crate_burnt.vmt"),
+		]);
+
+		let skin_families = library::mdl::skin_families(&fs::read(&model.full_path).unwrap()).unwrap();
+		assert_eq!(skin_families, vec!(vec!(String::from("crate")), vec!(String::from("crate_burnt"))));
+
+		let material_names: Vec<String> = skin_families.into_iter().flatten().collect();
+		let (used_materials, missing_materials) = resolve_model_materials(&model, &material_names, &vec!(String::from("models/props/")), &source_files, false);
+
+		assert_eq!(used_materials.keys().sorted().collect::<Vec<_>>(), vec!("materials\\models\\props\\crate.vmt", "materials\\models\\props\\crate_burnt.vmt"));
+		assert!(missing_materials.is_empty());
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn proxies_reference_external_materials_and_textures() {
+
+		let directory = test_directory("proxies");
+		let source_files: HashMap<String, SourceContentFile> = HashMap::from([
+			source_file(&directory, "materials/effects/monitor_static.vmt"),
+			source_file(&directory, "materials/effects/monitor_noise.vtf"),
+		]);
+
+		let material = br#""UnlitGeneric"
+		{
+			"$basetexture" "effects/monitor"
+			"Proxies"
+			{
+				"MaterialModify"
+				{
+					"material" "effects/monitor_static"
+				}
+				"AnimatedTexture"
+				{
+					"animatedtexturevar" "$basetexture"
+					"animatedtextureframenumvar" "$frame"
+					"animatedtextureframerate" "15"
+				}
+				"TextureScroll"
+				{
+					"texturescrollvar" "$texture2transform"
+					"texture2" "effects/monitor_noise"
+				}
+				"ToggleTexture"
+				{
+					"toggletexturevar" "$basetexture"
+					"texture" "effects/monitor_off"
+				}
+				"Camo"
+				{
+					"camopatterntexture" "effects/camo_pattern"
+				}
+			}
+		}"#;
+
+		let proxy_data = get_material_proxy_data(material, &source_files, "effects/monitor");
+
+		assert_eq!(proxy_data.used_materials.keys().collect::<Vec<_>>(), vec!("materials\\effects\\monitor_static.vmt"));
+		assert_eq!(proxy_data.used_textures.keys().collect::<Vec<_>>(), vec!("materials\\effects\\monitor_noise.vtf"));
+
+		// Parameter names ("$basetexture") and numbers ("15") are not references, and only values of known texture parameters are reported as missing
+		assert_eq!(proxy_data.missing_textures.keys().collect::<Vec<_>>(), vec!("materials\\effects\\camo_pattern.vtf"));
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn model_materials_fall_back_to_model_directory() {
+
+		let directory = test_directory("model_directory_fallback");
+		let model = SourceContentFile { full_path: directory.join("models/vehicles/truck.mdl").to_string_lossy().to_string(), local_path: String::from("models\\vehicles\\truck.mdl") };
+		let source_files: HashMap<String, SourceContentFile> = HashMap::from([source_file(&directory, "materials/models/vehicles/truck_body.vmt")]);
+
+		// "truck_body" is only found via materials\models\<model directory>, "truck_glass" nowhere
+		let material_names = vec!(String::from("truck_body"), String::from("truck_glass"));
+		let (used_materials, missing_materials) = resolve_model_materials(&model, &material_names, &vec!(String::from("models/props_vehicles/")), &source_files, false);
+
+		assert_eq!(used_materials.keys().collect::<Vec<_>>(), vec!("materials\\models\\vehicles\\truck_body.vmt"));
+		assert_eq!(missing_materials.keys().collect::<Vec<_>>(), vec!("materials\\models\\props_vehicles\\truck_glass.vmt"));
+
+		// The fallback path is lowercase, models outside of models\ have none
+		assert_eq!(make_model_fallback_material_path("models\\Vehicles\\Truck.mdl", "Truck_Body"), Some(String::from("materials\\models\\vehicles\\truck_body.vmt")));
+		assert_eq!(make_model_fallback_material_path("props\\truck.mdl", "truck_body"), None);
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn materials_of_models_with_unreadable_cdmaterials_fall_back_to_model_directory() {
+
+		let directory = test_directory("model_directory_fallback_cdmaterials");
+		write_files(&directory, &[("materials/models/vehicles/truck_body.vmt", "VertexLitGeneric { }")]);
+		write_minimal_model(&directory, "models/vehicles/truck.mdl", "models/props_vehicles/", "truck_body");
+
+		// Point the cdmaterials table outside of the file, so only the texture names can be read
+		let model_path = directory.join("models/vehicles/truck.mdl");
+		let mut mdl = fs::read(&model_path).unwrap();
+		mdl[216..220].copy_from_slice(&i32::MAX.to_le_bytes());
+		fs::write(&model_path, mdl).unwrap();
+
+		let model = SourceContentFile { full_path: model_path.to_string_lossy().to_string(), local_path: String::from("models\\vehicles\\truck.mdl") };
+		let source_files: HashMap<String, SourceContentFile> = HashMap::from([source_file(&directory, "materials/models/vehicles/truck_body.vmt")]);
+		let open_fs = collector::open_empty_file_system().unwrap();
+
+		let (used_materials, missing_materials) = collect_model_materials(&model, &source_files, &open_fs, false);
+		assert_eq!(used_materials.keys().collect::<Vec<_>>(), vec!("materials\\models\\vehicles\\truck_body.vmt"));
+		assert!(missing_materials.is_empty());
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn model_paths_skip_brush_models_and_add_missing_prefix() {
+
+		// Brush entities reference their brush model of the compiled map
+		assert_eq!(make_model_path("*0"), None);
+		assert_eq!(make_model_path("*12"), None);
+		assert_eq!(make_model_path("  "), None);
+
+		assert_eq!(make_model_path("props_c17/Oildrum001.mdl"), Some(String::from("models\\props_c17\\oildrum001.mdl")));
+		assert_eq!(make_model_path("models/props_c17/oildrum001.mdl"), Some(String::from("models\\props_c17\\oildrum001.mdl")));
+		assert_eq!(make_model_path("/models/props_c17/oildrum001"), Some(String::from("models\\props_c17\\oildrum001.mdl")));
+
+	}
+
+	#[test]
+	fn vehicle_script_references_models_materials_and_sounds() {
+
+		let directory = test_directory("vehicle_script");
+		fs::create_dir_all(directory.join("scripts/vehicles")).unwrap();
+		fs::write(directory.join("scripts/vehicles/buggy_custom.txt"), r#"
+			"vehicle"
+			{
+				"wheelsperaxle" "2"
+				"axle"
+				{
+					"wheel"
+					{
+						"radius" "18"
+						"model" "models/mymap/Wheel.mdl"
+					}
+				}
+			}
+			"vehicle_sounds"
+			{
+				"gear"
+				{
+					"max_speed" "0.3"
+				}
+				"state"
+				{
+					"name" "SS_START_IDLE"
+					"sound" "ATV_engine_start"
+				}
+				"crashsound"
+				{
+					"amplitude" "10"
+					"wavefile" "mymap/buggy_crash.wav"
+				}
+			}
+			"vehicle_overlay"
+			{
+				"material" "mymap/buggy_hud.vmt"
+			}
+		"#).unwrap();
+
+		let (_, vehicle_script) = source_file(&directory, "scripts/vehicles/buggy_custom.txt");
+		let references: Vec<String> = read_vehicle_script_references(&vehicle_script).into_iter().sorted().collect();
+
+		// Sounds referenced by soundscript names (e.g. "ATV_engine_start") are not files
+		assert_eq!(references, vec!("models/mymap/wheel.mdl", "mymap/buggy_crash.wav", "mymap/buggy_hud.vmt"));
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn break_models_are_collected_without_cycles() {
+
+		// Model whose $keyvalues text (index / size at 312 / 316) follows the header
+		let model_with_keyvalues = |keyvalues: &str| -> Vec<u8> {
+			let mut mdl = vec!(0u8; 320);
+			mdl[..4].copy_from_slice(b"IDST");
+			mdl[312..316].copy_from_slice(&320i32.to_le_bytes());
+			mdl[316..320].copy_from_slice(&(keyvalues.len() as i32 + 1).to_le_bytes());
+			mdl.extend(keyvalues.as_bytes());
+			mdl.push(0);
+			return mdl;
+		};
+
+		let directory = test_directory("break_models");
+		fs::create_dir_all(directory.join("models/mymap/gibs")).unwrap();
+		fs::write(directory.join("models/mymap/crate.mdl"), model_with_keyvalues("\"prop_data\" { \"base\" \"Wooden.Medium\" } \"break\" { \"model\" \"mymap/gibs/crate_gib1\" } \"break\" { \"model\" \"models/mymap/gibs/crate_gib2.mdl\" }")).unwrap();
+		// The gib breaks into the crate again
+		fs::write(directory.join("models/mymap/gibs/crate_gib1.mdl"), model_with_keyvalues("\"break\" { \"model\" \"models/mymap/crate.mdl\" }")).unwrap();
+
+		let source_files: HashMap<String, SourceContentFile> = HashMap::from([
+			source_file(&directory, "models/mymap/crate.mdl"),
+			source_file(&directory, "models/mymap/gibs/crate_gib1.mdl"),
+		]);
+		let mut used_models: HashMap<String, SourceContentFile> = HashMap::from([source_file(&directory, "models/mymap/crate.mdl")]);
+		let mut missing_models: HashMap<String, String> = HashMap::new();
+
+		assert_eq!(collect_break_models(&mut used_models, &mut missing_models, &source_files), 1);
+		assert_eq!(used_models.keys().sorted().collect::<Vec<_>>(), vec!("models\\mymap\\crate.mdl", "models\\mymap\\gibs\\crate_gib1.mdl"));
+		assert_eq!(missing_models.keys().collect::<Vec<_>>(), vec!("models\\mymap\\gibs\\crate_gib2.mdl"));
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn models_materials_of_many_models_match_sequential_collection() {
+
+		let directory = test_directory("many_models");
+		let mut models: HashMap<String, SourceContentFile> = HashMap::new();
+		let mut source_files: HashMap<String, SourceContentFile> = HashMap::new();
+
+		// Every second material exists
+		for index in 0..200 {
+			let model_local_path = format!("models/generated/prop_{}.mdl", index);
+			write_minimal_model(&directory, &model_local_path, "models/generated/", &format!("prop_{}", index));
+			models.extend([source_file(&directory, &model_local_path)]);
+			if index % 2 == 0 {
+				let material_local_path = format!("materials/models/generated/prop_{}.vmt", index);
+				write_files(&directory, &[(&material_local_path, "VertexLitGeneric { }")]);
+				source_files.extend([source_file(&directory, &material_local_path)]);
+			}
+		}
+
+		// The models are parsed, not read via the .qc fallback
+		let (_, model) = source_file(&directory, "models/generated/prop_42.mdl");
+		assert!(plumber_core::mdl::Model::read(Path::new(&model.full_path), &collector::open_empty_file_system().unwrap()).unwrap().verify().is_ok());
+
+		let open_fs = collector::open_empty_file_system().unwrap();
+		let (used_materials, missing_materials) = collect_models_materials(&models, &source_files, &open_fs, false, 4);
+		let (sequential_used_materials, sequential_missing_materials) = collect_models_materials(&models, &source_files, &open_fs, false, 1);
+
+		assert_eq!(used_materials.len(), 100);
+		assert_eq!(missing_materials.len(), 100);
+		assert_eq!(used_materials.keys().sorted().collect::<Vec<_>>(), sequential_used_materials.keys().sorted().collect::<Vec<_>>());
+		assert_eq!(missing_materials.keys().sorted().collect::<Vec<_>>(), sequential_missing_materials.keys().sorted().collect::<Vec<_>>());
+		assert!(used_materials.contains_key("materials\\models\\generated\\prop_42.vmt"));
+		assert!(missing_materials.contains_key("materials\\models\\generated\\prop_43.vmt"));
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn weapon_script_references_models_icons_and_sound_files() {
+
+		let directory = test_directory("weapon_script");
+		write_files(&directory, &[("scripts/weapon_mymap_rifle.txt", concat!(
+			"WeaponData\n{\n",
+			"\t\"printname\" \"Rifle\"\n",
+			"\t\"viewmodel\" \"models/weapons/v_Mymap_Rifle.mdl\"\n",
+			"\t\"playermodel\" \"models/weapons/w_mymap_rifle.mdl\"\n",
+			"\tSoundData\n\t{\n\t\t\"single_shot\" \"Weapon_SMG1.Single\"\n\t\t\"reload\" \"weapons/mymap_rifle/reload.wav\"\n\t}\n",
+			"\tTextureData\n\t{\n",
+			"\t\t\"weapon\" { \"font\" \"WeaponIcons\" \"character\" \"a\" }\n",
+			"\t\t\"ammo\" { \"file\" \"sprites/mymap/rifle_ammo\" \"x\" \"0\" \"y\" \"0\" }\n",
+			"\t}\n}\n",
+		))]);
+
+		let (_, weapon_script) = source_file(&directory, "scripts/weapon_mymap_rifle.txt");
+
+		// Soundscript names and font icons are part of the game
+		assert_eq!(read_weapon_script_references(&weapon_script), vec!(
+			"models/weapons/v_mymap_rifle.mdl",
+			"models/weapons/w_mymap_rifle.mdl",
+			"sprites/mymap/rifle_ammo.vmt",
+			"weapons/mymap_rifle/reload.wav",
+		));
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn custom_envmap_is_collected_unlike_env_cubemap() {
+
+		let directory = test_directory("envmap");
+		write_files(&directory, &[
+			("materials/mymap/glass.vmt", "\"LightmappedGeneric\"\n{\n\t\"$basetexture\" \"mymap/glass\"\n\t\"$envmap\" \"materials/mymap/cubemaps/lobby.vtf\"\n}\n"),
+			("materials/mymap/metal.vmt", "\"VertexLitGeneric\"\n{\n\t\"$basetexture\" \"mymap/glass\"\n\t\"$envmap\" \"Env_Cubemap\"\n}\n"),
+		]);
+		let source_files: HashMap<String, SourceContentFile> = HashMap::from([
+			source_file(&directory, "materials/mymap/glass.vmt"),
+			source_file(&directory, "materials/mymap/metal.vmt"),
+			source_file(&directory, "materials/mymap/glass.vtf"),
+			source_file(&directory, "materials/mymap/cubemaps/lobby.vtf"),
+		]);
+		let open_fs = collector::open_empty_file_system().unwrap();
+
+		let glass_data = read_material_data(&source_files["materials\\mymap\\glass.vmt"].full_path, &source_files, &open_fs).unwrap();
+		assert_eq!(glass_data.used_textures.keys().sorted().collect::<Vec<_>>(), vec!("materials\\mymap\\cubemaps\\lobby.vtf", "materials\\mymap\\glass.vtf"));
+
+		// env_cubemap is replaced by the cubemaps built into the map
+		let metal_data = read_material_data(&source_files["materials\\mymap\\metal.vmt"].full_path, &source_files, &open_fs).unwrap();
+		assert_eq!(metal_data.used_textures.keys().collect::<Vec<_>>(), vec!("materials\\mymap\\glass.vtf"));
+		assert!(metal_data.missing_textures.is_empty());
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn nodraw_faces_are_skipped_unless_overridden() {
+
+		let directory = test_directory("nodraw");
+		let mut source_files: HashMap<String, SourceContentFile> = HashMap::new();
+
+		assert_eq!(make_side_material_path("TOOLS/TOOLSNODRAW", &source_files), None);
+		assert_eq!(make_side_material_path("tools/toolsskybox", &source_files), None);
+		assert_eq!(make_side_material_path("Brick/BrickWall001", &source_files), Some(String::from("materials\\brick\\brickwall001.vmt")));
+
+		// Custom tool materials of the source paths are collected
+		source_files.extend([source_file(&directory, "materials/tools/toolsnodraw.vmt")]);
+		assert_eq!(make_side_material_path("TOOLS/TOOLSNODRAW", &source_files), Some(String::from("materials\\tools\\toolsnodraw.vmt")));
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn model_sound_events_collect_sound_files() {
+
+		// One sequence at 400 with two events at 400 + 212, whose options are the sound
+		let mut mdl = vec!(0u8; 400 + library::mdl::SEQUENCE_SIZE + 2 * library::mdl::EVENT_SIZE);
+		mdl[..4].copy_from_slice(b"IDST");
+		mdl[188..192].copy_from_slice(&1i32.to_le_bytes());
+		mdl[192..196].copy_from_slice(&400i32.to_le_bytes());
+		mdl[400 + 24..400 + 28].copy_from_slice(&2i32.to_le_bytes());
+		mdl[400 + 28..400 + 32].copy_from_slice(&(library::mdl::SEQUENCE_SIZE as i32).to_le_bytes());
+		for (event, options) in ["NPC/MyMap/Footstep1.wav", "NPC_Citizen.RunFootstepLeft"].iter().enumerate() {
+			let options_offset = 400 + library::mdl::SEQUENCE_SIZE + event * library::mdl::EVENT_SIZE + 12;
+			mdl[options_offset..options_offset + options.len()].copy_from_slice(options.as_bytes());
+		}
+
+		let directory = test_directory("model_sounds");
+		fs::create_dir_all(directory.join("models/mymap")).unwrap();
+		fs::write(directory.join("models/mymap/walker.mdl"), &mdl).unwrap();
+
+		let models: HashMap<String, SourceContentFile> = HashMap::from([source_file(&directory, "models/mymap/walker.mdl")]);
+		let source_files: HashMap<String, SourceContentFile> = HashMap::from([source_file(&directory, "sound/npc/mymap/footstep1.wav")]);
+
+		// The soundscript name can not be resolved to a file
+		assert_eq!(read_model_sound_events(&models["models\\mymap\\walker.mdl"]), vec!("npc/mymap/footstep1.wav"));
+
+		let (used_sounds, missing_sounds) = collect_models_sounds(&models, &source_files);
+		assert_eq!(used_sounds.keys().collect::<Vec<_>>(), vec!("sound\\npc\\mymap\\footstep1.wav"));
+		assert!(missing_sounds.is_empty());
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn skybox_check_reports_faces_missing_in_source_and_game() {
+
+		let face_source_paths = make_skybox_face_paths("sky_mymap_dusk");
+		assert_eq!(face_source_paths.len(), 6);
+		assert_eq!(face_source_paths[0], "materials\\skybox\\sky_mymap_duskrt.vmt");
+		assert_eq!(face_source_paths[5], "materials\\skybox\\sky_mymap_duskdn.vmt");
+
+		// Missing materials which were found in the game are no longer part of missing_materials
+		let missing_materials: HashMap<String, String> = HashMap::from([
+			(String::from("materials\\skybox\\sky_mymap_duskup.vmt"), String::from("Face of the 2D skybox \"sky_mymap_dusk\"")),
+			(String::from("materials\\mymap\\unrelated.vmt"), String::from("Used by world brush / solid 3")),
+		]);
+		assert_eq!(missing_skybox_faces(&face_source_paths, &missing_materials), vec!("materials\\skybox\\sky_mymap_duskup.vmt"));
+		assert!(missing_skybox_faces(&face_source_paths, &HashMap::new()).is_empty());
+
+	}
+
+	#[test]
+	fn material_roots_are_found_as_materials() {
+
+		let directory = test_directory("material_roots");
+		write_files(&directory, &[
+			("Content/Materials/Brick/Wall.vmt", "LightmappedGeneric { }"),
+			("content/materials/brick/wall.vtf", "VTF"),
+			("content/materials/metal/floor.vmt", "LightmappedGeneric { }"),
+			("materials/metal/floor.vmt", "LightmappedGeneric { }"),
+		]);
+
+		let mut source_files = build_source_files_map(&vec!(directory.to_owned()), None, false, None);
+		assert_eq!(add_material_root_files(&mut source_files, &vec!(String::from("content/materials/"))), 2);
+
+		let wall = &source_files["materials\\brick\\wall.vmt"];
+		assert_eq!(PathBuf::from(&wall.full_path), directory.join("Content/Materials/Brick/Wall.vmt"));
+		assert_eq!(PathBuf::from(&wall.local_path), Path::new("materials").join("Brick").join("Wall.vmt"));
+		assert!(source_files.contains_key("materials\\brick\\wall.vtf"));
+
+		// Files inside of materials\ take precedence
+		assert_eq!(PathBuf::from(&source_files["materials\\metal\\floor.vmt"].full_path), directory.join("materials/metal/floor.vmt"));
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn patch_material_collects_inserted_textures() {
+
+		let directory = test_directory("patch_material");
+		let source_files: HashMap<String, SourceContentFile> = HashMap::from([source_file(&directory, "materials/mymap/detail/plaster_detail.vtf")]);
+
+		let patch_material = br#"patch
+		{
+			include "materials/concrete/concretewall001a.vmt"
+			insert
+			{
+				$detail "mymap/detail/plaster_detail"
+				$detailscale 4
+				$bumpmap "mymap/plaster_normal"
+			}
+			replace
+			{
+				$envmap env_cubemap
+			}
+		}"#;
+
+		let patch_data = get_material_patch_data(patch_material, &source_files, "mymap/plaster");
+
+		// Parameters which are no textures ($detailscale) and the default cubemap are skipped
+		assert_eq!(patch_data.used_textures.keys().collect::<Vec<_>>(), vec!("materials\\mymap\\detail\\plaster_detail.vtf"));
+		assert_eq!(patch_data.missing_textures.keys().collect::<Vec<_>>(), vec!("materials\\mymap\\plaster_normal.vtf"));
+		assert!(patch_data.missing_normal_maps.contains("materials\\mymap\\plaster_normal.vtf"));
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn cdmaterials_paths_are_normalized() {
+		assert_eq!(normalize_cdmaterials_path("models/props_junk"), "models/props_junk/");
+		assert_eq!(normalize_cdmaterials_path("models/props_junk/"), "models/props_junk/");
+		assert_eq!(normalize_cdmaterials_path(" \\models\\props_junk\\ "), "models/props_junk/");
+		assert_eq!(normalize_cdmaterials_path(""), "");
+	}
+
+	#[test]
+	fn cdmaterials_without_trailing_slash_resolve_model_materials() {
+
+		let model = SourceContentFile { full_path: String::from("/addon/models/crates/crate.mdl"), local_path: String::from("models\\crates\\crate.mdl") };
+		let material = SourceContentFile { full_path: String::from("/addon/materials/models/props_junk/wood_crate.vmt"), local_path: String::from("materials\\models\\props_junk\\wood_crate.vmt") };
+		let source_files = HashMap::from([(String::from("materials\\models\\props_junk\\wood_crate.vmt"), material)]);
+
+		let cdmaterials_list = vec![normalize_cdmaterials_path("models\\props_junk")];
+		let (used_materials, missing_materials) = resolve_model_materials(&model, &vec![String::from("Wood_Crate")], &cdmaterials_list, &source_files, false);
+
+		assert!(used_materials.contains_key("materials\\models\\props_junk\\wood_crate.vmt"));
+		assert!(missing_materials.is_empty());
+
+	}
+
+	#[test]
+	fn material_case_mismatch_is_reported_for_differently_cased_texture() {
+
+		let directory = test_directory("material_case_mismatch");
+		write_files(&directory, &[
+			("materials/brick/wall.vmt", "\"LightmappedGeneric\"\n{\n\t\"$basetexture\" \"brick/MyTexture\"\n\t\"$bumpmap\" \"brick/mytexture_normal\"\n}\n"),
+			("materials/brick/mytexture.vtf", ""),
+			("materials/brick/mytexture_normal.vtf", ""),
+		]);
+
+		let materials = HashMap::from([source_file(&directory, "materials/brick/wall.vmt")]);
+		let source_files: HashMap<String, SourceContentFile> = HashMap::from([
+			source_file(&directory, "materials/brick/mytexture.vtf"),
+			source_file(&directory, "materials/brick/mytexture_normal.vtf"),
+		]);
+
+		let mismatches = find_material_case_mismatches(&materials, &source_files);
+
+		assert_eq!(mismatches.len(), 1);
+		let (material_path, param_key, param_value, texture_path) = &mismatches[0];
+		assert_eq!(material_path, "materials\\brick\\wall.vmt");
+		assert_eq!(param_key, "$basetexture");
+		assert_eq!(param_value, "brick/MyTexture");
+		assert_eq!(texture_path, "materials\\brick\\mytexture.vtf");
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn case_mismatches_of_the_collected_materials_are_found() {
+
+		let source_path = test_directory("material_case_collect");
+		write_files(&source_path, &[
+			("materials/brick/wall.vmt", "LightmappedGeneric\n{\n\t$basetexture brick/MyTexture\n}\n"),
+			("materials/brick/floor.vmt", "LightmappedGeneric\n{\n\t$basetexture brick/floor\n}\n"),
+			("materials/brick/mytexture.vtf", "VTF"),
+			("materials/brick/floor.vtf", "VTF"),
+		]);
+		let vmf = write_map(&source_path, "rp_harbor", &[], &["brick/wall", "brick/floor"], &[]);
+
+		let open_fs = collector::open_empty_file_system().unwrap();
+		let source_files = build_source_files_map(&vec!(source_path.to_owned()), None, false, None);
+		let options = test_options(&["--materials-case-report"]);
+		let context = test_context(&source_files, &open_fs, &options);
+
+		let content = resolve_content(&context, &vmf).unwrap();
+
+		// The texture resolves locally, so it is collected, but the reference is reported
+		assert_eq!(content.materials_data.used_textures.keys().sorted().collect::<Vec<_>>(), vec!("materials\\brick\\floor.vtf", "materials\\brick\\mytexture.vtf"));
+		let mismatches: Vec<(String, String, String, String)> = find_material_case_mismatches(&content.used_materials, context.source_files)
+			.into_iter()
+			.map(|(material_path, param_key, param_value, texture_path)| (material_path.replace("\\", "/"), param_key, param_value, texture_path.replace("\\", "/")))
+			.collect();
+		assert_eq!(mismatches, vec!((String::from("materials/brick/wall.vmt"), String::from("$basetexture"), String::from("brick/MyTexture"), String::from("materials/brick/mytexture.vtf"))));
+
+		fs::remove_dir_all(&source_path).unwrap();
+
+	}
+
+	#[test]
+	fn overlay_material_textures_are_collected() {
+
+		let overlay_rules = entity_keyvalue_rules("info_overlay", &HashMap::new());
+		let (material_key, material_kind) = overlay_rules.iter().find(|(key, _)| key == "material").unwrap();
+		let (material_source_path, _) = keyvalue_content_path(material_key, *material_kind, "overlays/MyMap/graffiti_01").unwrap();
+
+		let directory = test_directory("overlay");
+		write_files(&directory, &[
+			("materials/overlays/mymap/graffiti_01.vmt", "\"LightmappedGeneric\"\n{\n\t\"$basetexture\" \"overlays/mymap/graffiti_01\"\n\t\"$decal\" \"1\"\n\t\"$decalscale\" \"0.25\"\n}\n"),
+			("materials/overlays/mymap/graffiti_01.vtf", "VTF"),
+		]);
+		let source_files: HashMap<String, SourceContentFile> = HashMap::from([
+			source_file(&directory, "materials/overlays/mymap/graffiti_01.vmt"),
+			source_file(&directory, "materials/overlays/mymap/graffiti_01.vtf"),
+		]);
+
+		// The overlay material is added while collecting the entities, after the brush materials
+		let mut used_materials: HashMap<String, SourceContentFile> = HashMap::new();
+		used_materials.insert(material_source_path.to_owned(), source_files[&material_source_path].to_owned());
+
+		let open_fs = collector::open_empty_file_system().unwrap();
+		let material_data = collector::collect_materials_data(&mut used_materials, true, |source_file| read_material_data(&source_file.full_path, &source_files, &open_fs)).unwrap();
+
+		assert!(material_data.used_textures.contains_key("materials\\overlays\\mymap\\graffiti_01.vtf"));
+		assert!(material_data.missing_textures.is_empty());
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn scene_references_actor_models_and_sound_files() {
+
+		let (scene_source_path, _) = keyvalue_content_path("SceneFile", KeyValueContentKind::Scene, "scenes/MyMap/intro.vcd").unwrap();
+		assert_eq!(scene_source_path, "scenes\\mymap\\intro.vcd");
+		assert_eq!(make_scene_path("mymap/intro"), scene_source_path);
+
+		let directory = test_directory("scene");
+		write_files(&directory, &[(
+			"scenes/mymap/intro.vcd",
+			concat!(
+				"// Choreo version 1\n",
+				"actor \"narrator\"\n{\n",
+				"  channel \"audio\"\n  {\n",
+				"    event speak \"welcome\"\n    {\n      time 0.000000 2.500000\n      param \"mymap/vo/Welcome.wav\"\n      fixedlength\n      cctype \"cc_master\"\n    }\n",
+				"    event speak \"greeting\"\n    {\n      time 3.000000 4.000000\n      param \"Narrator.Greeting\"\n    }\n",
+				"  }\n",
+				"  faceposermodel \"models/MyMap/narrator.mdl\"\n",
+				"}\n",
+			),
+		)]);
+		let (_, scene_source_file) = source_file(&directory, "scenes/mymap/intro.vcd");
+
+		// The soundscript name of the second event can not be resolved to a file
+		let references = read_scene_references(&scene_source_file);
+		assert_eq!(references, vec!("models/mymap/narrator.mdl", "mymap/vo/welcome.wav"));
+		assert_eq!(make_model_path(&references[0]), Some(String::from("models\\mymap\\narrator.mdl")));
+		assert_eq!(make_sound_path(&references[1]), "sound\\mymap\\vo\\welcome.wav");
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn absolute_and_backslashed_texture_references_are_not_portable() {
+
+		assert_eq!(path_portability_problem("C:\\Users\\mapper\\Desktop\\brick.vtf"), Some("absolute path with a drive letter"));
+		assert_eq!(path_portability_problem("d:/sdk/materials/brick"), Some("absolute path with a drive letter"));
+		assert_eq!(path_portability_problem("/brick/wall"), Some("leading slash"));
+		assert_eq!(path_portability_problem("brick\\wall"), Some("backslash instead of a forward slash"));
+		assert_eq!(path_portability_problem(" brick/wall "), None);
+
+		let directory = test_directory("strict_paths");
+		write_files(&directory, &[
+			("materials/brick/absolute.vmt", "VertexLitGeneric\n{\n\t$basetexture \"C:/Users/mapper/gmod/materials/brick/wall\"\n\t$bumpmap brick/wall_normal\n\t$surfaceprop brick\n}\n"),
+			("materials/brick/portable.vmt", "LightmappedGeneric\n{\n\t$basetexture brick/wall\n\tLightmappedGeneric_DX8\n\t{\n\t\t$basetexture \"brick\\wall_dx8\"\n\t}\n}\n"),
+		]);
+		let materials: HashMap<String, SourceContentFile> = HashMap::from([
+			source_file(&directory, "materials/brick/absolute.vmt"),
+			source_file(&directory, "materials/brick/portable.vmt"),
+		]);
+
+		// Nested fallback blocks are checked as well
+		assert_eq!(find_non_portable_material_references(&materials), vec!(
+			(String::from("materials\\brick\\absolute.vmt"), String::from("$basetexture"), String::from("C:/Users/mapper/gmod/materials/brick/wall"), "absolute path with a drive letter"),
+			(String::from("materials\\brick\\portable.vmt"), String::from("$basetexture"), String::from("brick\\wall_dx8"), "backslash instead of a forward slash"),
+		));
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn non_portable_references_of_a_map_resolve_but_are_reported() {
+
+		let source_path = test_directory("strict_paths_collect");
+		write_files(&source_path, &[
+			("materials/brick/wall.vmt", "LightmappedGeneric\n{\n\t$basetexture \"brick\\wall\"\n}\n"),
+			("materials/brick/wall.vtf", "VTF"),
+			("materials/hud/mymap_icon.vmt", "UnlitGeneric { }"),
+			("lua/autorun/client/mymap_hud.lua", "local icon = Material(\"/hud/mymap_icon\")\n"),
+		]);
+		let vmf = write_map(&source_path, "rp_harbor", &[], &["brick/wall"], &[]);
+
+		let open_fs = collector::open_empty_file_system().unwrap();
+		let source_files = build_source_files_map(&vec!(source_path.to_owned()), None, false, None);
+
+		let options = test_options(&["--scan-lua"]);
+		let content = resolve_content(&test_context(&source_files, &open_fs, &options), &vmf).unwrap();
+		assert!(content.non_portable_lua_references.is_empty());
+
+		let options = test_options(&["--scan-lua", "--strict-paths"]);
+		let content = resolve_content(&test_context(&source_files, &open_fs, &options), &vmf).unwrap();
+
+		// Both references resolve locally
+		assert!(content.used_materials.contains_key("materials\\hud\\mymap_icon.vmt"));
+		assert!(content.materials_data.used_textures.contains_key("materials\\brick\\wall.vtf"));
+
+		let material_problems: Vec<(String, &str)> = find_non_portable_material_references(&content.used_materials).into_iter().map(|(_, _, reference, problem)| (reference, problem)).collect();
+		assert_eq!(material_problems, vec!((String::from("brick\\wall"), "backslash instead of a forward slash")));
+		let lua_problems: Vec<(&str, &str, &str)> = content.non_portable_lua_references.iter().map(|(_, function, reference, problem)| (*function, reference.as_str(), *problem)).collect();
+		assert_eq!(lua_problems, vec!(("Material", "/hud/mymap_icon", "leading slash")));
+
+		fs::remove_dir_all(&source_path).unwrap();
+
+	}
+
+	#[test]
+	fn texture_paths_are_normalized_relative_to_materials() {
+
+		assert_eq!(make_texture_path("Brick/Wall01"), "materials\\brick\\wall01.vtf");
+		assert_eq!(make_texture_path(" maps/rp_harbor/c-128_256_64.vtf "), "materials\\maps\\rp_harbor\\c-128_256_64.vtf");
+		assert_eq!(make_texture_path("materials/cubemaps/harbor_hdr"), "materials\\cubemaps\\harbor_hdr.vtf");
+		assert_eq!(make_texture_path("\\Materials\\skybox\\harbor_up"), "materials\\skybox\\harbor_up.vtf");
+
+	}
+
+	#[test]
+	fn output_path_placeholders_write_each_map_into_its_own_directory() {
+
+		let source_path = test_directory("output_placeholders_source");
+		let output_path = test_directory("output_placeholders_output");
+		write_files(&source_path, &[("materials/mymap/floor.vmt", "LightmappedGeneric { }"), ("materials/mymap/wall.vmt", "LightmappedGeneric { }")]);
+		let harbor_vmf = write_map(&source_path, "rp_harbor", &[], &["mymap/floor"], &[]);
+		let canals_vmf = write_map(&source_path, "rp_canals", &[], &["mymap/wall"], &[]);
+
+		// The directories of the maps do not exist yet
+		let output_path_template = output_path.join("{name}_{ext}");
+		collect_map(&harbor_vmf, &source_path, Some(&output_path_template), &[]);
+		collect_map(&canals_vmf, &source_path, Some(&output_path_template), &[]);
+
+		assert_eq!(directory_files(&output_path), vec!("rp_canals_vmf/materials/mymap/wall.vmt", "rp_harbor_vmf/materials/mymap/floor.vmt"));
+
+		fs::remove_dir_all(&source_path).unwrap();
+		fs::remove_dir_all(&output_path).unwrap();
+
+	}
+
+	#[test]
+	fn flatten_case_writes_all_collected_content_in_lowercase() {
+
+		let source_path = test_directory("flatten_case_collect_source");
+		let output_path = test_directory("flatten_case_collect_output");
+		write_files(&source_path, &[
+			("Materials/Brick/BrickWall01.VMT", "LightmappedGeneric\n{\n\t$basetexture Brick/BrickWall01\n}\n"),
+			("Materials/Brick/BrickWall01.vtf", "VTF"),
+		]);
+		let vmf = write_map(&source_path, "rp_harbor", &[], &["BRICK/BRICKWALL01"], &[]);
+
+		let summary_categories = collect_map(&vmf, &source_path, Some(&output_path), &["--flatten-case"]);
+
+		assert_eq!(summary_counts(&summary_categories, "Materials"), (1, 0));
+		assert_eq!(summary_counts(&summary_categories, "Textures"), (1, 0));
+		assert_eq!(directory_files(&output_path), vec!("materials/brick/brickwall01.vmt", "materials/brick/brickwall01.vtf"));
+		assert_eq!(fs::read_to_string(output_path.join("materials/brick/brickwall01.vtf")).unwrap(), "VTF");
+
+		// Without the flag, the casing of the source paths is kept
+		let output_path_cased = test_directory("flatten_case_collect_output_cased");
+		collect_map(&vmf, &source_path, Some(&output_path_cased), &[]);
+		assert_eq!(directory_files(&output_path_cased), vec!("Materials/Brick/BrickWall01.VMT", "Materials/Brick/BrickWall01.vtf"));
+
+		fs::remove_dir_all(&source_path).unwrap();
+		fs::remove_dir_all(&output_path).unwrap();
+		fs::remove_dir_all(&output_path_cased).unwrap();
+
+	}
+
+	#[test]
+	fn collection_into_a_locked_output_path_waits_or_fails() {
+
+		let source_path = test_directory("locked_output_source");
+		let output_path = test_directory("locked_output_output");
+		write_files(&source_path, &[("materials/mymap/floor.vmt", "LightmappedGeneric { }")]);
+		let vmf = write_map(&source_path, "rp_harbor", &[], &["mymap/floor"], &[]);
+		let source_path_strings = vec!(source_path.to_string_lossy().to_string());
+
+		// Another run is collecting into the output path
+		let lock = library::lock::DirectoryLock::acquire(&output_path, false).unwrap();
+
+		assert!(collect_content(&vmf, source_path_strings.to_owned(), &Some(output_path.to_owned()), &test_options(&["--skip-game-check"])).is_none());
+		assert_eq!(directory_files(&output_path), vec!(library::lock::LOCK_FILE_NAME));
+
+		// With --wait, the collection starts once the other run released the lock
+		let waiting_run = std::thread::spawn({
+			let (vmf, output_path) = (vmf.to_owned(), output_path.to_owned());
+			move || collect_content(&vmf, source_path_strings, &Some(output_path), &test_options(&["--skip-game-check", "--wait"])).is_some()
+		});
+		std::thread::sleep(std::time::Duration::from_millis(200));
+		drop(lock);
+		assert!(waiting_run.join().unwrap());
+
+		// The lock of the finished run is released as well
+		assert_eq!(directory_files(&output_path), vec!("materials/mymap/floor.vmt"));
+
+		fs::remove_dir_all(&source_path).unwrap();
+		fs::remove_dir_all(&output_path).unwrap();
+
+	}
+
+	#[test]
+	fn normal_map_variants_are_copied_with_fuzzy_textures() {
+
+		let source_path = test_directory("fuzzy_textures_collect_source");
+		let output_path = test_directory("fuzzy_textures_collect_output");
+		write_files(&source_path, &[
+			("materials/brick/wall.vmt", "LightmappedGeneric\n{\n\t$basetexture brick/wall\n\t$bumpmap brick/wall_normal\n}\n"),
+			("materials/brick/wall.vtf", "VTF"),
+			("materials/brick/wall_ssbump.vtf", "VTF"),
+		]);
+		let vmf = write_map(&source_path, "rp_harbor", &[], &["brick/wall"], &[]);
+
+		let summary_categories = collect_map(&vmf, &source_path, None, &[]);
+		assert_eq!(summary_counts(&summary_categories, "Textures"), (1, 1));
+
+		let summary_categories = collect_map(&vmf, &source_path, Some(&output_path), &["--fuzzy-textures"]);
+		assert_eq!(summary_counts(&summary_categories, "Textures"), (2, 0));
+		assert_eq!(directory_files(&output_path), vec!("materials/brick/wall.vmt", "materials/brick/wall.vtf", "materials/brick/wall_normal.vtf"));
+
+		fs::remove_dir_all(&source_path).unwrap();
+		fs::remove_dir_all(&output_path).unwrap();
+
+	}
+
+	#[test]
+	fn interrupted_collection_is_resumed_into_the_same_output_path() {
+
+		let source_path = test_directory("resume_collect_source");
+		let output_path = test_directory("resume_collect_output");
+		write_files(&source_path, &[
+			("materials/brick/wall.vmt", "LightmappedGeneric\n{\n\t$basetexture \"brick/wall\"\n}\n"),
+			("materials/brick/wall.vtf", "VTF texture of the wall"),
+			("sound/mymap/gulls.wav", "RIFF sound of the gulls"),
+		]);
+		let vmf = write_map(&source_path, "rp_harbor", &[], &["brick/wall"], &[&[("classname", "ambient_generic"), ("message", "mymap/gulls.wav")]]);
+
+		collect_map(&vmf, &source_path, Some(&output_path), &[]);
+
+		// Simulate an interruption: the texture was being written and the sound was never copied,
+		// the complete material is marked to tell whether it is copied again
+		fs::write(output_path.join("materials/brick/wall.vmt"), "LIGHTMAPPEDGENERIC\n{\n\t$basetexture \"brick/wall\"\n}\n").unwrap();
+		fs::write(output_path.join("materials/brick/wall.vtf"), "VTF tex").unwrap();
+		fs::remove_file(output_path.join("sound/mymap/gulls.wav")).unwrap();
+
+		let summary_categories = collect_map(&vmf, &source_path, Some(&output_path), &["--resume"]);
+
+		assert_eq!(summary_counts(&summary_categories, "Materials"), (1, 0));
+		assert_eq!(summary_counts(&summary_categories, "Sounds"), (1, 0));
+		assert!(fs::read_to_string(output_path.join("materials/brick/wall.vmt")).unwrap().starts_with("LIGHTMAPPEDGENERIC"));
+		assert_eq!(fs::read_to_string(output_path.join("materials/brick/wall.vtf")).unwrap(), "VTF texture of the wall");
+		assert_eq!(fs::read_to_string(output_path.join("sound/mymap/gulls.wav")).unwrap(), "RIFF sound of the gulls");
+
+		fs::remove_dir_all(&source_path).unwrap();
+		fs::remove_dir_all(&output_path).unwrap();
+
+	}
+
+}
@@ -0,0 +1,597 @@
+use std::{collections::{HashMap, HashSet}, fs, path::{Path, PathBuf}};
+use crate::library;
+use crate::library::collector::{self, SourceContentFile};
+use crate::library::log::{info, success, warn};
+use regex::Regex;
+use itertools::Itertools;
+use simple_error::{bail, SimpleError};
+use super::{CollectContext, ReportFormat, ResolvedContent, SummaryCategory, find_material_case_mismatches, find_non_portable_material_references, missing_skybox_faces};
+use super::copy::collected_files_with_companions;
+use super::filter::find_game_content;
+
+// Reports the collected and missing content: the checks of the collected content, the content summary, the report of missing content
+// and the reports requested by the options
+pub fn report_content(context: &CollectContext, vmf: &PathBuf, content: &ResolvedContent, summary_categories: &Vec<SummaryCategory>, missing_content: &Vec<(&str, &HashMap<String, String>)>, collected_content: &Vec<(&str, &HashMap<String, SourceContentFile>, Option<&Vec<&str>>)>) {
+
+	let options = context.options;
+	let source_files_count = context.source_files.len();
+
+	// Faces of the 2D skybox which are neither part of the source paths nor the game
+	if options.verify_skybox {
+		report_skybox_faces(content);
+	}
+
+	// References which contain absolute paths or backslashes
+	if options.strict_paths {
+		let non_portable_references: Vec<(String, String, String, &str)> = find_non_portable_material_references(&content.used_materials)
+			.into_iter()
+			.chain(content.non_portable_lua_references.iter().map(|(local_path, function, reference, problem)| (local_path.to_owned(), function.to_string(), reference.to_owned(), *problem)))
+			.collect();
+		collector::log_non_portable_references(&non_portable_references, "materials and Lua files");
+	}
+
+	// Texture references which only resolve because source files are looked up case-insensitively
+	if options.materials_case_report {
+		report_material_case_mismatches(context, content);
+	}
+
+	// Collected source content which overrides game content
+	if options.prefer_source_over_game {
+		report_overriding_content(context, content);
+	}
+
+	//
+	// Content summary
+	//
+	if options.summary_table {
+		print_content_summary_table(source_files_count, summary_categories);
+	} else {
+		print_content_summary(source_files_count, summary_categories, options.summary_only_missing);
+	}
+
+	if context.game_fs.is_none() {
+		warn!("The game check was skipped, so the missing content includes content which is part of the game");
+	}
+
+	if options.summary_json {
+		library::output::print(&content_summary_json(source_files_count, summary_categories));
+	}
+
+	record_content(vmf, source_files_count, summary_categories, missing_content);
+
+	//
+	// Report of missing content
+	//
+	if let Some(report_format) = &options.report_format {
+
+		let report = match report_format {
+			ReportFormat::Markdown => missing_report_markdown(vmf, missing_content),
+		};
+
+		match &options.report_path {
+			Some(report_path) => match fs::write(report_path, report) {
+				Ok(_) => success!("Wrote missing content report to \"<green>{}</>\"", report_path.display()),
+				Err(err) => warn!("Failed to write missing content report \"{}\": {}", report_path.display(), err.to_string()),
+			},
+			None => library::output::document("report", &report, &format!("\"input\": {}, \"report\": {}", library::json::string(&vmf.to_string_lossy()), library::json::string(&report))),
+		}
+
+	}
+
+	if options.report_skybox {
+		report_3d_skybox(content);
+	}
+
+	if options.report_templated {
+		report_templated_entities(content);
+	}
+
+	if options.report_by_entity {
+		report_by_entity(content);
+	}
+
+	if options.print_tree {
+		print_content_tree(collected_content, options.flatten_case);
+	}
+
+	if let Some(count) = options.report_largest {
+		print_largest_files(collected_content, count);
+	}
+
+	// Compare the required content with a manifest of a previous run, which lists the copied and the missing files
+	if let Some(baseline_path) = &options.version_check {
+		match read_manifest_paths(baseline_path) {
+			Ok(baseline_paths) => {
+				let current_paths: HashSet<String> = collected_files_with_companions(collected_content)
+					.iter()
+					.map(|source_file| &source_file.local_path)
+					.chain(missing_content.iter().flat_map(|(_, files)| files.keys()))
+					.map(|path| path.replace("\\", "/").to_lowercase())
+					.collect();
+				print_manifest_diff(baseline_path, &baseline_paths, &current_paths);
+			},
+			Err(err) => warn!("Failed to compare with baseline manifest: {}", err),
+		}
+	}
+
+}
+
+// Warns about the faces of the 2D skybox which are neither part of the source paths nor the game
+fn report_skybox_faces(content: &ResolvedContent) {
+	match &content.skyname {
+		Some(skyname) => {
+			let missing_faces = missing_skybox_faces(&content.skybox_face_source_paths, &content.missing_materials);
+			if missing_faces.len() > 0 {
+				warn!("<red>{}</> of 6 faces of the 2D skybox <cyan>{}</> are missing, the sky will not render correctly:", missing_faces.len(), skyname);
+				for face_source_path in missing_faces {
+					warn!("\t<red>-</> {}", face_source_path);
+				}
+			} else {
+				success!("<green>All 6 faces of the 2D skybox</> <cyan>{}</> <green>are present!</>", skyname);
+			}
+		},
+		None => warn!("Map has no <cyan>skyname</>, skipping 2D skybox check"),
+	}
+}
+
+// Warns about texture parameters of the collected materials which differ in case from their texture file
+fn report_material_case_mismatches(context: &CollectContext, content: &ResolvedContent) {
+	let case_mismatches = find_material_case_mismatches(&content.used_materials, context.source_files);
+	if case_mismatches.len() > 0 {
+		warn!("<red>{}</> texture parameters of materials differ in case from their texture file, which can fail on case-sensitive file systems (e.g. Linux):", case_mismatches.len());
+		for (material_local_path, param_key, param_value, texture_local_path) in case_mismatches {
+			warn!("\t<red>-</> {}: {} \"{}\" -> {}", material_local_path, param_key, param_value, texture_local_path);
+		}
+	} else {
+		success!("<green>All texture parameters of materials match the case of their texture files!</>");
+	}
+}
+
+// Lists the collected source content which overrides game content.
+// Only content missing in the source paths is ever looked up in (and removed because of) the game files, so these overrides are always collected.
+fn report_overriding_content(context: &CollectContext, content: &ResolvedContent) {
+
+	let game_fs = match context.game_fs {
+		Some(game_fs) => game_fs,
+		None => {
+			warn!("Can not list content overriding game content since the game check is skipped");
+			return;
+		}
+	};
+
+	let used_content: Vec<&String> = content.used_materials.keys()
+		.chain(content.used_models.keys())
+		.chain(content.materials_data.used_textures.keys())
+		.chain(content.used_sounds.keys())
+		.collect();
+
+	info!("Looking for <cyan>{}</> collected files in game files...", used_content.len());
+
+	let overriding_content = find_game_content(used_content, game_fs, context.options.concurrency_limit);
+	if overriding_content.len() > 0 {
+		info!("Collecting <cyan>{}</> files from the source paths which override game content:", overriding_content.len());
+		for file_local_path in overriding_content.iter().sorted() {
+			info!("\t<cyan>~</> {}", file_local_path);
+		}
+	} else {
+		success!("<green>No collected files override game content</>");
+	}
+
+}
+
+// Logs every reference marked as found (+), missing (-) or part of the game (~)
+fn log_references<'a>(references: impl Iterator<Item = &'a String>, found_files_maps: &[&HashMap<String, SourceContentFile>], missing_files_maps: &[&HashMap<String, String>]) {
+	for reference in references {
+		if found_files_maps.iter().any(|used_files| used_files.contains_key(reference)) {
+			info!("\t  <green>+</> {}", reference);
+		} else if missing_files_maps.iter().any(|missing_files| missing_files.contains_key(reference)) {
+			info!("\t  <red>-</> {} (missing)", reference);
+		} else {
+			info!("\t  <cyan>~</> {} (game content)", reference);
+		}
+	}
+}
+
+// Reports the content which is only used by entities and world solids inside the 3D skybox
+fn report_3d_skybox(content: &ResolvedContent) {
+
+	if content.sky_camera_origin.is_none() {
+		warn!("Map has no <cyan>sky_camera</> entity, skipping 3D skybox report");
+		return;
+	}
+
+	let skybox_only_references: Vec<&String> = content.skybox_references.difference(&content.other_references).sorted().collect();
+
+	info!("<magenta>3D SKYBOX:</>");
+	info!("\t<magenta>↳</> Entities near <cyan>sky_camera</>: <cyan>{}</>", content.skybox_entities_count);
+	info!("\t<magenta>↳</> World solids near <cyan>sky_camera</>: <cyan>{}</>", content.skybox_solids_count);
+	info!("\t<magenta>↳</> Materials / models only used by the 3D skybox: <cyan>{}</>", skybox_only_references.len());
+
+	log_references(skybox_only_references.into_iter(), &[&content.used_materials, &content.used_models], &[&content.missing_materials, &content.missing_models]);
+
+}
+
+// Reports the content which is only used by entities spawned by a point_template
+fn report_templated_entities(content: &ResolvedContent) {
+
+	let templated_only_references: Vec<&String> = content.templated_references.difference(&content.untemplated_references).sorted().collect();
+
+	info!("<magenta>TEMPLATED ENTITIES:</>");
+	info!("\t<magenta>↳</> <cyan>point_template</> entities: <cyan>{}</>", content.point_templates_count);
+	info!("\t<magenta>↳</> Entities spawned by templates: <cyan>{}</>", content.templated_entities_count);
+	info!("\t<magenta>↳</> Materials / models only used by templated entities: <cyan>{}</>", templated_only_references.len());
+
+	log_references(templated_only_references.into_iter(), &[&content.used_materials, &content.used_models], &[&content.missing_materials, &content.missing_models]);
+
+}
+
+// Reports the content referenced by each entity as a tree
+fn report_by_entity(content: &ResolvedContent) {
+
+	let found_files_maps: [&HashMap<String, SourceContentFile>; 7] = [&content.used_materials, &content.used_models, &content.materials_data.used_textures, &content.used_sounds, &content.used_scenes, &content.used_vehicle_scripts, &content.used_weapon_scripts];
+	let missing_files_maps: [&HashMap<String, String>; 7] = [&content.missing_materials, &content.missing_models, &content.materials_data.missing_textures, &content.missing_sounds, &content.missing_scenes, &content.missing_vehicle_scripts, &content.missing_weapon_scripts];
+
+	let entities_with_missing_files = content.entity_reports
+		.iter()
+		.filter(|(_, _, references)| references.iter().any(|reference| missing_files_maps.iter().any(|missing_files| missing_files.contains_key(reference))))
+		.count();
+
+	info!("<magenta>CONTENT BY ENTITY:</>");
+	info!("\t<magenta>↳</> Entities referencing content: <cyan>{}</>", content.entity_reports.len());
+	info!("\t<magenta>↳</> Entities with missing content: <red>{}</>", entities_with_missing_files);
+
+	for (id, class_name, references) in &content.entity_reports {
+		info!("\t<magenta>↳</> Entity <cyan>{}</> with class <cyan>{}</>", id, class_name);
+		log_references(references.iter(), &found_files_maps, &missing_files_maps);
+	}
+
+}
+
+// If only_missing is set, categories without missing content are left out
+pub fn print_content_summary(source_files_count: usize, categories: &Vec<SummaryCategory>, only_missing: bool) {
+
+	if only_missing && categories.iter().all(|category| category.missing == 0) {
+		success!("<green>No content missing in source files!</>");
+		return;
+	}
+
+	info!("<magenta>CONTENT SUMMARY:</>");
+	info!("\t<magenta>↳</> Source files: Total <cyan>{}</>", source_files_count);
+
+	for category in categories {
+		if only_missing && category.missing == 0 {
+			continue;
+		}
+		info!("\t<magenta>↳</> {}: Found <green>{}</>; Missing <red>{}</>", category.name, category.found, category.missing);
+	}
+
+}
+
+pub fn print_content_summary_table(source_files_count: usize, categories: &Vec<SummaryCategory>) {
+
+	let mut rows: Vec<[String; 4]> = categories
+		.iter()
+		.map(|category| [category.name.to_string(), category.found.to_string(), category.missing.to_string(), format_size(category.size)])
+		.collect();
+
+	rows.push([
+		String::from("Total"),
+		categories.iter().map(|category| category.found).sum::<usize>().to_string(),
+		categories.iter().map(|category| category.missing).sum::<usize>().to_string(),
+		format_size(categories.iter().map(|category| category.size).sum()),
+	]);
+
+	let header = [String::from("Category"), String::from("Found"), String::from("Missing"), String::from("Size")];
+
+	let mut widths = [0usize; 4];
+	for row in rows.iter().chain([&header]) {
+		for (index, cell) in row.iter().enumerate() {
+			widths[index] = widths[index].max(cell.chars().count());
+		}
+	}
+
+	// Category is left aligned, numbers are right aligned
+	let format_row = |row: &[String; 4]| format!(
+		"| {:<w0$} | {:>w1$} | {:>w2$} | {:>w3$} |",
+		row[0], row[1], row[2], row[3],
+		w0 = widths[0], w1 = widths[1], w2 = widths[2], w3 = widths[3]
+	);
+	let separator = format!("+{}+", widths.iter().map(|width| "-".repeat(width + 2)).join("+"));
+
+	info!("<magenta>CONTENT SUMMARY:</> Source files: Total <cyan>{}</>", source_files_count);
+	info!("{}", separator);
+	info!("{}", format_row(&header));
+	info!("{}", separator);
+	for (index, row) in rows.iter().enumerate() {
+		if index == rows.len() - 1 {
+			info!("{}", separator);
+		}
+		info!("{}", format_row(row));
+	}
+	info!("{}", separator);
+
+}
+
+pub fn format_size(bytes: u64) -> String {
+	return match bytes {
+		bytes if bytes >= 1024 * 1024 => format!("{:.2} MB", bytes as f64 / 1024.0 / 1024.0),
+		bytes if bytes >= 1024 => format!("{:.2} KB", bytes as f64 / 1024.0),
+		bytes => format!("{} B", bytes),
+	};
+}
+
+// Total size of the files in bytes, including their companion files (e.g. .vvd of models)
+pub fn files_size(source_files: &HashMap<String, SourceContentFile>, companion_extensions: Option<&Vec<&str>>) -> u64 {
+
+	let mut size: u64 = 0;
+
+	for source_file in source_files.values() {
+		let source_file_path = Path::new(&source_file.full_path);
+		size += fs::metadata(source_file_path).map(|metadata| metadata.len()).unwrap_or(0);
+		for extension in companion_extensions.unwrap_or(&vec!()) {
+			size += fs::metadata(source_file_path.with_extension(extension)).map(|metadata| metadata.len()).unwrap_or(0);
+		}
+	}
+
+	return size;
+
+}
+
+pub fn missing_report_markdown(vmf: &PathBuf, missing_content: &Vec<(&str, &HashMap<String, String>)>) -> String {
+
+	let map_name = vmf.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or(String::from("map"));
+	let mut report = format!("# Missing content of `{}`\n", map_name);
+
+	let missing_count: usize = missing_content.iter().map(|(_, files)| files.len()).sum();
+	if missing_count == 0 {
+		report.push_str("\nNo content is missing.\n");
+		return report;
+	}
+
+	for (category, files) in missing_content {
+
+		if files.len() == 0 {
+			continue;
+		}
+
+		// Capitalize the category for the heading
+		let mut category_chars = category.chars();
+		let category_heading = match category_chars.next() {
+			Some(first) => first.to_uppercase().chain(category_chars).collect::<String>(),
+			None => String::new(),
+		};
+
+		report.push_str(&format!("\n## {} ({})\n\n", category_heading, files.len()));
+
+		for (file_local_path, reason) in files.iter().sorted() {
+			report.push_str(&format!("- `{}` - {}\n", file_local_path.replace("\\", "/"), reason));
+		}
+
+	}
+
+	return report;
+
+}
+
+// Outputs the content summary and every missing file as records of the structured output format (--format)
+pub fn record_content(input: &Path, source_files_count: usize, summary_categories: &Vec<SummaryCategory>, missing_content: &Vec<(&str, &HashMap<String, String>)>) {
+
+	if !library::output::is_structured() {
+		return;
+	}
+
+	let input_json = library::json::string(&input.to_string_lossy());
+
+	for (category, files) in missing_content {
+		for (path, reason) in files.iter().sorted_by_key(|(path, _)| *path) {
+			library::output::record("missing", &format!(
+				"\"input\": {}, \"category\": {}, \"path\": {}, \"reason\": {}",
+				input_json,
+				library::json::string(category),
+				library::json::string(&path.replace("\\", "/")),
+				library::json::string(reason)
+			));
+		}
+	}
+
+	library::output::record("summary", &format!("\"input\": {}, \"summary\": {}", input_json, content_summary_json(source_files_count, summary_categories)));
+
+}
+
+pub fn content_summary_json(source_files_count: usize, categories: &Vec<SummaryCategory>) -> String {
+
+	let categories_json = categories
+		.iter()
+		.map(|category| format!(
+			"\"{}\": {{\"found\": {}, \"missing\": {}}}",
+			category.name.to_lowercase().replace(" / ", "_").replace(" ", "_"),
+			category.found,
+			category.missing
+		))
+		.join(", ");
+
+	return format!("{{\"source_files\": {}, {}}}", source_files_count, categories_json);
+
+}
+
+pub fn log_missing_files_hashmap(name: &str, map: &HashMap<String, String>) {
+
+	warn!("Missing <red>{}</> {} in source files:", map.len(), name);
+
+	for (file_local_path, error_message) in map {
+
+		warn!("\t<red>-</> {}", file_local_path);
+		warn!("\t  ↳ {}", error_message);
+
+	}
+
+}
+
+// Prints the directory structure which copying the collected content creates inside the output path as an indented tree, sorted by path
+pub fn print_content_tree(collected_content: &Vec<(&str, &HashMap<String, SourceContentFile>, Option<&Vec<&str>>)>, flatten_case: bool) {
+
+	let mut paths_components: Vec<Vec<String>> = collected_files_with_companions(collected_content)
+		.iter()
+		.map(|source_file| &source_file.local_path)
+		.map(|local_path| match flatten_case {
+			true => local_path.to_lowercase(),
+			false => local_path.to_owned(),
+		})
+		.map(|local_path| local_path.split(['/', '\\']).map(|component| component.to_string()).collect())
+		.unique()
+		.collect();
+	paths_components.sort();
+
+	info!("");
+	info!("<cyan>Content tree of the output directory ({} files):</>", paths_components.len());
+
+	let mut previous_components: Vec<String> = vec!();
+	for components in paths_components {
+
+		// Directories shared with the previous path were already printed
+		let shared_depth = components
+			.iter()
+			.zip(previous_components.iter())
+			.take(components.len() - 1)
+			.take_while(|(component, previous_component)| component == previous_component)
+			.count();
+
+		for (depth, component) in components.iter().enumerate().skip(shared_depth) {
+			match depth == components.len() - 1 {
+				true => info!("{}{}", "  ".repeat(depth), component),
+				false => info!("{}<cyan>{}/</>", "  ".repeat(depth), component),
+			}
+		}
+
+		previous_components = components;
+
+	}
+
+}
+
+// Prints the largest collected files (including companion files) by their size in the source paths
+pub fn print_largest_files(collected_content: &Vec<(&str, &HashMap<String, SourceContentFile>, Option<&Vec<&str>>)>, count: usize) {
+
+	let files_sizes: Vec<(String, u64)> = collected_files_with_companions(collected_content)
+		.into_iter()
+		.filter_map(|source_file| match fs::metadata(&source_file.full_path) {
+			Ok(metadata) => Some((source_file.local_path, metadata.len())),
+			Err(err) => {
+				warn!("Failed to get size of \"{}\": {}", source_file.full_path, err.to_string());
+				None
+			}
+		})
+		.unique_by(|(local_path, _)| local_path.to_owned())
+		// Sorted by path for files with the same size, so the output is deterministic
+		.sorted_by(|(local_path_a, size_a), (local_path_b, size_b)| size_b.cmp(size_a).then(local_path_a.cmp(local_path_b)))
+		.take(count)
+		.collect();
+
+	info!("");
+	info!("<magenta>LARGEST FILES:</>");
+	for (local_path, size) in files_sizes {
+		info!("\t<magenta>↳</> <cyan>{:>10}</> {}", format_size(size), local_path);
+	}
+
+}
+
+// Returns the paths of all copied and missing files of a manifest written by --manifest
+pub fn read_manifest_paths(manifest_path: &Path) -> Result<HashSet<String>, SimpleError> {
+
+	let content = match fs::read_to_string(manifest_path) {
+		Ok(content) => content,
+		Err(err) => bail!("Failed to read manifest \"{}\": {}", manifest_path.display(), err.to_string()),
+	};
+
+	// The manifest is written by content_manifest_json, so only its "path" values have to be read
+	let path_regex = Regex::new(r#""path":\s*"((?:[^"\\]|\\.)*)""#).unwrap();
+	let paths: HashSet<String> = path_regex
+		.captures_iter(&content)
+		.map(|captures| library::json::unescape(&captures[1]).to_lowercase())
+		.collect();
+
+	if paths.len() == 0 && !content.contains("\"files\"") {
+		bail!("\"{}\" is not a manifest written by --manifest", manifest_path.display());
+	}
+
+	return Ok(paths);
+
+}
+
+// Lists the content which is required by the map now, but not by the baseline, and the other way around
+pub fn print_manifest_diff(baseline_path: &Path, baseline_paths: &HashSet<String>, current_paths: &HashSet<String>) {
+
+	let added_paths: Vec<&String> = current_paths.difference(baseline_paths).sorted().collect();
+	let removed_paths: Vec<&String> = baseline_paths.difference(current_paths).sorted().collect();
+
+	info!("");
+	info!("<magenta>CHANGES COMPARED TO \"{}\":</>", baseline_path.display());
+
+	if added_paths.len() == 0 && removed_paths.len() == 0 {
+		success!("<green>The map requires the same content as the baseline</>");
+		return;
+	}
+
+	info!("\t<magenta>↳</> Newly required: <green>{}</>", added_paths.len());
+	for path in added_paths {
+		info!("\t  <green>+</> {}", path);
+	}
+
+	info!("\t<magenta>↳</> No longer required: <red>{}</>", removed_paths.len());
+	for path in removed_paths {
+		info!("\t  <red>-</> {}", path);
+	}
+
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+	use super::super::test_utils::*;
+
+	#[test]
+	fn manifest_paths_are_read_lowercase() {
+
+		let directory = test_directory("manifest_paths");
+		fs::write(directory.join("manifest.json"), concat!(
+			"{\n\t\"vmf\": \"maps/test.vmf\",\n",
+			"\t\"files\": [\n\t\t{\"category\": \"materials\", \"path\": \"materials/Brick/wall.vmt\", \"size\": 64}\n\t],\n",
+			"\t\"missing\": [\n\t\t{\"category\": \"materials\", \"path\": \"materials/dev/missing.vmt\", \"reason\": \"Used by brush \\\"wall\\\"\"}\n\t]\n}\n",
+		)).unwrap();
+		fs::write(directory.join("other.json"), "{ \"name\": \"other\" }").unwrap();
+
+		let paths = read_manifest_paths(&directory.join("manifest.json")).unwrap();
+		assert_eq!(paths, HashSet::from([String::from("materials/brick/wall.vmt"), String::from("materials/dev/missing.vmt")]));
+
+		// Other json files are no baseline
+		assert!(read_manifest_paths(&directory.join("other.json")).is_err());
+		assert!(read_manifest_paths(&directory.join("missing.json")).is_err());
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn missing_report_lists_missing_files_by_category() {
+
+		let missing_materials = HashMap::from([
+			(String::from("materials\\mymap\\wall.vmt"), String::from("Used by world brush / solid 2")),
+			(String::from("materials\\mymap\\floor.vmt"), String::from("Used by world brush / solid 3")),
+		]);
+		let missing_models = HashMap::new();
+		let missing_content: Vec<(&str, &HashMap<String, String>)> = vec!(("materials", &missing_materials), ("models", &missing_models));
+
+		assert_eq!(missing_report_markdown(&PathBuf::from("maps/rp_harbor.vmf"), &missing_content), concat!(
+			"# Missing content of `rp_harbor`\n",
+			"\n## Materials (2)\n\n",
+			"- `materials/mymap/floor.vmt` - Used by world brush / solid 3\n",
+			"- `materials/mymap/wall.vmt` - Used by world brush / solid 2\n",
+		));
+
+		// Empty categories are left out, a report without missing content says so
+		assert_eq!(missing_report_markdown(&PathBuf::from("maps/rp_harbor.vmf"), &vec!(("models", &missing_models))), "# Missing content of `rp_harbor`\n\nNo content is missing.\n");
+
+	}
+
+}
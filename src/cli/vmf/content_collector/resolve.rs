@@ -0,0 +1,1806 @@
+use std::{collections::{HashMap, HashSet}, fs, path::{Path, PathBuf}};
+use crate::library;
+use crate::library::collector::{self, SourceContentFile};
+use crate::library::log::{error, info, success, warn};
+use plumber_core::uncased::UncasedStr;
+use regex::Regex;
+use itertools::Itertools;
+use super::*;
+
+// Resolves all content referenced by the map (including its instances) in the source paths, or returns None if the map can not be read
+pub fn resolve_content(context: &CollectContext, vmf: &PathBuf) -> Option<ResolvedContent> {
+
+	let options = context.options;
+	let mut content = ResolvedContent::new();
+
+	let map_name = vmf.file_stem().map(|stem| stem.to_string_lossy().to_lowercase()).unwrap_or_default();
+
+	let vmf_parsed = read_map(vmf, &options.instances_dir)?;
+	let entities = vmf_parsed.entities;
+
+	//
+	// Read the keyvalue rules of the entity classes from the fgd
+	//
+	let (fgd_keyvalue_rules, fgd_class_models) = match &options.fgd {
+		Some(fgd_path) => read_fgd_rules(fgd_path)?,
+		None => (HashMap::new(), HashMap::new()),
+	};
+
+	//
+	// Locate the 3D skybox camera to be able to tell which entities are part of the 3D skybox
+	//
+	content.sky_camera_origin = entities
+		.iter()
+		.find(|ent| ent.class_name == "sky_camera")
+		.and_then(|ent| ent.properties.get(UncasedStr::new("origin")))
+		.and_then(|origin| parse_origin(origin));
+
+	//
+	// Locate point_template entities and the names of the entities they spawn.
+	// The templated entities are part of the vmf, so their content is collected like the content of any other entity.
+	//
+	let point_templates = read_point_templates(&entities);
+	content.point_templates_count = point_templates.len();
+
+	if options.report_templated {
+		warn_unresolved_templates(&entities, &point_templates);
+	}
+
+	//
+	// Collect materials from all world solids / brushes, the 2D skybox and the detail sprites
+	//
+	resolve_world(context, &mut content, vmf_parsed.world);
+
+	//
+	// Collect models and materials from entities
+	//
+	let entity_class_names = resolve_entities(context, &mut content, entities, &point_templates, &fgd_keyvalue_rules, &fgd_class_models);
+
+	//
+	// Collect spawn menu icons of the used entity classes
+	//
+	if options.include_spawnicons {
+		resolve_spawnicons(context, &mut content, &entity_class_names);
+	}
+
+	//
+	// Collect the console / loading screen backgrounds of the source paths
+	//
+	if options.include_backgrounds {
+		resolve_backgrounds(context, &mut content);
+	}
+
+	//
+	// Collect materials and GUI layout files (.res) referenced by Lua files (e.g. HUD / GUI materials)
+	//
+	if options.scan_lua {
+		resolve_lua_content(context, &mut content);
+	}
+
+	//
+	// Collect soundscape scripts and the sounds played by the used soundscapes
+	//
+	if content.used_soundscape_names.len() > 0 {
+		info!("Collecting scripts and sounds of <cyan>{}</> soundscapes...", content.used_soundscape_names.len());
+		content.soundscape_data = collect_soundscape_data(&content.used_soundscape_names, context.source_files);
+
+		// Sounds played by soundscapes and sounds referenced by entity keyvalues are collected together
+		content.used_sounds.extend(content.soundscape_data.used_sounds.drain());
+		content.missing_sounds.extend(content.soundscape_data.missing_sounds.drain());
+	}
+
+	//
+	// Collect the particle files (.pcf) listed in the particle manifest of the map and the particle files which define the particle systems
+	// of info_particle_system entities in one pass, so every particle file and the materials used by its particles are collected once
+	//
+	resolve_particles(context, &mut content, &map_name);
+
+	//
+	// Collect gib models, materials and sounds of the collected models
+	//
+	resolve_models_content(context, &mut content);
+
+	//
+	// Collect the precompiled scenes.image, which contains all scenes
+	//
+	if options.include_scenes {
+		if let Some(source_file) = context.source_files.get(SCENES_IMAGE_SOURCE_PATH) {
+			content.used_scenes.insert(SCENES_IMAGE_SOURCE_PATH.to_owned(), source_file.to_owned());
+		}
+	}
+
+	//
+	// Collect navigation mesh (.nav) and AI node graph (.ain) of the map
+	//
+	if options.include_nav {
+		resolve_nav_files(context, &mut content, &map_name);
+	}
+
+	//
+	// Collect textures used by the collected materials, once all materials are known
+	//
+	resolve_textures(context, &mut content)?;
+
+	return Some(content);
+
+}
+
+// Reads and parses the vmf and its instances (func_instance) recursively.
+// The world solids and entities of the instances are added to the map, so their content is collected as if it was part of the map.
+fn read_map(vmf: &PathBuf, instances_dir: &Option<PathBuf>) -> Option<plumber_core::vmf::Vmf> {
+
+	//
+	// Read vmf
+	//
+	info!("Reading vmf \"<green>{}</>\"...", vmf.display());
+	let vmf_content = match fs::read(vmf) {
+		Ok(content) => content,
+		Err(err) => {
+			error!("Failed to read vmf file in \"{}\": {}", vmf.display(), err.to_string());
+			return None;
+		}
+	};
+
+	//
+	// Parse vmf
+	//
+	info!("Parsing vmf...");
+	let mut vmf_parsed = match plumber_core::vmf::from_bytes(&vmf_content) {
+		Ok(parsed) => parsed,
+		Err(err) => {
+			error!("Failed to parse vmf file in \"{}\": {}", vmf.display(), err.to_string());
+			return None;
+		}
+	};
+
+	//
+	// Read and parse instances
+	//
+	let mut visited_vmfs: HashSet<PathBuf> = HashSet::new();
+	visited_vmfs.insert(fs::canonicalize(vmf).unwrap_or(vmf.to_owned()));
+
+	let instances = read_instances(vmf, &vmf_parsed.entities, instances_dir, &mut visited_vmfs);
+	if instances.len() > 0 {
+		info!("Read <cyan>{}</> instances", instances.len());
+	}
+
+	for instance in instances {
+		vmf_parsed.world.solids.extend(instance.world.solids);
+		vmf_parsed.entities.extend(instance.entities);
+	}
+
+	return Some(vmf_parsed);
+
+}
+
+// Reads the keyvalue rules of the entity classes and the models of classes without a "model" keyvalue (e.g. of custom pickups) from the fgd
+fn read_fgd_rules(fgd_path: &PathBuf) -> Option<(HashMap<String, Vec<(String, KeyValueContentKind)>>, HashMap<String, String>)> {
+
+	info!("Reading fgd \"<green>{}</>\"...", fgd_path.display());
+
+	let fgd_classes = match library::fgd::read(fgd_path, &mut HashSet::new()) {
+		Ok(classes) => classes,
+		Err(err) => {
+			error!("{}", err.to_string());
+			return None;
+		}
+	};
+
+	let fgd_keyvalue_rules = fgd_entity_keyvalue_rules(&fgd_classes);
+
+	let fgd_class_models = fgd_classes
+		.iter()
+		.filter_map(|class| class.studio.as_ref().map(|studio| (class.name.to_owned(), studio.to_owned())))
+		.collect();
+
+	info!("Read keyvalue rules of <cyan>{}</> entity classes from fgd", fgd_keyvalue_rules.len());
+
+	return Some((fgd_keyvalue_rules, fgd_class_models));
+
+}
+
+// Returns the name of every point_template entity together with the names of the entities it spawns (its "TemplateXX" keyvalues), all lowercase
+pub fn read_point_templates(entities: &Vec<plumber_core::vmf::Entity>) -> Vec<(String, Vec<String>)> {
+	return entities
+		.iter()
+		.filter(|ent| ent.class_name.eq_ignore_ascii_case("point_template"))
+		.map(|ent| (
+			ent.properties.get(UncasedStr::new("targetname")).map(|name| name.to_lowercase()).unwrap_or_default(),
+			(1..=16)
+				.filter_map(|index| ent.properties.get(UncasedStr::new(&format!("Template{:02}", index))))
+				.map(|name| name.trim().to_lowercase())
+				.filter(|name| !name.is_empty())
+				.collect()
+		))
+		.collect();
+}
+
+// Warns about env_entity_maker entities without a matching point_template and about point_template targets which do not exist,
+// since the content of the entities they would spawn can not be collected
+fn warn_unresolved_templates(entities: &Vec<plumber_core::vmf::Entity>, point_templates: &Vec<(String, Vec<String>)>) {
+
+	let entity_names: Vec<String> = entities
+		.iter()
+		.filter_map(|ent| ent.properties.get(UncasedStr::new("targetname")))
+		.map(|name| name.to_lowercase())
+		.collect();
+
+	// env_entity_maker entities spawn the entities of the point_template named in their "EntityTemplate" keyvalue
+	for ent in entities.iter().filter(|ent| ent.class_name.eq_ignore_ascii_case("env_entity_maker")) {
+		match ent.properties.get(UncasedStr::new("EntityTemplate")) {
+			Some(template_name) if !template_name.trim().is_empty() => {
+				if !point_templates.iter().any(|(name, _)| matches_entity_name(template_name, name)) {
+					warn!("Entity {} with class env_entity_maker uses template \"{}\", but there is no point_template with this name. The content of the entities it spawns can not be collected!", ent.id, template_name);
+				}
+			},
+			_ => warn!("Entity {} with class env_entity_maker has no template set", ent.id),
+		}
+	}
+
+	for (template_name, template_targets) in point_templates {
+		for template_target in template_targets {
+			if !entity_names.iter().any(|name| matches_entity_name(template_target, name)) {
+				warn!("point_template \"{}\" spawns \"{}\", but there is no entity with this name", template_name, template_target);
+			}
+		}
+	}
+
+}
+
+// Adds the file to used_files if it is part of the source files, otherwise to missing_files with the reason
+fn resolve_file(source_files: &HashMap<String, SourceContentFile>, source_path: String, reason: String, used_files: &mut HashMap<String, SourceContentFile>, missing_files: &mut HashMap<String, String>) {
+	match source_files.get(&source_path) {
+		Some(source_file) => {
+			used_files.insert(source_path, source_file.to_owned());
+		},
+		None => {
+			missing_files.insert(source_path, reason);
+		}
+	}
+}
+
+// Collects the materials of the world solids / brushes, the six face materials of the 2D skybox (skyname) and the material of the detail sprites.
+// The 3D skybox is collected like any other part of the map.
+fn resolve_world(context: &CollectContext, content: &mut ResolvedContent, world: plumber_core::vmf::World) {
+
+	let source_files = context.source_files;
+
+	info!("Collecting materials used by world solids / brushes...");
+	for solid in world.solids {
+
+		// World solids have no origin, they are part of the 3D skybox if their center is near the sky_camera like the origin of entities
+		let in_skybox = match (content.sky_camera_origin, solids_center(std::slice::from_ref(&solid))) {
+			(Some(sky_camera_origin), Some(solid_center)) => is_within_distance(&solid_center, &sky_camera_origin, context.options.skybox_radius),
+			_ => false,
+		};
+
+		if in_skybox {
+			content.skybox_solids_count += 1;
+		}
+
+		for side in solid.sides {
+
+			let side_material_source_path = match make_side_material_path(&side.material.into_string(), source_files) {
+				Some(path) => path,
+				None => continue,
+			};
+
+			match in_skybox {
+				true => content.skybox_references.insert(side_material_source_path.to_owned()),
+				false => content.other_references.insert(side_material_source_path.to_owned()),
+			};
+			content.untemplated_references.insert(side_material_source_path.to_owned());
+
+			resolve_file(source_files, side_material_source_path, format!("Used by world brush / solid {}", solid.id), &mut content.used_materials, &mut content.missing_materials);
+
+		}
+
+	}
+
+	// The six face materials of the 2D skybox
+	content.skyname = world.properties
+		.get(UncasedStr::new("skyname"))
+		.map(|skyname| skyname.trim().to_lowercase())
+		.filter(|skyname| !skyname.is_empty());
+	content.skybox_face_source_paths = content.skyname.as_ref().map(|skyname| make_skybox_face_paths(skyname)).unwrap_or_default();
+
+	if let Some(skyname) = &content.skyname {
+		for (face, face_source_path) in SKYBOX_FACES.iter().zip(&content.skybox_face_source_paths) {
+
+			content.other_references.insert(face_source_path.to_owned());
+			content.untemplated_references.insert(face_source_path.to_owned());
+
+			resolve_file(source_files, face_source_path.to_owned(), format!("Face of the 2D skybox \"{}\" (\"skyname\" property of the world)", skyname), &mut content.used_materials, &mut content.missing_materials);
+
+			// The HDR faces are optional, the engine falls back to the LDR faces
+			let hdr_face_source_path = make_material_path(&format!("skybox/{}_hdr{}", skyname, face));
+			if let Some(source_file) = source_files.get(&hdr_face_source_path) {
+				content.used_materials.insert(hdr_face_source_path, source_file.to_owned());
+			}
+
+		}
+	}
+
+	// The material of the detail sprites (e.g. grass on displacements), which env_detail_controller entities only fade.
+	// The sprites of func_dustcloud / func_dustmotes entities are part of the game.
+	if let Some(detail_material) = world.properties.get(UncasedStr::new("detailmaterial")).filter(|detail_material| !detail_material.trim().is_empty()) {
+
+		let detail_material_source_path = make_material_path(detail_material);
+
+		content.other_references.insert(detail_material_source_path.to_owned());
+		content.untemplated_references.insert(detail_material_source_path.to_owned());
+
+		match source_files.get(&detail_material_source_path) {
+			Some(source_file) => {
+				content.used_materials.insert(detail_material_source_path, source_file.to_owned());
+			},
+			None => {
+				// The default detail sprites are part of the game
+				if !DEFAULT_DETAIL_MATERIALS.contains(&detail_material_source_path.as_str()) {
+					content.missing_materials.insert(detail_material_source_path, String::from("Used as detail sprite material by the world (\"detailmaterial\" property)"));
+				}
+			}
+		}
+
+	}
+
+}
+
+// Collects the content of all entities which are not of an excluded class and returns the (lowercase) classes of these entities
+fn resolve_entities(context: &CollectContext, content: &mut ResolvedContent, entities: Vec<plumber_core::vmf::Entity>, point_templates: &Vec<(String, Vec<String>)>, fgd_keyvalue_rules: &HashMap<String, Vec<(String, KeyValueContentKind)>>, fgd_class_models: &HashMap<String, String>) -> HashSet<String> {
+
+	let options = context.options;
+
+	info!("Collecting models and materials used by entities...");
+
+	if !options.exclude_class.is_empty() {
+		let excluded_entities_count = entities.iter().filter(|ent| is_excluded_class(&options.exclude_class, &ent.class_name)).count();
+		warn!("Skipping the content of <cyan>{}</> entities of the excluded classes, content which is only used by them is not collected!", excluded_entities_count);
+	}
+
+	let mut entity_class_names: HashSet<String> = HashSet::new();
+
+	for ent in entities {
+
+		if is_excluded_class(&options.exclude_class, &ent.class_name) {
+			continue;
+		}
+
+		// Brush entities without an origin (e.g. func_detail) are located by the center of their solids
+		let ent_origin = ent.properties.get(UncasedStr::new("origin")).and_then(|origin| parse_origin(origin)).or_else(|| solids_center(&ent.solids));
+		let in_skybox = match (content.sky_camera_origin, ent_origin) {
+			(Some(sky_camera_origin), Some(ent_origin)) => ent.class_name != "sky_camera" && is_within_distance(&ent_origin, &sky_camera_origin, options.skybox_radius),
+			_ => false,
+		};
+
+		if in_skybox {
+			content.skybox_entities_count += 1;
+		}
+
+		let is_templated = match ent.properties.get(UncasedStr::new("targetname")) {
+			Some(name) => is_templated_entity(point_templates, name),
+			None => false,
+		};
+
+		if is_templated {
+			content.templated_entities_count += 1;
+		}
+
+		entity_class_names.insert(ent.class_name.to_lowercase());
+
+		let (entity_references, entity_other_references) = resolve_entity(context, content, &ent, fgd_keyvalue_rules, fgd_class_models);
+
+		if options.report_by_entity && (entity_references.len() > 0 || entity_other_references.len() > 0) {
+			let mut references: Vec<String> = entity_references.iter().chain(entity_other_references.iter()).cloned().collect();
+			references.sort();
+			references.dedup();
+			content.entity_reports.push((ent.id.to_string(), ent.class_name.to_owned(), references));
+		}
+
+		if is_templated {
+			content.templated_references.extend(entity_references.iter().cloned());
+		} else {
+			content.untemplated_references.extend(entity_references.iter().cloned());
+		}
+
+		if in_skybox {
+			content.skybox_references.extend(entity_references);
+		} else {
+			content.other_references.extend(entity_references);
+		}
+
+	}
+
+	return entity_class_names;
+
+}
+
+// Collects the content of a single entity. Returns the content it references which is used for the 3D skybox and templated entities reports,
+// and its other referenced content (e.g. sounds and scripts), which is only used for the entity report.
+fn resolve_entity(context: &CollectContext, content: &mut ResolvedContent, ent: &plumber_core::vmf::Entity, fgd_keyvalue_rules: &HashMap<String, Vec<(String, KeyValueContentKind)>>, fgd_class_models: &HashMap<String, String>) -> (Vec<String>, Vec<String>) {
+
+	let source_files = context.source_files;
+
+	let mut entity_references: Vec<String> = vec!();
+	let mut entity_other_references: Vec<String> = vec!();
+
+	// Collect materials from all entity solids / brushes
+	for solid in &ent.solids {
+		for side in &solid.sides {
+
+			let side_material_source_path = match make_side_material_path(side.material.as_str(), source_files) {
+				Some(path) => path,
+				None => continue,
+			};
+
+			entity_references.push(side_material_source_path.to_owned());
+
+			resolve_file(source_files, side_material_source_path, format!("Used by brush / solid {} in entity {} with class {}", solid.id, ent.id, ent.class_name), &mut content.used_materials, &mut content.missing_materials);
+
+		}
+	}
+
+	// Collect the weapon script of weapon entities (e.g. weapon_smg1 -> scripts\weapon_smg1.txt), once per class
+	let weapon_class = ent.class_name.to_lowercase();
+	if context.options.include_weapon_scripts && weapon_class.starts_with("weapon_") {
+		resolve_weapon_script(context, content, ent, &weapon_class, &mut entity_other_references);
+	}
+
+	// Collect content referenced by keyvalues (see ENTITY_KEYVALUE_RULES), using the rules of the fgd if it defines this class
+	for (key, kind) in entity_keyvalue_rules(&ent.class_name, fgd_keyvalue_rules) {
+
+		let value = match ent.properties.get(UncasedStr::new(&key)) {
+			Some(value) if !value.trim().is_empty() => value,
+			_ => continue,
+		};
+
+		let (source_path, report_missing) = match keyvalue_content_path(&key, kind, value) {
+			Some(content_path) => content_path,
+			None => continue,
+		};
+
+		let reason = format!("Used by entity {} with class {} in \"{}\" property", ent.id, ent.class_name, key);
+
+		match kind {
+			KeyValueContentKind::Material => {
+
+				entity_references.push(source_path.to_owned());
+
+				if report_missing || source_files.contains_key(&source_path) {
+					resolve_file(source_files, source_path, reason, &mut content.used_materials, &mut content.missing_materials);
+				}
+
+			},
+			KeyValueContentKind::Scene => {
+
+				if !context.options.include_scenes {
+					continue;
+				}
+
+				entity_other_references.push(source_path.to_owned());
+
+				let scene_source_file = match source_files.get(&source_path) {
+					Some(source_file) => source_file,
+					None => {
+						content.missing_scenes.insert(source_path, reason);
+						continue;
+					}
+				};
+
+				// Collect the actor models and sound files of the scene
+				let scene_reason = format!("Referenced by scene \"{}\" of entity {} with class {}", scene_source_file.local_path, ent.id, ent.class_name);
+				entity_references.extend(resolve_script_references(context, content, read_scene_references(scene_source_file), &scene_reason));
+
+				content.used_scenes.insert(source_path, scene_source_file.to_owned());
+
+			},
+			KeyValueContentKind::Model => {
+
+				entity_references.push(source_path.to_owned());
+
+				resolve_file(source_files, source_path, reason, &mut content.used_models, &mut content.missing_models);
+
+			},
+			KeyValueContentKind::VehicleScript => {
+
+				entity_other_references.push(source_path.to_owned());
+
+				let vehicle_script_source_file = match source_files.get(&source_path) {
+					Some(source_file) => source_file,
+					None => {
+						content.missing_vehicle_scripts.insert(source_path, reason);
+						continue;
+					}
+				};
+
+				// Collect the models, sounds and materials referenced by the vehicle script
+				let script_reason = format!("Referenced by vehicle script \"{}\" of entity {} with class {}", vehicle_script_source_file.local_path, ent.id, ent.class_name);
+				entity_other_references.extend(resolve_script_references(context, content, read_vehicle_script_references(vehicle_script_source_file), &script_reason));
+
+				content.used_vehicle_scripts.insert(source_path, vehicle_script_source_file.to_owned());
+
+			},
+			KeyValueContentKind::Texture => {
+
+				entity_references.push(source_path.to_owned());
+
+				if report_missing || source_files.contains_key(&source_path) {
+					resolve_file(source_files, source_path, reason, &mut content.materials_data.used_textures, &mut content.materials_data.missing_textures);
+				}
+
+			},
+			KeyValueContentKind::Raw => {
+
+				entity_other_references.push(source_path.to_owned());
+
+				// Collected as materials, since they are located in materials/correction/
+				resolve_file(source_files, source_path, reason, &mut content.used_materials, &mut content.missing_materials);
+
+			},
+			KeyValueContentKind::Sound => {
+
+				entity_other_references.push(source_path.to_owned());
+
+				resolve_file(source_files, source_path, reason, &mut content.used_sounds, &mut content.missing_sounds);
+
+			},
+		}
+
+	}
+
+	match ent.properties.get(UncasedStr::new("model")) {
+
+		// Special case: env_sprite entities use their "model" property as a material path to the sprite material
+		Some(model) if ent.class_name == "env_sprite" => {
+
+			let source_file_path = make_material_path(model);
+
+			entity_references.push(source_file_path.to_owned());
+
+			resolve_file(source_files, source_file_path, format!("Used as sprite material by entity {} with class {}", ent.id, ent.class_name), &mut content.used_materials, &mut content.missing_materials);
+
+		},
+
+		// Brush entities reference their brush model as "*<index>", which is part of the compiled map and skipped
+		Some(model) => {
+			if let Some(model_source_path) = make_model_path(model) {
+
+				entity_references.push(model_source_path.to_owned());
+
+				resolve_file(source_files, model_source_path, format!("Used by entity {} with class {}", ent.id, ent.class_name), &mut content.used_models, &mut content.missing_models);
+
+			}
+		},
+
+		// Collect the world model of entities which is defined by their class instead of a "model" keyvalue, e.g. of item_ / weapon_ entities.
+		// The models of the built-in classes are part of the game, so they are only collected if the source paths override them and never reported as missing.
+		None => {
+			if let Some((model_source_path, report_missing)) = entity_class_model(&ent.class_name, fgd_class_models) {
+
+				entity_references.push(model_source_path.to_owned());
+
+				if report_missing || source_files.contains_key(&model_source_path) {
+					resolve_file(source_files, model_source_path, format!("World model of entity {} with class {} (studio model of the fgd)", ent.id, ent.class_name), &mut content.used_models, &mut content.missing_models);
+				}
+
+			}
+		},
+
+	}
+
+	// Collect soundscape played by env_soundscape / env_soundscape_triggerable entities
+	if ent.class_name.starts_with("env_soundscape") {
+		match ent.properties.get(UncasedStr::new("soundscape")) {
+			Some(soundscape) if !soundscape.is_empty() => {
+				content.used_soundscape_names.insert(soundscape.to_lowercase(), format!("Used by entity {} with class {}", ent.id, ent.class_name));
+			},
+			_ => {}
+		}
+	}
+
+	// Collect particle system played by info_particle_system entities, its particle file is resolved by resolve_particles
+	if ent.class_name == "info_particle_system" {
+		match ent.properties.get(UncasedStr::new("effect_name")) {
+			Some(effect_name) if !effect_name.trim().is_empty() => {
+				content.used_particle_systems.insert(effect_name.trim().to_owned(), format!("Used by entity {} with class {}", ent.id, ent.class_name));
+			},
+			_ => {}
+		}
+	}
+
+	return (entity_references, entity_other_references);
+
+}
+
+// Collects the weapon script of a weapon entity and the models, sounds and materials it references
+fn resolve_weapon_script(context: &CollectContext, content: &mut ResolvedContent, ent: &plumber_core::vmf::Entity, weapon_class: &str, entity_other_references: &mut Vec<String>) {
+
+	let source_files = context.source_files;
+	let weapon_script_source_path = format!("scripts\\{}.txt", weapon_class);
+
+	entity_other_references.push(weapon_script_source_path.to_owned());
+
+	match source_files.get(&weapon_script_source_path) {
+		Some(weapon_script_source_file) if !content.used_weapon_scripts.contains_key(&weapon_script_source_path) => {
+
+			let script_reason = format!("Referenced by weapon script \"{}\" of entity {} with class {}", weapon_script_source_file.local_path, ent.id, ent.class_name);
+			entity_other_references.extend(resolve_script_references(context, content, read_weapon_script_references(weapon_script_source_file), &script_reason));
+
+			content.used_weapon_scripts.insert(weapon_script_source_path, weapon_script_source_file.to_owned());
+
+		},
+		Some(_) => {},
+		None => {
+			// Lua weapons (SWEPs) do not have a weapon script
+			let lua_weapon = source_files.contains_key(&format!("lua\\weapons\\{}.lua", weapon_class))
+				|| source_files.contains_key(&format!("lua\\weapons\\{}\\shared.lua", weapon_class));
+			if !lua_weapon {
+				content.missing_weapon_scripts.insert(weapon_script_source_path, format!("Used by entity {} with class {}", ent.id, ent.class_name));
+			}
+		}
+	}
+
+}
+
+// Collects the models (.mdl), materials (.vmt) and sounds (everything else) referenced by a weapon script, vehicle script or scene
+// and returns their source paths
+fn resolve_script_references(context: &CollectContext, content: &mut ResolvedContent, references: Vec<String>, reason: &str) -> Vec<String> {
+
+	let mut reference_source_paths: Vec<String> = vec!();
+
+	for reference in references {
+
+		let (reference_source_path, used_files, missing_files) = if reference.ends_with(".mdl") {
+			match make_model_path(&reference) {
+				Some(model_source_path) => (model_source_path, &mut content.used_models, &mut content.missing_models),
+				None => continue,
+			}
+		} else if reference.ends_with(".vmt") {
+			(make_material_path(&reference), &mut content.used_materials, &mut content.missing_materials)
+		} else {
+			(make_sound_path(&reference), &mut content.used_sounds, &mut content.missing_sounds)
+		};
+
+		reference_source_paths.push(reference_source_path.to_owned());
+
+		resolve_file(context.source_files, reference_source_path, reason.to_owned(), used_files, missing_files);
+
+	}
+
+	return reference_source_paths;
+
+}
+
+// Collects the spawn menu icons of the used entity classes.
+// Missing icons are not reported, since most entities are part of the game or have no icon.
+fn resolve_spawnicons(context: &CollectContext, content: &mut ResolvedContent, entity_class_names: &HashSet<String>) {
+
+	let mut spawnicons_count = 0;
+
+	for class_name in entity_class_names.iter().sorted() {
+		for spawnicon_source_path in [format!("materials\\vgui\\entities\\{}.vmt", class_name), format!("materials\\entities\\{}.png", class_name)] {
+			if let Some(source_file) = context.source_files.get(&spawnicon_source_path) {
+				content.used_materials.insert(spawnicon_source_path, source_file.to_owned());
+				spawnicons_count += 1;
+			}
+		}
+	}
+
+	info!("Found <cyan>{}</> spawn menu icons of <cyan>{}</> entity classes", spawnicons_count, entity_class_names.len());
+
+}
+
+// Collects the console / loading screen backgrounds of the source paths.
+// They are provided by an addon instead of being referenced by the map, so all of them are collected and none are reported as missing.
+fn resolve_backgrounds(context: &CollectContext, content: &mut ResolvedContent) {
+
+	let background_source_paths: Vec<&String> = context.source_files
+		.keys()
+		.filter(|source_file_path| source_file_path.starts_with(BACKGROUND_MATERIALS_PREFIX) && source_file_path.ends_with(".vmt"))
+		.sorted()
+		.collect();
+
+	info!("Found <cyan>{}</> console / loading screen backgrounds in source paths", background_source_paths.len());
+
+	for background_source_path in background_source_paths {
+		info!("\t<magenta>↳</> <green>{}</>", background_source_path.strip_prefix(BACKGROUND_MATERIALS_PREFIX).unwrap_or(background_source_path));
+		content.used_materials.insert(background_source_path.to_owned(), context.source_files[background_source_path].to_owned());
+	}
+
+}
+
+// Collects the gib models of the collected models, which breakable props spawn when they break, the materials used by all collected models
+// and (with --include-model-sounds) the sound files played by their animation events
+fn resolve_models_content(context: &CollectContext, content: &mut ResolvedContent) {
+
+	let options = context.options;
+
+	info!("Collecting gib models of <cyan>{}</> collected models...", content.used_models.len());
+	let found_break_models = collect_break_models(&mut content.used_models, &mut content.missing_models, context.source_files);
+	info!("Found <green>{}</> gib models", found_break_models);
+
+	info!("Collecting materials used by <cyan>{}</> collected models...", content.used_models.len());
+	let (models_used_materials, models_missing_materials) = collect_models_materials(&content.used_models, context.source_files, context.open_fs, options.verbose, options.concurrency_limit);
+	content.used_materials.extend(models_used_materials);
+	content.missing_materials.extend(models_missing_materials);
+
+	if options.include_model_sounds {
+		let (models_used_sounds, models_missing_sounds) = collect_models_sounds(&content.used_models, context.source_files);
+		info!("Found <green>{}</> sound files played by animation events of models, <red>{}</> are missing in source files", models_used_sounds.len(), models_missing_sounds.len());
+		content.used_sounds.extend(models_used_sounds);
+		content.missing_sounds.extend(models_missing_sounds);
+	}
+
+}
+
+// Collects the textures used by the collected materials as well as the materials referenced by materials.
+// Returns None if a material fails to be read with --strict.
+fn resolve_textures(context: &CollectContext, content: &mut ResolvedContent) -> Option<()> {
+
+	if context.options.materials_only_vmt {
+		info!("Skipping textures of <cyan>{}</> materials (<cyan>--materials-only-vmt</>)", content.used_materials.len());
+		// Textures referenced by entities are skipped as well
+		content.materials_data = SourceMaterialData::new();
+		return Some(());
+	}
+
+	info!("Collecting textures used by <cyan>{}</> materials...", content.used_materials.len());
+
+	match collector::collect_materials_data(&mut content.used_materials, context.options.strict, |source_file| read_material_data(&source_file.full_path, context.source_files, context.open_fs)) {
+		Ok(data) => content.materials_data.extend(data),
+		Err(err) => {
+			collector::log_strict_abort(&err);
+			return None;
+		}
+	}
+
+	// Add materials which are referenced by materials, but missing in source files (found ones were added while reading)
+	content.missing_materials.extend(content.materials_data.missing_materials.drain());
+
+	return Some(());
+
+}
+
+// Collects the materials and GUI layout files (.res) referenced by Lua files (e.g. HUD / GUI materials)
+fn resolve_lua_content(context: &CollectContext, content: &mut ResolvedContent) {
+
+	info!("Collecting materials and GUI layout files referenced by Lua files...");
+
+	for (source_file_path, source_file) in context.source_files {
+
+		if !source_file_path.starts_with("lua\\") || !source_file_path.ends_with(".lua") {
+			continue;
+		}
+
+		let lua_content = match fs::read_to_string(&source_file.full_path) {
+			Ok(content) => content,
+			Err(err) => {
+				warn!("Failed to read Lua file \"{}\": {}", source_file.full_path, err.to_string());
+				continue;
+			}
+		};
+
+		for material in read_lua_material_references(&lua_content) {
+
+			if let Some(problem) = path_portability_problem(&material).filter(|_| context.options.strict_paths) {
+				content.non_portable_lua_references.push((source_file.local_path.to_owned(), "Material", material.to_owned(), problem));
+			}
+
+			resolve_file(context.source_files, make_material_path(&material), format!("Used in Lua file \"{}\"", source_file.local_path), &mut content.used_materials, &mut content.missing_materials);
+
+		}
+
+		for layout_file in read_lua_layout_files(&lua_content) {
+
+			if let Some(problem) = path_portability_problem(&layout_file).filter(|_| context.options.strict_paths) {
+				content.non_portable_lua_references.push((source_file.local_path.to_owned(), "GUI layout file", layout_file.to_owned(), problem));
+			}
+
+			// Layout files are relative to the game directory, e.g. "resource/ui/menu.res"
+			let layout_file_source_path = layout_file.trim().replace("/", "\\").trim_start_matches('\\').to_lowercase();
+
+			resolve_file(context.source_files, layout_file_source_path, format!("Loaded in Lua file \"{}\"", source_file.local_path), &mut content.used_layout_files, &mut content.missing_layout_files);
+
+		}
+
+	}
+
+	info!("Found <cyan>{}</> GUI layout files referenced by Lua files", content.used_layout_files.len() + content.missing_layout_files.len());
+
+}
+
+// Collects the particle files (.pcf) listed in the particle manifest of the map and the particle files which define the used particle systems,
+// as well as the materials used by their particles
+pub fn resolve_particles(context: &CollectContext, content: &mut ResolvedContent, map_name: &str) {
+
+	let source_files = context.source_files;
+	let used_particle_systems = &content.used_particle_systems;
+
+	// Particle files to collect and why
+	let mut particle_source_paths: HashMap<String, String> = HashMap::new();
+
+	let particle_manifest_source_path = format!("maps\\{}_particles.txt", map_name);
+
+	if let Some(manifest_source_file) = source_files.get(&particle_manifest_source_path) {
+		info!("Reading particle manifest \"<green>{}</>\"...", manifest_source_file.local_path);
+		content.used_particles.insert(particle_manifest_source_path.to_owned(), manifest_source_file.to_owned());
+		for particle_source_path in read_particle_manifest(manifest_source_file) {
+			particle_source_paths.insert(particle_source_path, format!("Listed in particle manifest \"{}\"", manifest_source_file.local_path));
+		}
+	}
+
+	// Particle systems which are not defined by any particle file of the source paths are usually part of the game, so they are not reported as missing
+	if used_particle_systems.len() > 0 {
+		info!("Looking for the particle files of <cyan>{}</> particle systems used by entities...", used_particle_systems.len());
+		let particle_system_files = find_particle_system_files(used_particle_systems, source_files);
+		info!("Found the particle files of <green>{}</>/<cyan>{}</> particle systems in source files", particle_system_files.len(), used_particle_systems.len());
+		for (particle_system, particle_source_path) in particle_system_files {
+			particle_source_paths
+				.entry(particle_source_path)
+				.or_insert(format!("Defines particle system \"{}\" ({})", particle_system, used_particle_systems[&particle_system]));
+		}
+	}
+
+	if particle_source_paths.len() > 0 {
+
+		info!("Collecting <cyan>{}</> particle files...", particle_source_paths.len());
+
+		for (particle_source_path, reason) in particle_source_paths {
+			match source_files.get(&particle_source_path) {
+				Some(source_file) => {
+
+					// Particles use sprite materials, which are collected like any other material
+					for material_source_path in read_particle_materials(source_file) {
+						resolve_file(source_files, material_source_path, format!("Used by particle file \"{}\"", source_file.local_path), &mut content.used_materials, &mut content.missing_materials);
+					}
+
+					content.used_particles.insert(particle_source_path, source_file.to_owned());
+
+				},
+				None => {
+					content.missing_particles.insert(particle_source_path, reason);
+				}
+			}
+		}
+
+		let particle_files_count = content.used_particles.keys().filter(|source_path| source_path.ends_with(".pcf")).count();
+		info!("Found <green>{}</> particle files, <red>{}</> are missing in source files", particle_files_count, content.missing_particles.len());
+
+	}
+
+}
+
+// Collects the navigation mesh (.nav) and AI node graph (.ain) of the map
+pub fn resolve_nav_files(context: &CollectContext, content: &mut ResolvedContent, map_name: &str) {
+
+	let source_files = context.source_files;
+
+	// The engine looks for the node graph in maps\graphs\, but some content packs ship it next to the .nav
+	let nav_candidates = [
+		("navigation mesh", vec![format!("maps\\{}.nav", map_name)]),
+		("AI node graph", vec![format!("maps\\graphs\\{}.ain", map_name), format!("maps\\{}.ain", map_name)]),
+	];
+
+	for (name, candidates) in nav_candidates {
+		match candidates.iter().find(|candidate| source_files.contains_key(*candidate)) {
+			Some(source_file_path) => {
+
+				let mut source_file = source_files[source_file_path].to_owned();
+				success!("Found {} \"<green>{}</>\"", name, source_file.local_path);
+
+				// A file found at a fallback path is copied to the path the engine loads it from
+				if source_file_path != &candidates[0] {
+					source_file.local_path = PathBuf::from_iter(candidates[0].split('\\')).to_string_lossy().to_string();
+				}
+
+				content.used_nav_files.insert(candidates[0].to_owned(), source_file);
+
+			},
+			None => {
+				warn!("Did not find {} in source files", name);
+				content.missing_nav_files.insert(candidates[0].to_owned(), format!("Expected {} of map \"{}\"", name, map_name));
+			}
+		}
+	}
+
+}
+
+// Returns the materials referenced by a Lua file via Material("...") / surface.GetTextureID("..."), as written in the file
+pub fn read_lua_material_references(lua_content: &str) -> Vec<String> {
+
+	let lua_material_regex = Regex::new(r#"(?:\bMaterial|surface\.GetTextureID)\(\s*["']([^"']+)["']"#).unwrap();
+
+	return lua_material_regex
+		.captures_iter(lua_content)
+		.map(|captures| captures[1].to_owned())
+		.unique()
+		.collect();
+
+}
+
+// Returns the GUI layout files (.res) referenced by a Lua file, as written in the file.
+// Layout files are loaded via e.g. panel:LoadControlsFromFile("resource/ui/menu.res") or vgui.Create("Frame"):LoadControlsFromFile(...),
+// but are also often kept in a variable first, so every string literal ending in .res is a reference.
+pub fn read_lua_layout_files(lua_content: &str) -> Vec<String> {
+
+	let lua_layout_file_regex = Regex::new(r#"(?i)["']([^"'\r\n]+\.res)["']"#).unwrap();
+
+	return lua_layout_file_regex
+		.captures_iter(lua_content)
+		.map(|captures| captures[1].to_owned())
+		.unique()
+		.collect();
+
+}
+
+pub fn read_instances(vmf: &Path, entities: &Vec<plumber_core::vmf::Entity>, instances_dir: &Option<PathBuf>, visited_vmfs: &mut HashSet<PathBuf>) -> Vec<plumber_core::vmf::Vmf> {
+
+	let mut instances: Vec<plumber_core::vmf::Vmf> = vec!();
+
+	for ent in entities {
+
+		if ent.class_name != "func_instance" {
+			continue;
+		}
+
+		let instance_file = match ent.properties.get(UncasedStr::new("file")) {
+			Some(file) if !file.is_empty() => file,
+			_ => continue,
+		};
+
+		// Hammer resolves instances relative to the vmf containing them, or relative to the instances directory
+		let mut instance_candidates: Vec<PathBuf> = vec!();
+		if let Some(vmf_dir) = vmf.parent() {
+			instance_candidates.push(vmf_dir.join(instance_file));
+		}
+		if let Some(instances_dir) = instances_dir {
+			instance_candidates.push(instances_dir.join(instance_file));
+		}
+
+		let instance_path = match instance_candidates.into_iter().find(|candidate| candidate.is_file()) {
+			Some(path) => path,
+			None => {
+				warn!("Failed to find instance \"{}\" used by func_instance {} in \"{}\"", instance_file, ent.id, vmf.display());
+				continue;
+			}
+		};
+
+		// Guard against instances including each other
+		if !visited_vmfs.insert(fs::canonicalize(&instance_path).unwrap_or(instance_path.to_owned())) {
+			continue;
+		}
+
+		let instance_content = match fs::read(&instance_path) {
+			Ok(content) => content,
+			Err(err) => {
+				warn!("Failed to read instance \"{}\": {}", instance_path.display(), err.to_string());
+				continue;
+			}
+		};
+
+		let instance_parsed = match plumber_core::vmf::from_bytes(&instance_content) {
+			Ok(parsed) => parsed,
+			Err(err) => {
+				warn!("Failed to parse instance \"{}\": {}", instance_path.display(), err.to_string());
+				continue;
+			}
+		};
+
+		info!("Reading instance \"<green>{}</>\"...", instance_path.display());
+
+		let nested_instances = read_instances(&instance_path, &instance_parsed.entities, instances_dir, visited_vmfs);
+		instances.push(instance_parsed);
+		instances.extend(nested_instances);
+
+	}
+
+	return instances;
+
+}
+
+// Keyvalue rules of an entity class: the rules of the fgd if it defines the class, otherwise ENTITY_KEYVALUE_RULES
+pub fn entity_keyvalue_rules(class_name: &str, fgd_keyvalue_rules: &HashMap<String, Vec<(String, KeyValueContentKind)>>) -> Vec<(String, KeyValueContentKind)> {
+
+	return match fgd_keyvalue_rules.get(&class_name.to_lowercase()) {
+		// Fgds have no property type for color correction files and declare screen overlays / material overrides as plain strings, so the built-in rules for them always apply
+		Some(rules) => rules
+			.iter()
+			.cloned()
+			.chain(
+				ENTITY_KEYVALUE_RULES
+					.iter()
+					.filter(|(key, kind)| matches!(kind, KeyValueContentKind::Raw) || key.starts_with("overlayname") || *key == "materialoverride")
+					.map(|(key, kind)| (key.to_string(), *kind))
+			)
+			.collect(),
+		None => ENTITY_KEYVALUE_RULES.iter().map(|(key, kind)| (key.to_string(), *kind)).collect(),
+	};
+
+}
+
+// Source path of the world model an entity class defines instead of a "model" keyvalue (see ENTITY_CLASS_MODELS and the studio models of the fgd)
+// and whether it is reported as missing if it is not in the source files, which is only the case for classes which are not built into the game
+pub fn entity_class_model(class_name: &str, fgd_class_models: &HashMap<String, String>) -> Option<(String, bool)> {
+
+	let class_name = class_name.to_lowercase();
+	let built_in_model = ENTITY_CLASS_MODELS.iter().find(|(class, _)| *class == class_name).map(|(_, model)| *model);
+
+	let (model, report_missing) = match fgd_class_models.get(&class_name) {
+		Some(model) => (model.as_str(), built_in_model.is_none()),
+		None => (built_in_model?, false),
+	};
+
+	return make_model_path(model).map(|model_source_path| (model_source_path, report_missing));
+
+}
+
+// Source path of the content referenced by the value of a keyvalue and whether it is reported as missing if it is not in the source files,
+// or None if the value is no reference to a file
+pub fn keyvalue_content_path(key: &str, kind: KeyValueContentKind, value: &str) -> Option<(String, bool)> {
+
+	return match kind {
+		KeyValueContentKind::Material => {
+			let material_source_path = make_material_path(value);
+			// The default rope materials are part of the game
+			let report_missing = !(key.eq_ignore_ascii_case("ropematerial") && DEFAULT_ROPE_MATERIALS.contains(&material_source_path.as_str()));
+			Some((material_source_path, report_missing))
+		},
+		KeyValueContentKind::Texture => {
+			let texture_source_path = make_texture_path(value.trim());
+			// The default projected texture (flashlight) cookie is part of the game
+			let report_missing = !DEFAULT_PROJECTED_TEXTURES.contains(&texture_source_path.as_str());
+			Some((texture_source_path, report_missing))
+		},
+		KeyValueContentKind::Model => make_model_path(value).map(|model_source_path| (model_source_path, true)),
+		KeyValueContentKind::Sound => {
+			// Sound keyvalues usually contain soundscript names (e.g. "Doors.Move1"), only sound files can be collected
+			let value_lowercase = value.to_lowercase();
+			match SOUND_EXTENSIONS.iter().any(|extension| value_lowercase.ends_with(extension)) {
+				true => Some((make_sound_path(value), true)),
+				false => None,
+			}
+		},
+		KeyValueContentKind::Scene => Some((make_scene_path(value), true)),
+		KeyValueContentKind::Raw => {
+			// The keyvalue may reference other files for other entities, only .raw lookup files are collected
+			let raw_source_path = value.trim().replace("/", "\\").to_lowercase();
+			match raw_source_path.ends_with(".raw") {
+				true => Some((raw_source_path, true)),
+				false => None,
+			}
+		},
+		KeyValueContentKind::VehicleScript => Some((value.trim().replace("/", "\\").to_lowercase(), true)),
+	};
+
+}
+
+// Derives the keyvalue rules of every entity class from the fgd property types, including the properties of base classes
+pub fn fgd_entity_keyvalue_rules(classes: &Vec<library::fgd::FgdClass>) -> HashMap<String, Vec<(String, KeyValueContentKind)>> {
+
+	let classes_by_name: HashMap<&str, &library::fgd::FgdClass> = classes.iter().map(|class| (class.name.as_str(), class)).collect();
+	let mut rules: HashMap<String, Vec<(String, KeyValueContentKind)>> = HashMap::new();
+
+	for class in classes {
+
+		let mut class_rules: Vec<(String, KeyValueContentKind)> = vec!();
+		let mut pending_classes: Vec<&str> = vec![class.name.as_str()];
+		let mut visited_classes: HashSet<&str> = HashSet::new();
+
+		while let Some(class_name) = pending_classes.pop() {
+
+			if !visited_classes.insert(class_name) {
+				continue;
+			}
+
+			let current_class = match classes_by_name.get(class_name) {
+				Some(current_class) => current_class,
+				None => continue,
+			};
+
+			for (key, property_type) in &current_class.properties {
+
+				// The "model" keyvalue is already handled separately (including env_sprite materials and brush models)
+				if key == "model" || class_rules.iter().any(|(rule_key, _)| rule_key == key) {
+					continue;
+				}
+
+				let kind = match property_type.as_str() {
+					// The cookie of env_projectedtexture is declared as material, but references a texture
+					"material" if key == "texturename" => KeyValueContentKind::Texture,
+					"material" | "sprite" | "decal" => KeyValueContentKind::Material,
+					"studio" => KeyValueContentKind::Model,
+					"sound" => KeyValueContentKind::Sound,
+					"scene" => KeyValueContentKind::Scene,
+					_ => continue,
+				};
+
+				class_rules.push((key.to_owned(), kind));
+
+			}
+
+			pending_classes.extend(current_class.bases.iter().map(|base| base.as_str()));
+
+		}
+
+		rules.insert(class.name.to_owned(), class_rules);
+
+	}
+
+	return rules;
+
+}
+
+// Center of the bounding box of the points which define the planes of the solids' sides, which are points on the faces of the solids
+pub fn solids_center(solids: &[plumber_core::vmf::Solid]) -> Option<[f64; 3]> {
+
+	let mut min = [f64::MAX; 3];
+	let mut max = [f64::MIN; 3];
+	let mut has_points = false;
+
+	for point in solids.iter().flat_map(|solid| solid.sides.iter()).flat_map(|side| side.plane.0.iter()) {
+		for (axis, coordinate) in [point.x, point.y, point.z].into_iter().enumerate() {
+			min[axis] = min[axis].min(coordinate as f64);
+			max[axis] = max[axis].max(coordinate as f64);
+		}
+		has_points = true;
+	}
+
+	if !has_points {
+		return None;
+	}
+
+	return Some([(min[0] + max[0]) / 2.0, (min[1] + max[1]) / 2.0, (min[2] + max[2]) / 2.0]);
+
+}
+
+pub fn parse_origin(origin: &str) -> Option<[f64; 3]> {
+
+	let coordinates: Vec<f64> = origin
+		.split_whitespace()
+		.filter_map(|coordinate| coordinate.parse().ok())
+		.collect();
+
+	if coordinates.len() != 3 {
+		return None;
+	}
+
+	return Some([coordinates[0], coordinates[1], coordinates[2]]);
+
+}
+
+// Entity names in inputs and templates may end with a "*" wildcard
+pub fn matches_entity_name(pattern: &str, name: &str) -> bool {
+
+	let pattern = pattern.trim().to_lowercase();
+	let name = name.trim().to_lowercase();
+
+	return match pattern.strip_suffix('*') {
+		Some(prefix) => name.starts_with(prefix),
+		None => name == pattern,
+	};
+
+}
+
+// Whether an entity with this name is spawned by any of the point_template entities (name, template targets)
+pub fn is_templated_entity(point_templates: &Vec<(String, Vec<String>)>, name: &str) -> bool {
+	return point_templates.iter().any(|(_, template_targets)| template_targets.iter().any(|template_target| matches_entity_name(template_target, name)));
+}
+
+// Whether the entity class matches any of the classes excluded from collection, which may end with a "*" wildcard
+pub fn is_excluded_class(exclude_class: &Vec<String>, class_name: &str) -> bool {
+	return exclude_class.iter().any(|class| matches_entity_name(class, class_name));
+}
+
+pub fn is_within_distance(a: &[f64; 3], b: &[f64; 3], distance: f64) -> bool {
+	return a.iter().zip(b.iter()).all(|(a, b)| (a - b).abs() <= distance);
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+	use super::super::test_utils::*;
+
+	#[test]
+	fn nav_files_fall_back_to_node_graph_next_to_nav() {
+
+		let directory = test_directory("nav_files");
+		let source_files: HashMap<String, SourceContentFile> = HashMap::from([
+			source_file(&directory, "maps/my_map.nav"),
+			source_file(&directory, "maps/my_map.ain"),
+		]);
+
+		let open_fs = collector::open_file_system(None).unwrap();
+		let options = test_options(&["--include-nav"]);
+		let context = test_context(&source_files, &open_fs, &options);
+
+		let mut content = ResolvedContent::new();
+		resolve_nav_files(&context, &mut content, "my_map");
+		let summary_categories = filter::filter_content(&context, &mut content);
+
+		let summary = summary_categories.iter().find(|category| category.name == "Navigation / AI node graph").unwrap();
+		assert_eq!(summary.found, 2);
+		assert_eq!(summary.missing, 0);
+
+		// The node graph next to the .nav is copied to maps/graphs/, where the engine loads it from
+		let node_graph = &content.used_nav_files["maps\\graphs\\my_map.ain"];
+		assert_eq!(node_graph.local_path.replace("\\", "/"), "maps/graphs/my_map.ain");
+		assert_eq!(node_graph.full_path, directory.join("maps/my_map.ain").to_string_lossy());
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn missing_nav_files_are_reported_at_their_expected_path() {
+
+		let directory = test_directory("missing_nav_files");
+		let source_files: HashMap<String, SourceContentFile> = HashMap::from([source_file(&directory, "maps/my_map.nav")]);
+
+		let open_fs = collector::open_file_system(None).unwrap();
+		let options = test_options(&["--include-nav"]);
+		let context = test_context(&source_files, &open_fs, &options);
+
+		let mut content = ResolvedContent::new();
+		resolve_nav_files(&context, &mut content, "my_map");
+
+		assert_eq!(content.used_nav_files.len(), 1);
+		assert_eq!(content.missing_nav_files.keys().collect::<Vec<_>>(), vec!("maps\\graphs\\my_map.ain"));
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn brush_models_are_skipped_and_prefixless_models_are_collected() {
+
+		let source_path = test_directory("model_paths_collect");
+		write_files(&source_path, &[("materials/models/props_c17/oildrum001.vmt", "VertexLitGeneric { }")]);
+		write_minimal_model(&source_path, "models/props_c17/oildrum001.mdl", "models/props_c17/", "oildrum001");
+		let vmf = write_map(&source_path, "rp_harbor", &[], &[], &[
+			&[("classname", "func_door"), ("model", "*1")],
+			&[("classname", "prop_physics"), ("model", "props_c17/Oildrum001.mdl")],
+		]);
+
+		let open_fs = collector::open_empty_file_system().unwrap();
+		let source_files = build_source_files_map(&vec!(source_path.to_owned()), None, false, None);
+		let options = test_options(&[]);
+		let context = test_context(&source_files, &open_fs, &options);
+
+		let content = resolve_content(&context, &vmf).unwrap();
+
+		// The brush model of the door is part of the compiled map and not reported as missing
+		assert_eq!(content.used_models.keys().collect::<Vec<_>>(), vec!("models\\props_c17\\oildrum001.mdl"));
+		assert!(content.missing_models.is_empty());
+		assert_eq!(content.used_materials.keys().collect::<Vec<_>>(), vec!("materials\\models\\props_c17\\oildrum001.vmt"));
+
+		fs::remove_dir_all(&source_path).unwrap();
+
+	}
+
+	#[test]
+	fn rope_collects_cable_material_and_its_textures() {
+
+		let rope_rules = entity_keyvalue_rules("move_rope", &HashMap::new());
+		let (material_key, material_kind) = rope_rules.iter().find(|(key, _)| key == "ropematerial").unwrap();
+		let (material_source_path, report_missing) = keyvalue_content_path(material_key, *material_kind, "cable/Custom_Cable").unwrap();
+		assert_eq!(material_source_path, "materials\\cable\\custom_cable.vmt");
+		assert!(report_missing);
+
+		let directory = test_directory("cable");
+		write_files(&directory, &[
+			("materials/cable/custom_cable.vmt", "\"Cable\"\n{\n\t\"$basetexture\" \"cable/custom_cable\"\n\t\"$bumpmap\" \"cable/custom_cable_normal\"\n}\n"),
+			("materials/cable/custom_cable.vtf", "VTF"),
+			("materials/cable/custom_cable_normal.vtf", "VTF"),
+		]);
+		let source_files: HashMap<String, SourceContentFile> = HashMap::from([
+			source_file(&directory, "materials/cable/custom_cable.vmt"),
+			source_file(&directory, "materials/cable/custom_cable.vtf"),
+			source_file(&directory, "materials/cable/custom_cable_normal.vtf"),
+		]);
+
+		let open_fs = collector::open_empty_file_system().unwrap();
+		let material_data = read_material_data(&source_files[&material_source_path].full_path, &source_files, &open_fs).unwrap();
+
+		assert_eq!(material_data.used_textures.keys().sorted().collect::<Vec<_>>(), vec!("materials\\cable\\custom_cable.vtf", "materials\\cable\\custom_cable_normal.vtf"));
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn only_custom_rope_materials_are_reported_missing() {
+
+		assert_eq!(keyvalue_content_path("RopeMaterial", KeyValueContentKind::Material, "cable/cable.vmt"), Some((String::from("materials\\cable\\cable.vmt"), false)));
+		assert_eq!(keyvalue_content_path("RopeMaterial", KeyValueContentKind::Material, "cable/rope"), Some((String::from("materials\\cable\\rope.vmt"), false)));
+		assert_eq!(keyvalue_content_path("RopeMaterial", KeyValueContentKind::Material, "mymap/rope_red"), Some((String::from("materials\\mymap\\rope_red.vmt"), true)));
+
+		// The default rope materials are only exempt for ropes
+		assert_eq!(keyvalue_content_path("material", KeyValueContentKind::Material, "cable/cable"), Some((String::from("materials\\cable\\cable.vmt"), true)));
+
+	}
+
+	#[test]
+	fn rope_materials_of_the_map_are_collected() {
+
+		let source_path = test_directory("rope_collect");
+		write_files(&source_path, &[
+			("materials/mymap/rope_red.vmt", "Cable\n{\n\t$basetexture mymap/rope_red\n}\n"),
+			("materials/mymap/rope_red.vtf", "VTF"),
+		]);
+		let vmf = write_map(&source_path, "rp_harbor", &[], &[], &[
+			&[("classname", "move_rope"), ("RopeMaterial", "mymap/rope_red")],
+			&[("classname", "keyframe_rope"), ("RopeMaterial", "cable/cable.vmt")],
+			&[("classname", "keyframe_rope"), ("RopeMaterial", "mymap/rope_blue")],
+		]);
+
+		let open_fs = collector::open_empty_file_system().unwrap();
+		let source_files = build_source_files_map(&vec!(source_path.to_owned()), None, false, None);
+		let options = test_options(&[]);
+		let context = test_context(&source_files, &open_fs, &options);
+
+		let content = resolve_content(&context, &vmf).unwrap();
+
+		// The default cable material is part of the game, the missing custom one is reported
+		assert_eq!(content.used_materials.keys().collect::<Vec<_>>(), vec!("materials\\mymap\\rope_red.vmt"));
+		assert_eq!(content.missing_materials.keys().collect::<Vec<_>>(), vec!("materials\\mymap\\rope_blue.vmt"));
+		assert_eq!(content.materials_data.used_textures.keys().collect::<Vec<_>>(), vec!("materials\\mymap\\rope_red.vtf"));
+
+		fs::remove_dir_all(&source_path).unwrap();
+
+	}
+
+	#[test]
+	fn point_template_targets_are_templated() {
+
+		// point_template "crate_spawner" with Template01 "crate_custom" and Template02 "gib_*"
+		let point_templates = vec!((String::from("crate_spawner"), vec!(String::from("crate_custom"), String::from("gib_*"))));
+
+		assert!(is_templated_entity(&point_templates, "Crate_Custom"));
+		assert!(is_templated_entity(&point_templates, "gib_wood_01"));
+		assert!(!is_templated_entity(&point_templates, "crate_custom_2"));
+		assert!(!is_templated_entity(&point_templates, "crate_spawner"));
+
+		// The custom model of the templated prop is collected like the model of any other prop
+		assert_eq!(make_model_path("models/mymap/crate_custom.mdl"), Some(String::from("models\\mymap\\crate_custom.mdl")));
+
+	}
+
+	#[test]
+	fn content_of_point_template_targets_is_resolved() {
+
+		// The crate is only spawned at runtime by the env_entity_maker through the point_template
+		let vmf = "versioninfo\n{\n\t\"editorversion\" \"400\"\n}\nworld\n{\n\t\"id\" \"1\"\n\t\"classname\" \"worldspawn\"\n}\n\
+			entity\n{\n\t\"id\" \"2\"\n\t\"classname\" \"point_template\"\n\t\"targetname\" \"crate_spawner\"\n\t\"Template01\" \"crate_custom\"\n\t\"origin\" \"0 0 0\"\n}\n\
+			entity\n{\n\t\"id\" \"3\"\n\t\"classname\" \"prop_physics\"\n\t\"targetname\" \"crate_custom\"\n\t\"model\" \"models/mymap/crate_custom.mdl\"\n\t\"origin\" \"0 0 0\"\n}\n\
+			entity\n{\n\t\"id\" \"4\"\n\t\"classname\" \"env_entity_maker\"\n\t\"EntityTemplate\" \"crate_spawner\"\n\t\"origin\" \"0 0 0\"\n}\n";
+
+		let directory = test_directory("point_template");
+		write_files(&directory, &[
+			("maps/harbor.vmf", vmf),
+			("materials/models/mymap/crate_custom.vmt", "VertexLitGeneric\n{\n\t\"$basetexture\" \"models/mymap/crate_custom\"\n}\n"),
+			("materials/models/mymap/crate_custom.vtf", "VTF"),
+		]);
+		write_minimal_model(&directory, "models/mymap/crate_custom.mdl", "models/mymap/", "crate_custom");
+
+		let source_files: HashMap<String, SourceContentFile> = HashMap::from([
+			source_file(&directory, "models/mymap/crate_custom.mdl"),
+			source_file(&directory, "models/mymap/crate_custom.vvd"),
+			source_file(&directory, "models/mymap/crate_custom.dx90.vtx"),
+			source_file(&directory, "materials/models/mymap/crate_custom.vmt"),
+			source_file(&directory, "materials/models/mymap/crate_custom.vtf"),
+		]);
+
+		let open_fs = collector::open_empty_file_system().unwrap();
+		let options = test_options(&["--report-templated"]);
+		let context = test_context(&source_files, &open_fs, &options);
+
+		let content = resolve_content(&context, &directory.join("maps/harbor.vmf")).unwrap();
+
+		assert_eq!(content.point_templates_count, 1);
+		assert_eq!(content.templated_entities_count, 1);
+		assert_eq!(content.used_models.keys().collect::<Vec<_>>(), vec!("models\\mymap\\crate_custom.mdl"));
+		assert!(content.templated_references.contains("models\\mymap\\crate_custom.mdl"));
+		assert!(!content.untemplated_references.contains("models\\mymap\\crate_custom.mdl"));
+		assert_eq!(content.used_materials.keys().collect::<Vec<_>>(), vec!("materials\\models\\mymap\\crate_custom.vmt"));
+		assert_eq!(content.materials_data.used_textures.keys().collect::<Vec<_>>(), vec!("materials\\models\\mymap\\crate_custom.vtf"));
+		assert!(content.missing_models.is_empty() && content.missing_materials.is_empty());
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn color_correction_collects_raw_lookup_file() {
+
+		// The fgd declares the filename of color_correction as a plain string, the built-in rule for .raw files applies anyway
+		let fgd_keyvalue_rules = HashMap::from([(String::from("color_correction"), vec!())]);
+		let rules = entity_keyvalue_rules("Color_Correction", &fgd_keyvalue_rules);
+		let (key, kind) = rules.iter().find(|(key, _)| key == "filename").unwrap();
+
+		assert_eq!(keyvalue_content_path(key, *kind, "Materials/Correction/Night.raw"), Some((String::from("materials\\correction\\night.raw"), true)));
+
+		// Other entities use "filename" for other files
+		assert_eq!(keyvalue_content_path(key, *kind, "maps/mymap.txt"), None);
+
+	}
+
+	#[test]
+	fn particle_manifest_collects_listed_particle_files() {
+
+		let directory = test_directory("particle_manifest");
+		write_files(&directory, &[
+			("maps/my_map_particles.txt", "particles_manifest\n{\n\t\"file\" \"!particles/My_Map_Fire.pcf\"\n\t\"file\" \"particles/my_map_smoke.pcf\"\n}\n"),
+			("particles/my_map_fire.pcf", "<!-- dmx encoding binary 2 format pcf 1 -->\0my_map_fire\0particles/my_map/flame.vmt\0"),
+			("materials/particles/my_map/flame.vmt", "SpriteCard { }"),
+		]);
+		let source_files: HashMap<String, SourceContentFile> = HashMap::from([
+			source_file(&directory, "maps/my_map_particles.txt"),
+			source_file(&directory, "particles/my_map_fire.pcf"),
+			source_file(&directory, "materials/particles/my_map/flame.vmt"),
+		]);
+
+		let open_fs = collector::open_file_system(None).unwrap();
+		let options = test_options(&[]);
+		let context = test_context(&source_files, &open_fs, &options);
+
+		let mut content = ResolvedContent::new();
+		resolve_particles(&context, &mut content, "my_map");
+
+		// The manifest is collected as well, since the engine reads it
+		assert_eq!(content.used_particles.keys().sorted().collect::<Vec<_>>(), vec!("maps\\my_map_particles.txt", "particles\\my_map_fire.pcf"));
+		assert_eq!(content.missing_particles.keys().collect::<Vec<_>>(), vec!("particles\\my_map_smoke.pcf"));
+		assert_eq!(content.used_materials.keys().collect::<Vec<_>>(), vec!("materials\\particles\\my_map\\flame.vmt"));
+		assert!(content.missing_materials.is_empty());
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn projected_texture_cookie_is_a_texture() {
+
+		// The fgd declares the cookie as material, but it is a texture
+		let fgd_classes = vec!(library::fgd::FgdClass {
+			name: String::from("env_projectedtexture"),
+			bases: vec!(),
+			properties: vec!((String::from("texturename"), String::from("material")), (String::from("lightfov"), String::from("float"))),
+			studio: None,
+		});
+		let fgd_keyvalue_rules = fgd_entity_keyvalue_rules(&fgd_classes);
+		let rules = entity_keyvalue_rules("env_projectedtexture", &fgd_keyvalue_rules);
+		let (key, kind) = rules.iter().find(|(key, _)| key == "texturename").unwrap();
+		assert!(matches!(kind, KeyValueContentKind::Texture));
+
+		assert_eq!(keyvalue_content_path(key, *kind, "mymap/cookies/window_blinds"), Some((String::from("materials\\mymap\\cookies\\window_blinds.vtf"), true)));
+
+		// The default flashlight cookie is part of the game
+		assert_eq!(keyvalue_content_path(key, *kind, "effects/flashlight001"), Some((String::from("materials\\effects\\flashlight001.vtf"), false)));
+
+	}
+
+	#[test]
+	fn screen_overlay_materials_are_collected() {
+
+		// env_screenoverlay references up to ten overlays, fgds declare them as plain strings
+		let fgd_keyvalue_rules = HashMap::from([(String::from("env_screenoverlay"), vec!())]);
+		let rules = entity_keyvalue_rules("env_screenoverlay", &fgd_keyvalue_rules);
+		let overlay_keys: Vec<&String> = rules.iter().map(|(key, _)| key).filter(|key| key.starts_with("overlayname")).collect();
+		assert_eq!(overlay_keys.len(), 10);
+
+		let (key, kind) = rules.iter().find(|(key, _)| key == "overlayname1").unwrap();
+		assert_eq!(keyvalue_content_path(key, *kind, "effects/mymap/Underwater_Overlay"), Some((String::from("materials\\effects\\mymap\\underwater_overlay.vmt"), true)));
+
+	}
+
+	#[test]
+	fn weapon_scripts_are_only_resolved_with_the_flag() {
+
+		let source_path = test_directory("weapon_scripts");
+		write_files(&source_path, &[
+			("scripts/weapon_mymap_rifle.txt", concat!(
+				"WeaponData\n{\n",
+				"\t\"viewmodel\" \"models/weapons/v_mymap_rifle.mdl\"\n",
+				"\tSoundData\n\t{\n\t\t\"single_shot\" \"Weapon_SMG1.Single\"\n\t\t\"reload\" \"weapons/mymap_rifle/reload.wav\"\n\t}\n",
+				"\tTextureData\n\t{\n\t\t\"ammo\" { \"file\" \"sprites/mymap/rifle_ammo\" }\n\t}\n}\n",
+			)),
+			("sound/weapons/mymap_rifle/reload.wav", "RIFF"),
+			("materials/sprites/mymap/rifle_ammo.vmt", "UnlitGeneric { }"),
+			("materials/models/weapons/v_mymap_rifle.vmt", "VertexLitGeneric { }"),
+		]);
+		write_minimal_model(&source_path, "models/weapons/v_mymap_rifle.mdl", "models/weapons/", "v_mymap_rifle");
+		let vmf = write_map(&source_path, "rp_harbor", &[], &[], &[&[("classname", "weapon_mymap_rifle")], &[("classname", "weapon_mymap_rifle"), ("origin", "128 0 0")]]);
+
+		let open_fs = collector::open_empty_file_system().unwrap();
+		let source_files = build_source_files_map(&vec!(source_path.to_owned()), None, false, None);
+
+		let options = test_options(&[]);
+		let content = resolve_content(&test_context(&source_files, &open_fs, &options), &vmf).unwrap();
+		assert!(content.used_weapon_scripts.is_empty());
+		assert!(content.used_models.is_empty());
+
+		// The script is resolved once for both weapons of its class
+		let options = test_options(&["--include-weapon-scripts"]);
+		let content = resolve_content(&test_context(&source_files, &open_fs, &options), &vmf).unwrap();
+		assert_eq!(content.used_weapon_scripts.keys().collect::<Vec<_>>(), vec!("scripts\\weapon_mymap_rifle.txt"));
+		assert_eq!(content.used_models.keys().collect::<Vec<_>>(), vec!("models\\weapons\\v_mymap_rifle.mdl"));
+		assert_eq!(content.used_materials.keys().sorted().collect::<Vec<_>>(), vec!("materials\\models\\weapons\\v_mymap_rifle.vmt", "materials\\sprites\\mymap\\rifle_ammo.vmt"));
+		assert_eq!(content.used_sounds.keys().collect::<Vec<_>>(), vec!("sound\\weapons\\mymap_rifle\\reload.wav"));
+
+		fs::remove_dir_all(&source_path).unwrap();
+
+	}
+
+	#[test]
+	fn tool_faces_of_world_and_entity_brushes_are_skipped() {
+
+		let source_path = test_directory("nodraw_collect");
+		write_files(&source_path, &[("materials/brick/wall.vmt", "LightmappedGeneric { }"), ("materials/mymap/trim.vmt", "LightmappedGeneric { }")]);
+		let vmf = write_map(&source_path, "rp_harbor", &[], &["TOOLS/TOOLSNODRAW", "brick/wall", "tools/toolsskybox"], &[]);
+
+		// A func_detail brush entity with a nodraw face
+		let mut vmf_text = fs::read_to_string(&vmf).unwrap();
+		vmf_text.push_str("entity\n{\n\t\"id\" \"3\"\n\t\"classname\" \"func_detail\"\n\tsolid\n\t{\n\t\t\"id\" \"4\"\n");
+		for (side_id, material) in [(1, "tools/toolsnodraw"), (2, "mymap/trim")] {
+			vmf_text.push_str(&format!("\t\tside\n\t\t{{\n\t\t\t\"id\" \"{}\"\n\t\t\t\"plane\" \"(0 0 0) (0 64 0) (64 64 0)\"\n\t\t\t\"material\" \"{}\"\n\t\t\t\"uaxis\" \"[1 0 0 0] 0.25\"\n\t\t\t\"vaxis\" \"[0 -1 0 0] 0.25\"\n\t\t\t\"rotation\" \"0\"\n\t\t\t\"lightmapscale\" \"16\"\n\t\t\t\"smoothing_groups\" \"0\"\n\t\t}}\n", side_id, material));
+		}
+		vmf_text.push_str("\t}\n}\n");
+		fs::write(&vmf, vmf_text).unwrap();
+
+		let open_fs = collector::open_empty_file_system().unwrap();
+		let source_files = build_source_files_map(&vec!(source_path.to_owned()), None, false, None);
+		let options = test_options(&[]);
+		let context = test_context(&source_files, &open_fs, &options);
+
+		let content = resolve_content(&context, &vmf).unwrap();
+
+		assert_eq!(content.used_materials.keys().sorted().collect::<Vec<_>>(), vec!("materials\\brick\\wall.vmt", "materials\\mymap\\trim.vmt"));
+		assert!(content.missing_materials.is_empty());
+
+		fs::remove_dir_all(&source_path).unwrap();
+
+	}
+
+	#[test]
+	fn skybox_faces_and_3d_skybox_materials_are_collected_together() {
+
+		let source_path = test_directory("skybox_collect");
+		write_files(&source_path, &[("materials/mymap/cliff.vmt", "LightmappedGeneric { }"), ("materials/skybox/sky_mymap_dusk_hdrrt.vmt", "Sky { }")]);
+		for face in ["rt", "lf", "bk", "ft", "up"] {
+			write_files(&source_path, &[(&format!("materials/skybox/sky_mymap_dusk{}.vmt", face), "UnlitGeneric { }")]);
+		}
+
+		// The world brush is next to the sky_camera, so it is part of the 3D skybox
+		let vmf = write_map(&source_path, "rp_harbor", &[("skyname", "Sky_MyMap_Dusk")], &["mymap/cliff"], &[&[("classname", "sky_camera"), ("origin", "0 0 0")]]);
+
+		let open_fs = collector::open_empty_file_system().unwrap();
+		let source_files = build_source_files_map(&vec!(source_path.to_owned()), None, false, None);
+		let options = test_options(&["--verify-skybox", "--report-skybox"]);
+		let context = test_context(&source_files, &open_fs, &options);
+
+		let mut content = resolve_content(&context, &vmf).unwrap();
+		filter::filter_content(&context, &mut content);
+
+		assert_eq!(content.skyname.as_deref(), Some("sky_mymap_dusk"));
+		assert_eq!(content.used_materials.len(), 7);
+		assert!(content.used_materials.contains_key("materials\\skybox\\sky_mymap_dusk_hdrrt.vmt"));
+		assert_eq!(content.skybox_solids_count, 1);
+		assert!(content.skybox_references.contains("materials\\mymap\\cliff.vmt"));
+
+		// Without the game, the missing face is still missing after the game content was removed
+		assert_eq!(missing_skybox_faces(&content.skybox_face_source_paths, &content.missing_materials), vec!("materials\\skybox\\sky_mymap_duskdn.vmt"));
+
+		fs::remove_dir_all(&source_path).unwrap();
+
+	}
+
+	#[test]
+	fn excluded_classes_match_exactly_or_by_wildcard() {
+
+		let exclude_class = vec![String::from("prop_physics*"), String::from("Info_Particle_System")];
+
+		assert!(is_excluded_class(&exclude_class, "prop_physics"));
+		assert!(is_excluded_class(&exclude_class, "prop_physics_multiplayer"));
+		assert!(is_excluded_class(&exclude_class, "info_particle_system"));
+		assert!(!is_excluded_class(&exclude_class, "prop_dynamic"));
+		assert!(!is_excluded_class(&exclude_class, "info_particle_system_2"));
+		assert!(!is_excluded_class(&Vec::new(), "prop_physics"));
+
+	}
+
+	#[test]
+	fn content_of_excluded_classes_is_neither_resolved_nor_missing() {
+
+		let source_path = test_directory("exclude_class");
+		write_files(&source_path, &[("materials/models/mymap/barrel.vmt", "VertexLitGeneric { }"), ("materials/models/mymap/crate.vmt", "VertexLitGeneric { }")]);
+		write_minimal_model(&source_path, "models/mymap/barrel.mdl", "models/mymap/", "barrel");
+		write_minimal_model(&source_path, "models/mymap/crate.mdl", "models/mymap/", "crate");
+		let vmf = write_map(&source_path, "rp_harbor", &[], &[], &[
+			&[("classname", "prop_static"), ("model", "models/mymap/barrel.mdl")],
+			&[("classname", "prop_physics"), ("model", "models/mymap/crate.mdl")],
+			&[("classname", "prop_physics_multiplayer"), ("model", "models/dev/placeholder.mdl")],
+		]);
+
+		let open_fs = collector::open_empty_file_system().unwrap();
+		let source_files = build_source_files_map(&vec!(source_path.to_owned()), None, false, None);
+		let options = test_options(&["--exclude-class", "prop_physics*"]);
+		let context = test_context(&source_files, &open_fs, &options);
+
+		let content = resolve_content(&context, &vmf).unwrap();
+
+		assert_eq!(content.used_models.keys().collect::<Vec<_>>(), vec!("models\\mymap\\barrel.mdl"));
+		assert!(content.missing_models.is_empty());
+		assert_eq!(content.used_materials.keys().collect::<Vec<_>>(), vec!("materials\\models\\mymap\\barrel.vmt"));
+
+		fs::remove_dir_all(&source_path).unwrap();
+
+	}
+
+	#[test]
+	fn button_sound_keyvalues_resolve_only_sound_files() {
+
+		let rules = entity_keyvalue_rules("func_button", &HashMap::new());
+		let (key, kind) = rules.iter().find(|(key, _)| key == "locked_sound").unwrap();
+		assert!(matches!(kind, KeyValueContentKind::Sound));
+
+		assert_eq!(keyvalue_content_path(key, *kind, "MyMap/Buttons/Denied.wav"), Some((String::from("sound\\mymap\\buttons\\denied.wav"), true)));
+		assert_eq!(keyvalue_content_path("noise1", KeyValueContentKind::Sound, ")mymap/doors/creak.mp3"), Some((String::from("sound\\mymap\\doors\\creak.mp3"), true)));
+
+		// Numeric sound presets and soundscript names are no files
+		assert_eq!(keyvalue_content_path(key, *kind, "2"), None);
+		assert_eq!(keyvalue_content_path("noise1", KeyValueContentKind::Sound, "Doors.Move1"), None);
+
+	}
+
+	#[test]
+	fn prop_material_override_is_a_material_reference() {
+
+		// The fgd declares the override as a plain string, so the built-in rule still applies
+		let fgd_classes = library::fgd::parse("@PointClass studio() = prop_dynamic : \"Dynamic prop\"\n[\n\tmaterialoverride(string) : \"Material Override\" : \"\"\n\tskin(integer) : \"Skin\" : 0\n]\n");
+		let rules = entity_keyvalue_rules("prop_dynamic", &fgd_entity_keyvalue_rules(&fgd_classes));
+
+		let (key, kind) = rules.iter().find(|(key, _)| key == "materialoverride").unwrap();
+		assert!(matches!(kind, KeyValueContentKind::Material));
+
+		assert_eq!(keyvalue_content_path(key, *kind, "models/MyMap/crate_red"), Some((String::from("materials\\models\\mymap\\crate_red.vmt"), true)));
+
+	}
+
+	#[test]
+	fn manifest_and_entity_particles_are_collected_once() {
+
+		let directory = test_directory("particle_sources");
+		write_files(&directory, &[
+			("maps/rp_harbor_particles.txt", "particles_manifest\n{\n\t\"file\" \"particles/harbor_fx.pcf\"\n}\n"),
+			("particles/harbor_fx.pcf", "<!-- dmx encoding binary 2 format pcf 1 -->\0harbor_steam\0particles/harbor/smoke.vmt\0"),
+			("particles/harbor_water.pcf", "<!-- dmx encoding binary 2 format pcf 1 -->\0harbor_splash\0particles/harbor/smoke.vmt\0"),
+			("materials/particles/harbor/smoke.vmt", "SpriteCard { }"),
+		]);
+		let source_files: HashMap<String, SourceContentFile> = [
+			"maps/rp_harbor_particles.txt",
+			"particles/harbor_fx.pcf",
+			"particles/harbor_water.pcf",
+			"materials/particles/harbor/smoke.vmt",
+		].iter().map(|local_path| source_file(&directory, local_path)).collect();
+
+		let open_fs = collector::open_file_system(None).unwrap();
+		let options = test_options(&[]);
+		let context = test_context(&source_files, &open_fs, &options);
+
+		// harbor_steam is also listed by the manifest, while the game particle system is not defined by any source file
+		let mut content = ResolvedContent::new();
+		content.used_particle_systems = HashMap::from([
+			(String::from("harbor_steam"), String::from("Used by entity 12 with class info_particle_system")),
+			(String::from("harbor_splash"), String::from("Used by entity 15 with class info_particle_system")),
+			(String::from("env_fire_large"), String::from("Used by entity 18 with class info_particle_system")),
+		]);
+
+		resolve_particles(&context, &mut content, "rp_harbor");
+
+		assert_eq!(content.used_particles.keys().sorted().collect::<Vec<_>>(), vec!("maps\\rp_harbor_particles.txt", "particles\\harbor_fx.pcf", "particles\\harbor_water.pcf"));
+		assert!(content.missing_particles.is_empty());
+		assert_eq!(content.used_materials.keys().collect::<Vec<_>>(), vec!("materials\\particles\\harbor\\smoke.vmt"));
+		assert!(content.missing_materials.is_empty());
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn lua_layout_file_references_are_found() {
+
+		let lua_content = r#"
+local PANEL = {}
+local LAYOUT = "resource/ui/MyAddon_Menu.res"
+
+function PANEL:Init()
+	self:LoadControlsFromFile(LAYOUT)
+end
+
+concommand.Add("myaddon_scoreboard", function()
+	local frame = vgui.Create("DFrame")
+	frame:LoadControlsFromFile('resource/ui/myaddon_scoreboard.res')
+	frame:SetTitle("Scores")
+	frame:LoadControlsFromFile("resource/ui/MyAddon_Menu.res")
+end)
+"#;
+
+		assert_eq!(read_lua_layout_files(lua_content), vec!("resource/ui/MyAddon_Menu.res", "resource/ui/myaddon_scoreboard.res"));
+
+	}
+
+	#[test]
+	fn lua_material_references_are_read_once_as_written() {
+
+		let lua_content = r#"
+local icon = Material("hud/MyAddon_Icon.png", "smooth")
+local texture = surface.GetTextureID( 'hud/myaddon_bar' )
+local other = Material("hud/MyAddon_Icon.png")
+local name = "hud/not_a_material"
+"#;
+
+		assert_eq!(read_lua_material_references(lua_content), vec!("hud/MyAddon_Icon.png", "hud/myaddon_bar"));
+
+	}
+
+	#[test]
+	fn item_classes_resolve_their_world_model() {
+
+		let no_fgd: HashMap<String, String> = HashMap::new();
+
+		// Built-in models are part of the game, so they are only collected if the source paths override them
+		assert_eq!(entity_class_model("item_healthkit", &no_fgd), Some((String::from("models\\items\\healthkit.mdl"), false)));
+		assert_eq!(entity_class_model("Item_Battery", &no_fgd), Some((String::from("models\\items\\battery.mdl"), false)));
+		assert_eq!(entity_class_model("prop_physics", &no_fgd), None);
+
+		let fgd_class_models: HashMap<String, String> = HashMap::from([
+			(String::from("item_mymod_keycard"), String::from("models/mymod/keycard.mdl")),
+			(String::from("item_healthkit"), String::from("models/mymod/medkit.mdl")),
+		]);
+
+		// Custom classes of the fgd are reported as missing, unlike fgds which only change the model of a built-in class
+		assert_eq!(entity_class_model("item_mymod_keycard", &fgd_class_models), Some((String::from("models\\mymod\\keycard.mdl"), true)));
+		assert_eq!(entity_class_model("item_healthkit", &fgd_class_models), Some((String::from("models\\mymod\\medkit.mdl"), false)));
+
+	}
+
+	#[test]
+	fn world_models_of_item_and_weapon_classes_are_resolved() {
+
+		let source_path = test_directory("class_models");
+		let fgd_path = test_directory("class_models_fgd").join("mymod.fgd");
+		write_files(&source_path, &[
+			("materials/models/items/healthkit.vmt", "VertexLitGeneric { }"),
+			("materials/models/mymod/wrench.vmt", "VertexLitGeneric { }"),
+		]);
+		write_minimal_model(&source_path, "models/items/healthkit.mdl", "models/items/", "healthkit");
+		write_minimal_model(&source_path, "models/mymod/wrench.mdl", "models/mymod/", "wrench");
+		fs::write(&fgd_path, concat!(
+			"@PointClass studio(\"models/mymod/keycard.mdl\") = item_mymod_keycard : \"Keycard\" []\n",
+			"@PointClass studio(\"models/mymod/wrench.mdl\") = weapon_mymod_wrench : \"Wrench\" []\n",
+		)).unwrap();
+		let vmf = write_map(&source_path, "rp_harbor", &[], &[], &[
+			&[("classname", "item_healthkit")],
+			&[("classname", "item_battery")],
+			&[("classname", "item_mymod_keycard")],
+			&[("classname", "weapon_mymod_wrench")],
+		]);
+
+		let open_fs = collector::open_empty_file_system().unwrap();
+		let source_files = build_source_files_map(&vec!(source_path.to_owned()), None, false, None);
+		let options = test_options(&["--fgd", &fgd_path.to_string_lossy()]);
+		let context = test_context(&source_files, &open_fs, &options);
+
+		let content = resolve_content(&context, &vmf).unwrap();
+
+		// The overridden healthkit and the wrench are collected, the battery of the game is not missing, unlike the keycard of the fgd
+		assert_eq!(content.used_models.keys().sorted().collect::<Vec<_>>(), vec!("models\\items\\healthkit.mdl", "models\\mymod\\wrench.mdl"));
+		assert_eq!(content.missing_models.keys().collect::<Vec<_>>(), vec!("models\\mymod\\keycard.mdl"));
+		assert_eq!(content.used_materials.keys().sorted().collect::<Vec<_>>(), vec!("materials\\models\\items\\healthkit.vmt", "materials\\models\\mymod\\wrench.vmt"));
+
+		fs::remove_dir_all(&source_path).unwrap();
+		fs::remove_dir_all(fgd_path.parent().unwrap()).unwrap();
+
+	}
+
+}
@@ -0,0 +1,127 @@
+// Helpers of the content collector tests, which write the files of the source paths into temporary directories
+
+use std::{collections::HashMap, fs, path::{Path, PathBuf}};
+use crate::library;
+use crate::library::collector::SourceContentFile;
+use plumber_core::fs::OpenFileSystem;
+use super::{CollectContext, CollectOptions, SummaryCategory};
+
+// Creates an empty directory for a test, named after the test so parallel tests do not share files
+pub fn test_directory(name: &str) -> PathBuf {
+	let directory = std::env::temp_dir().join(format!("gcli_collector_test_{}_{}", name, std::process::id()));
+	let _ = fs::remove_dir_all(&directory);
+	fs::create_dir_all(&directory).unwrap();
+	return directory;
+}
+
+// Writes the files (local paths with forward slashes) into the directory
+pub fn write_files(directory: &Path, files: &[(&str, &str)]) {
+	for (local_path, content) in files {
+		let path = directory.join(local_path);
+		fs::create_dir_all(path.parent().unwrap()).unwrap();
+		fs::write(path, content).unwrap();
+	}
+}
+
+pub fn source_file(directory: &Path, local_path: &str) -> (String, SourceContentFile) {
+	let source_file = SourceContentFile { full_path: directory.join(local_path).to_string_lossy().to_string(), local_path: local_path.replace("/", "\\") };
+	return (local_path.replace("/", "\\").to_lowercase(), source_file);
+}
+
+// The options refer to arguments of the command and the global --format
+#[derive(clap::Parser)]
+struct TestCommand {
+	#[arg(short, long)]
+	output_path: Option<PathBuf>,
+	#[arg(long)]
+	input_list: Option<PathBuf>,
+	#[arg(long)]
+	format: Option<String>,
+	#[command(flatten)]
+	options: CollectOptions,
+}
+
+// Parses the options like they are passed on the command line, e.g. &["--include-nav"]
+pub fn test_options(args: &[&str]) -> CollectOptions {
+	return <TestCommand as clap::Parser>::parse_from(["gcli"].iter().chain(args)).options;
+}
+
+// Context of a collection without the game files
+pub fn test_context<'a>(source_files: &'a HashMap<String, SourceContentFile>, open_fs: &'a OpenFileSystem, options: &'a CollectOptions) -> CollectContext<'a> {
+	return CollectContext { source_files, game_fs: None, open_fs, model_companion_extensions: vec!("dx90.vtx", "phy", "vvd"), options };
+}
+
+// Writes maps/<name>.vmf into the directory, with a world brush whose sides use the materials and the entities with their keyvalues,
+// e.g. &[&[("classname", "prop_physics"), ("model", "models/mymap/crate.mdl")]]
+pub fn write_map(directory: &Path, name: &str, world_keyvalues: &[(&str, &str)], side_materials: &[&str], entities: &[&[(&str, &str)]]) -> PathBuf {
+
+	let keyvalues = |keyvalues: &[(&str, &str)]| -> String {
+		return keyvalues.iter().map(|(key, value)| format!("\t\"{}\" \"{}\"\n", key, value)).collect();
+	};
+
+	let sides: String = side_materials
+		.iter()
+		.enumerate()
+		.map(|(index, material)| format!(
+			"\t\tside\n\t\t{{\n\t\t\t\"id\" \"{}\"\n\t\t\t\"plane\" \"(0 0 0) (0 64 0) (64 64 0)\"\n\t\t\t\"material\" \"{}\"\n\t\t\t\"uaxis\" \"[1 0 0 0] 0.25\"\n\t\t\t\"vaxis\" \"[0 -1 0 0] 0.25\"\n\t\t\t\"rotation\" \"0\"\n\t\t\t\"lightmapscale\" \"16\"\n\t\t\t\"smoothing_groups\" \"0\"\n\t\t}}\n",
+			index + 1, material,
+		))
+		.collect();
+
+	let mut vmf = format!("versioninfo\n{{\n\t\"editorversion\" \"400\"\n}}\nworld\n{{\n\t\"id\" \"1\"\n\t\"classname\" \"worldspawn\"\n{}\tsolid\n\t{{\n\t\t\"id\" \"2\"\n{}\t}}\n}}\n", keyvalues(world_keyvalues), sides);
+	for (index, entity) in entities.iter().enumerate() {
+		let origin = match entity.iter().any(|(key, _)| *key == "origin") {
+			true => "",
+			false => "\t\"origin\" \"0 0 0\"\n",
+		};
+		vmf.push_str(&format!("entity\n{{\n\t\"id\" \"{}\"\n{}{}}}\n", index + 3, keyvalues(entity), origin));
+	}
+
+	let local_path = format!("maps/{}.vmf", name);
+	write_files(directory, &[(&local_path, &vmf)]);
+	return directory.join(local_path);
+
+}
+
+// (found, missing) of a category of the content summary
+pub fn summary_counts(summary_categories: &Vec<SummaryCategory>, name: &str) -> (usize, usize) {
+	let summary = summary_categories.iter().find(|category| category.name == name).unwrap();
+	return (summary.found, summary.missing);
+}
+
+// Writes a model with a single texture and cdmaterials path and without any meshes, along with the .vvd and .dx90.vtx it is read with
+pub fn write_minimal_model(directory: &Path, local_path: &str, cdmaterials: &str, texture_name: &str) {
+
+	// studiohdr_t is followed by the texture table, the cdmaterials table and the strings they reference
+	let texture_index = 408;
+	let cdmaterials_index = texture_index + 64;
+	let texture_name_index = cdmaterials_index + 4;
+	let cdmaterials_name_index = texture_name_index + texture_name.len() + 1;
+	let checksum = 1234;
+
+	let mut mdl = vec!(0u8; cdmaterials_name_index);
+	mdl.extend(cdmaterials.as_bytes());
+	mdl.push(0);
+	mdl[texture_name_index..texture_name_index + texture_name.len()].copy_from_slice(texture_name.as_bytes());
+	mdl[..4].copy_from_slice(library::mdl::MDL_IDENT);
+	let length = mdl.len();
+	for (offset, value) in [(4, 48), (8, checksum), (76, length), (204, 1), (208, texture_index), (212, 1), (216, cdmaterials_index), (texture_index, texture_name_index - texture_index), (cdmaterials_index, cdmaterials_name_index)] {
+		mdl[offset..offset + 4].copy_from_slice(&(value as i32).to_le_bytes());
+	}
+
+	let mut vvd = vec!(0u8; 64);
+	vvd[..4].copy_from_slice(b"IDSV");
+	vvd[4..8].copy_from_slice(&4i32.to_le_bytes());
+	vvd[8..12].copy_from_slice(&(checksum as i32).to_le_bytes());
+
+	let mut vtx = vec!(0u8; 36);
+	vtx[..4].copy_from_slice(&7i32.to_le_bytes());
+	vtx[16..20].copy_from_slice(&(checksum as i32).to_le_bytes());
+
+	let path = directory.join(local_path);
+	fs::create_dir_all(path.parent().unwrap()).unwrap();
+	fs::write(&path, mdl).unwrap();
+	fs::write(path.with_extension("vvd"), vvd).unwrap();
+	fs::write(path.with_extension("dx90.vtx"), vtx).unwrap();
+
+}
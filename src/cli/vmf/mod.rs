@@ -3,16 +3,31 @@ use clap::Subcommand;
 use crate::library;
 
 pub mod content_collector;
+pub mod stats;
 
 #[derive(Subcommand)]
 pub enum Actions {
+	// Later occurrences of an argument override earlier ones, e.g. of an option which is repeated by a script wrapping the command
+	#[command(args_override_self = true)]
 	CollectContent {
-		#[arg(value_parser = validate_vmf_path)]
-		vmf_path: PathBuf,
+		#[arg(value_parser = validate_vmf_path, required_unless_present_any = ["input_list", "print_schema"])]
+		vmf_path: Option<PathBuf>,
+		#[arg(long, value_parser = validate_input_list_path, conflicts_with_all = ["vmf_path", "output_gma"], help = "Path to a file which lists one vmf or `.mdl` path per line (relative to the file). The content of every listed map or model is collected into its own subdirectory of the output path. Models are collected like by `model collect-content`, with the options it shares with this command.")]
+		input_list: Option<PathBuf>,
+		#[arg(long, requires = "input_list", conflicts_with_all = ["clean", "list_stale"], help = "Collect the content of all maps of `--input-list` into the output path itself instead of a subdirectory per map.")]
+		shared_output: bool,
 		#[arg(short, long, help = "Path to a directory which contains content the map potentially uses. The directory should contain subdirectories like `materials/` and `models/`. This option can be used multiple times.")]
 		source_path: Vec<String>,
-		#[arg(short, long, value_parser = validate_output_path, help="Path to a directory where all of the content the map uses will be copied to.")]
-		output_path: PathBuf,
+		#[arg(short, long, value_parser = validate_output_path, required_unless_present_any = ["output_gma", "print_schema", "only_missing"], help="Path to a directory where all of the content the map uses will be copied to.")]
+		output_path: Option<PathBuf>,
+		#[command(flatten)]
+		options: content_collector::CollectOptions,
+	},
+	Stats {
+		#[arg(value_parser = validate_vmf_path)]
+		vmf_path: PathBuf,
+		#[arg(long, conflicts_with = "format", help = "Print the stats as a single JSON object. Can not be combined with `--format`, which wraps the stats into a record instead.")]
+		json: bool,
 	}
 }
 
@@ -20,6 +35,14 @@ fn validate_vmf_path(input: &str) -> Result<PathBuf, String> {
 	return library::validation::validate_input_file_exists(input, "vmf");
 }
 
+fn validate_input_list_path(input: &str) -> Result<PathBuf, String> {
+	let path = PathBuf::from(input);
+	if !path.is_file() {
+		return Err("Provided path is not a file".to_owned());
+	}
+	return Ok(path);
+}
+
 fn validate_output_path(input: &str) -> Result<PathBuf, String> {
-	return library::validation::validate_path_is_directory(input);
+	return library::validation::validate_output_path_template(input);
 }
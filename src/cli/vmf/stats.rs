@@ -0,0 +1,95 @@
+use std::{collections::HashSet, fs, path::PathBuf};
+use crate::library::log::{error, info};
+use plumber_core::uncased::UncasedStr;
+use crate::library;
+
+// World (worldspawn) properties which are reported as metadata
+const WORLD_METADATA_PROPERTIES: [&str; 4] = ["mapversion", "skyname", "detailmaterial", "maxpropscreenwidth"];
+
+pub fn output_vmf_stats(vmf: &PathBuf, json: bool) {
+
+	// Keep stdout free for the JSON object
+	if json {
+		library::log::set_log_to_stderr(true);
+	}
+
+	// Read vmf
+	let vmf_content = match fs::read(vmf) {
+		Ok(content) => content,
+		Err(err) => {
+			error!("Failed to read vmf file in \"{}\": {}", vmf.display(), err.to_string());
+			return;
+		}
+	};
+
+	// Parse vmf
+	let vmf_parsed = match plumber_core::vmf::from_bytes(&vmf_content) {
+		Ok(parsed) => parsed,
+		Err(err) => {
+			error!("Failed to parse vmf file in \"{}\": {}", vmf.display(), err.to_string());
+			return;
+		}
+	};
+
+	let world_solids = vmf_parsed.world.solids.len();
+	let world_sides: usize = vmf_parsed.world.solids.iter().map(|solid| solid.sides.len()).sum();
+
+	// Brush entities (e.g. func_detail) have their own solids
+	let entity_solids: usize = vmf_parsed.entities.iter().map(|ent| ent.solids.len()).sum();
+	let brush_entities = vmf_parsed.entities.iter().filter(|ent| ent.solids.len() > 0).count();
+
+	// env_sprite entities use their "model" property for a material, so they are not model props
+	let unique_models: HashSet<String> = vmf_parsed.entities
+		.iter()
+		.filter(|ent| ent.class_name != "env_sprite")
+		.filter_map(|ent| ent.properties.get(UncasedStr::new("model")))
+		.filter(|model| model.to_lowercase().ends_with(".mdl"))
+		.map(|model| model.replace("\\", "/").to_lowercase())
+		.collect();
+
+	let world_metadata: Vec<(&str, Option<&String>)> = WORLD_METADATA_PROPERTIES
+		.iter()
+		.map(|property| (*property, vmf_parsed.world.properties.get(UncasedStr::new(property)).filter(|value| !value.trim().is_empty())))
+		.collect();
+
+	// Lights use "-1 -1 -1 1" as their HDR color / brightness to fall back to the LDR values
+	let custom_hdr_lighting = vmf_parsed.entities
+		.iter()
+		.filter(|ent| ent.class_name.starts_with("light"))
+		.filter_map(|ent| ent.properties.get(UncasedStr::new("_lightHDR")))
+		.any(|light_hdr| !light_hdr.trim().starts_with("-1"));
+
+	if json || library::output::is_structured() {
+
+		let world_metadata_json = world_metadata
+			.iter()
+			.map(|(property, value)| format!(", \"{}\": {}", property, value.map(|value| library::json::string(value)).unwrap_or(String::from("null"))))
+			.collect::<String>();
+		let stats_json = format!(
+			"\"world_solids\": {}, \"world_sides\": {}, \"entities\": {}, \"brush_entities\": {}, \"entity_solids\": {}, \"unique_models\": {}{}, \"custom_hdr_lighting\": {}",
+			world_solids, world_sides, vmf_parsed.entities.len(), brush_entities, entity_solids, unique_models.len(), world_metadata_json, custom_hdr_lighting
+		);
+
+		// --json prints the plain stats object, --format wraps it into a record
+		match json {
+			true => library::output::print(&format!("{{{}}}", stats_json)),
+			false => library::output::record("stats", &format!("\"vmf\": {}, {}", library::json::string(&vmf.to_string_lossy()), stats_json)),
+		}
+
+		return;
+
+	}
+
+	info!("<magenta>VMF STATS:</> <green>{}</>", vmf.display());
+	info!("\t<magenta>↳</> World solids: <cyan>{}</> (<cyan>{}</> sides)", world_solids, world_sides);
+	info!("\t<magenta>↳</> Entities: <cyan>{}</>", vmf_parsed.entities.len());
+	info!("\t<magenta>↳</> Brush entities: <cyan>{}</> (<cyan>{}</> solids)", brush_entities, entity_solids);
+	info!("\t<magenta>↳</> Unique models: <cyan>{}</>", unique_models.len());
+
+	for (property, value) in &world_metadata {
+		info!("\t<magenta>↳</> World {}: <cyan>{}</>", property, value.map(|value| value.as_str()).unwrap_or("-"));
+	}
+
+	info!("\t<magenta>↳</> Custom HDR light values: <cyan>{}</>", if custom_hdr_lighting { "Yes" } else { "No" });
+
+}
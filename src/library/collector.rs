@@ -0,0 +1,547 @@
+use std::{collections::{HashMap, HashSet}, fs, path::{Path, PathBuf}};
+use plumber_core::{fs::{FileSystem, OpenFileSystem}, steam::App};
+use simple_error::{bail, SimpleError};
+use crate::library;
+use crate::library::log::{error, info, success, warn};
+use crate::library::validation::{validate_directory_is_readable, validate_directory_is_writable, validate_path_is_directory};
+
+// Parts of the content collection which are shared by `vmf collect-content` and `model collect-content`
+
+pub const GMOD_APP_ID: u32 = 4_000;
+
+#[derive(Debug, Clone)]
+pub struct SourceContentFile {
+	pub full_path: String,
+	pub local_path: String,
+}
+
+#[derive(Debug)]
+pub struct SourceMaterialData {
+	pub used_materials: HashMap<String, SourceContentFile>,
+	pub missing_materials: HashMap<String, String>,
+	pub used_textures: HashMap<String, SourceContentFile>,
+	pub missing_textures: HashMap<String, String>,
+	// Missing textures which are used as normal maps, see NORMAL_MAP_PARAMETERS
+	pub missing_normal_maps: HashSet<String>,
+}
+
+impl SourceMaterialData {
+	pub fn new() -> Self {
+		Self {
+			used_materials: HashMap::new(),
+			missing_materials: HashMap::new(),
+			used_textures: HashMap::new(),
+			missing_textures: HashMap::new(),
+			missing_normal_maps: HashSet::new(),
+		}
+	}
+	pub fn extend(&mut self, other: Self) {
+		self.used_materials.extend(other.used_materials);
+		self.missing_materials.extend(other.missing_materials);
+		self.used_textures.extend(other.used_textures);
+		self.missing_textures.extend(other.missing_textures);
+		self.missing_normal_maps.extend(other.missing_normal_maps);
+	}
+}
+
+//
+// Paths
+//
+
+// Expands the placeholders of the output path (see library::validation::expand_output_path) and creates the resulting directory
+pub fn resolve_output_path(output_path: &PathBuf, input: &Path) -> Option<PathBuf> {
+
+	if !library::validation::has_output_path_placeholders(output_path) {
+		return Some(output_path.to_owned());
+	}
+
+	let expanded_output_path = library::validation::expand_output_path(output_path, input);
+
+	if let Err(err) = fs::create_dir_all(&expanded_output_path) {
+		error!("Failed to create output directory \"{}\": {}", expanded_output_path.display(), err.to_string());
+		return None;
+	}
+
+	info!("Using output path \"<cyan>{}</>\"", expanded_output_path.display());
+
+	return Some(expanded_output_path);
+
+}
+
+// Resolves the output path and makes sure it can be written to before the scan, instead of failing to copy every single file after it
+pub fn prepare_output_path(output_path: &PathBuf, input: &Path) -> Option<PathBuf> {
+
+	let output_path = resolve_output_path(output_path, input)?;
+
+	if let Err(err) = validate_directory_is_writable(&output_path) {
+		error!("Can not write to output path \"{}\": {}", output_path.display(), err);
+		return None;
+	}
+
+	return Some(output_path);
+
+}
+
+// Returns the source paths which can be read, unreadable ones are skipped with a warning
+pub fn validate_source_paths(source_path_strings: Vec<String>) -> Option<Vec<PathBuf>> {
+
+	let source_path_strings_len = source_path_strings.len();
+	let mut source_paths: Vec<PathBuf> = vec!();
+	for source_path_string in source_path_strings {
+		match validate_path_is_directory(&source_path_string).and_then(|path| validate_directory_is_readable(&path).map(|_| path)) {
+			Ok(path) => source_paths.push(path),
+			Err(err) => warn!("Skipping provided source path \"{}\": {}", source_path_string, err)
+		}
+	}
+
+	if source_path_strings_len > 0 && source_paths.len() == 0 {
+		error!("None of the provided source paths can be read");
+		return None;
+	} else if source_paths.len() == 0 {
+		warn!("No source paths were provided");
+	}
+
+	return Some(source_paths);
+
+}
+
+//
+// Input lists
+//
+
+// Reads the inputs listed in a newline-delimited file, relative to the file. Empty lines and comments are ignored,
+// paths which do not exist or do not have one of the extensions are skipped with a warning.
+pub fn read_input_list(input_list: &Path, extensions: &[&str]) -> Option<Vec<PathBuf>> {
+
+	let input_list_content = match fs::read_to_string(input_list) {
+		Ok(content) => content,
+		Err(err) => {
+			error!("Failed to read input list \"{}\": {}", input_list.display(), err.to_string());
+			return None;
+		}
+	};
+
+	// Relative paths are relative to the input list
+	let input_list_dir_path = input_list.parent().unwrap_or(Path::new("."));
+
+	let mut inputs: Vec<PathBuf> = vec!();
+	for (line_index, line) in input_list_content.lines().enumerate() {
+
+		let line = line.trim();
+		if line.is_empty() || line.starts_with('#') || line.starts_with("//") {
+			continue;
+		}
+
+		let path = input_list_dir_path.join(line);
+		let extension = path.extension().map(|extension| extension.to_string_lossy().to_string()).unwrap_or_default();
+
+		if !extensions.iter().any(|expected_extension| expected_extension.eq_ignore_ascii_case(&extension)) {
+			warn!("Skipping \"{}\" (line {} of input list): Expected a {} file", line, line_index + 1, extensions.join(" / "));
+			continue;
+		}
+
+		match library::validation::validate_input_file_exists(&path.to_string_lossy(), &extension) {
+			Ok(path) => inputs.push(path),
+			Err(err) => warn!("Skipping \"{}\" (line {} of input list): {}", line, line_index + 1, err),
+		}
+
+	}
+
+	if inputs.len() == 0 {
+		error!("Input list \"{}\" does not contain any valid {} paths", input_list.display(), extensions.join(" / "));
+		return None;
+	}
+
+	return Some(inputs);
+
+}
+
+// Collects every input of an input list with collect_input, which returns the number of missing files or None if the collection failed, and prints an overall summary.
+// Every input is collected into its own subdirectory of the output path named after the input, unless shared_output is set or the output path has placeholders.
+// Returns whether all inputs are complete, or None if any input failed to be collected.
+pub fn collect_input_list(inputs: &Vec<PathBuf>, output_path: &Option<PathBuf>, shared_output: bool, mut collect_input: impl FnMut(&PathBuf, &Option<PathBuf>) -> Option<usize>) -> Option<bool> {
+
+	let mut results: Vec<(&PathBuf, Option<usize>)> = vec!();
+	for (index, input) in inputs.iter().enumerate() {
+
+		info!("");
+		info!("<magenta>[{}/{}]</> <cyan>{}</>", index + 1, inputs.len(), input.display());
+
+		// Output paths with placeholders (e.g. "{name}") are already specific to every input
+		let input_output_path = match (output_path, shared_output) {
+			(Some(output_path), false) if !library::validation::has_output_path_placeholders(output_path) => {
+
+				let input_name = input.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or(String::from("input"));
+				let input_output_path = output_path.join(input_name);
+
+				if let Err(err) = fs::create_dir_all(&input_output_path) {
+					error!("Failed to create output directory \"{}\": {}", input_output_path.display(), err.to_string());
+					results.push((input, None));
+					continue;
+				}
+
+				Some(input_output_path)
+
+			},
+			_ => output_path.to_owned(),
+		};
+
+		results.push((input, collect_input(input, &input_output_path)));
+
+	}
+
+	//
+	// Overall summary across all inputs
+	//
+	info!("");
+	info!("<magenta>OVERALL SUMMARY:</>");
+
+	let mut incomplete_count = 0;
+	let mut failed_count = 0;
+	for (input, missing) in &results {
+		match missing {
+			Some(0) => info!("\t<magenta>↳</> {}: <green>Complete</>", input.display()),
+			Some(missing) => {
+				incomplete_count += 1;
+				info!("\t<magenta>↳</> {}: Missing <red>{}</>", input.display(), missing);
+			},
+			None => {
+				failed_count += 1;
+				info!("\t<magenta>↳</> {}: <red>Failed</>", input.display());
+			}
+		}
+	}
+
+	if incomplete_count > 0 || failed_count > 0 {
+		warn!("<red>{}</> of <cyan>{}</> inputs are missing content and <red>{}</> failed to be collected", incomplete_count, results.len(), failed_count);
+	} else {
+		success!("<green>All {} inputs are complete!</>", results.len());
+	}
+
+	return match failed_count {
+		0 => Some(incomplete_count == 0),
+		_ => None,
+	};
+
+}
+
+//
+// Game
+//
+
+// Returns the install directory of Garry's Mod, or None if the game check is skipped. Fails if the game is required but can not be found.
+pub fn locate_game(skip_game_check: bool, game_dir_override: Option<&PathBuf>) -> Result<Option<PathBuf>, SimpleError> {
+
+	if skip_game_check {
+		warn!("<red>Skipping the game check (<cyan>--skip-game-check</>): Content which is part of the game can not be distinguished and is reported as missing!</>");
+		return Ok(None);
+	}
+
+	// The game files are required to tell which content is part of the game, so the collection can not continue without them
+	return match locate_game_dir(game_dir_override) {
+		Some(game_dir) => Ok(Some(game_dir)),
+		None => {
+			info!("Make sure that Steam and Garry's Mod are installed, or provide the path to the Garry's Mod install directory via <cyan>--game-dir</>,");
+			info!("or use <cyan>--skip-game-check</> to report all content which is not in the source paths as missing.");
+			info!("e.g. <cyan>--game-dir \"C:\\Program Files (x86)\\Steam\\steamapps\\common\\GarrysMod\"</>");
+			bail!("The game files are required to find out which content is already part of the game")
+		},
+	};
+
+}
+
+// Opens the file system of the game, or an empty one if the game check is skipped
+pub fn open_file_system(game_dir: Option<&PathBuf>) -> Option<OpenFileSystem> {
+	return match game_dir {
+		Some(game_dir) => open_game_file_system(game_dir),
+		None => open_empty_file_system(),
+	};
+}
+
+// Returns the install directory of Garry's Mod, either the provided one (--game-dir) or the one found via Steam
+pub fn locate_game_dir(game_dir_override: Option<&PathBuf>) -> Option<PathBuf> {
+
+	if let Some(game_dir) = game_dir_override {
+
+		// The install directory contains the garrysmod/ game directory with the gameinfo.txt
+		if !game_dir.join("garrysmod").join("gameinfo.txt").is_file() {
+			error!("\"{}\" is not a Garry's Mod install directory (missing <cyan>garrysmod/gameinfo.txt</>)", game_dir.display());
+			return None;
+		}
+
+		info!("Using <cyan>Garry's Mod</> install in \"<green>{}</>\"", game_dir.display());
+
+		return Some(game_dir.to_owned());
+
+	}
+
+	let mut steam_dir = match steamlocate::SteamDir::locate() {
+		Some(dir) => dir,
+		None => {
+			error!("Failed to locate Steam installation");
+			return None;
+		}
+	};
+
+	let game_dir = match steam_dir.app(&GMOD_APP_ID) {
+		Some(app) => app.path.to_owned(),
+		None => {
+			error!("Failed to locate Garry's Mod installation in any Steam library");
+			return None;
+		}
+	};
+
+	info!("Found <cyan>Garry's Mod</> install in \"<green>{}</>\"", game_dir.display());
+
+	return Some(game_dir);
+
+}
+
+// Models and materials are read through a file system, which has no search paths if the game check is skipped
+pub fn open_empty_file_system() -> Option<OpenFileSystem> {
+	return match (FileSystem { name: String::from("Garry's Mod (skipped)"), search_paths: vec!() }).open() {
+		Ok(fs) => Some(fs),
+		Err(err) => {
+			error!("Failed to open empty file system: {}", err.to_string());
+			None
+		}
+	};
+}
+
+pub fn open_game_file_system(game_dir: &PathBuf) -> Option<OpenFileSystem> {
+
+	let game_app = App { app_id: GMOD_APP_ID, name: String::from("Garry's Mod"), install_dir: game_dir.to_owned() };
+	let game_fs = match FileSystem::from_app(&game_app) {
+		Ok(fs) => fs,
+		Err(err) => {
+			error!("Failed to create game file system: {}", err.to_string());
+			return None;
+		}
+	};
+
+	return match game_fs.open() {
+		Ok(fs) => Some(fs),
+		Err(err) => {
+			error!("Failed to open game file system: {}", err.to_string());
+			None
+		}
+	};
+
+}
+
+//
+// Materials
+//
+
+// Reads all materials (.vmt) of used_materials with read_material_data and returns their combined data. Materials found while reading
+// (e.g. "$bottommaterial" or materials of proxies) are added to used_materials and read in the next pass, until no new materials are found.
+// Materials which fail to be read are skipped with a warning, unless strict is set (see --strict), which returns the first error instead.
+pub fn collect_materials_data(used_materials: &mut HashMap<String, SourceContentFile>, strict: bool, read_material_data: impl Fn(&SourceContentFile) -> Result<SourceMaterialData, SimpleError>)
+	-> Result<SourceMaterialData, SimpleError>
+{
+
+	let mut used_materials_data = SourceMaterialData::new();
+	let mut read_materials: HashSet<String> = HashSet::new();
+
+	loop {
+
+		// Images used as materials directly (.png / .jpg) do not reference any textures
+		let unread_materials: Vec<(String, SourceContentFile)> = used_materials
+			.iter()
+			.filter(|(source_file_path, _)| source_file_path.ends_with(".vmt") && !read_materials.contains(*source_file_path))
+			.map(|(source_file_path, source_file)| (source_file_path.to_owned(), source_file.to_owned()))
+			.collect();
+
+		if unread_materials.is_empty() {
+			break;
+		}
+
+		for (source_file_path, source_file) in unread_materials {
+
+			read_materials.insert(source_file_path);
+
+			match read_material_data(&source_file) {
+				Ok(data) => used_materials_data.extend(data),
+				Err(err) => {
+					if strict {
+						bail!("Failed to read material data of \"{}\": {}", source_file.full_path, err.to_string());
+					}
+					warn!("Failed to read material data of \"{}\": {}", source_file.full_path, err.to_string());
+				},
+			}
+
+		}
+
+		used_materials.extend(used_materials_data.used_materials.drain());
+
+	}
+
+	return Ok(used_materials_data);
+
+}
+
+// Logs why the collection is aborted after a material failed to be read with --strict, the caller returns and releases the lock of the output path
+pub fn log_strict_abort(err: &SimpleError) {
+	error!("{}", err.to_string());
+	error!("Aborting collection since <cyan>--strict</> is enabled");
+}
+
+// Logs the references which are not portable (see --strict-paths), as (local path, context, reference, problem)
+pub fn log_non_portable_references(references: &Vec<(String, String, String, &str)>, sources: &str) {
+
+	if references.len() == 0 {
+		success!("<green>All references of {} are portable!</>", sources);
+		return;
+	}
+
+	warn!("<red>{}</> references of {} are not portable and can break on other machines, even if they resolve locally:", references.len(), sources);
+	for (local_path, context, reference, problem) in references {
+		warn!("\t<red>-</> {}: {} \"{}\" ({})", local_path, context, reference, problem);
+	}
+
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+
+	fn test_directory(name: &str) -> PathBuf {
+		let directory = std::env::temp_dir().join(format!("gcli_shared_collector_test_{}_{}", name, std::process::id()));
+		let _ = fs::remove_dir_all(&directory);
+		fs::create_dir_all(&directory).unwrap();
+		return directory;
+	}
+
+	fn material(local_path: &str) -> (String, SourceContentFile) {
+		return (local_path.to_lowercase(), SourceContentFile { full_path: format!("/content/{}", local_path), local_path: local_path.to_owned() });
+	}
+
+	#[test]
+	fn output_path_placeholders_are_created() {
+
+		let directory = test_directory("output");
+
+		let output_path = prepare_output_path(&directory.join("{name}_content"), Path::new("maps/rp_town.vmf")).unwrap();
+		assert_eq!(output_path, directory.join("rp_town_content"));
+		assert!(output_path.is_dir());
+
+		// Without placeholders, the output path is used as it is
+		assert_eq!(prepare_output_path(&directory, Path::new("maps/rp_town.vmf")), Some(directory.to_owned()));
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn unreadable_source_paths_are_skipped() {
+
+		let directory = test_directory("sources");
+		let missing = directory.join("missing");
+
+		assert_eq!(validate_source_paths(vec!(directory.to_string_lossy().to_string(), missing.to_string_lossy().to_string())), Some(vec!(directory.to_owned())));
+		assert_eq!(validate_source_paths(vec!(missing.to_string_lossy().to_string())), None);
+		assert_eq!(validate_source_paths(vec!()), Some(vec!()));
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn input_list_keeps_existing_inputs_with_the_extensions() {
+
+		let directory = test_directory("input_list");
+		fs::write(directory.join("rp_town.vmf"), "").unwrap();
+		fs::write(directory.join("crate.MDL"), "").unwrap();
+		fs::write(directory.join("notes.txt"), "").unwrap();
+		fs::write(directory.join("maps.txt"), "# Map pool\nrp_town.vmf\n\ncrate.MDL\nnotes.txt\nmissing.vmf\n").unwrap();
+
+		assert_eq!(read_input_list(&directory.join("maps.txt"), &["vmf", "mdl"]), Some(vec!(directory.join("rp_town.vmf"), directory.join("crate.MDL"))));
+		assert_eq!(read_input_list(&directory.join("maps.txt"), &["mdl"]), Some(vec!(directory.join("crate.MDL"))));
+		assert_eq!(read_input_list(&directory.join("maps.txt"), &["bsp"]), None);
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn input_list_collects_every_input_into_its_own_subdirectory() {
+
+		let directory = test_directory("input_list_output");
+		let inputs = vec!(PathBuf::from("maps/rp_town.vmf"), PathBuf::from("models/crate.mdl"));
+
+		let mut output_paths: Vec<Option<PathBuf>> = vec!();
+		let complete = collect_input_list(&inputs, &Some(directory.to_owned()), false, |_, output_path| {
+			output_paths.push(output_path.to_owned());
+			return Some(0);
+		});
+
+		assert_eq!(complete, Some(true));
+		assert_eq!(output_paths, vec!(Some(directory.join("rp_town")), Some(directory.join("crate"))));
+		assert!(directory.join("rp_town").is_dir());
+
+		// Incomplete inputs are still collected, a failed input fails the whole list
+		assert_eq!(collect_input_list(&inputs, &Some(directory.to_owned()), true, |input, output_path| {
+			assert_eq!(output_path, &Some(directory.to_owned()));
+			return match input.ends_with("rp_town.vmf") { true => Some(3), false => Some(0) };
+		}), Some(false));
+		assert_eq!(collect_input_list(&inputs, &None, false, |input, _| if input.ends_with("crate.mdl") { None } else { Some(0) }), None);
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn missing_game_is_an_error_unless_skipped() {
+
+		let directory = test_directory("game");
+
+		// The directory does not contain garrysmod/gameinfo.txt
+		assert_eq!(locate_game(false, Some(&directory)).err().unwrap().to_string(), "The game files are required to find out which content is already part of the game");
+		assert_eq!(locate_game(true, Some(&directory)), Ok(None));
+
+		fs::create_dir_all(directory.join("garrysmod")).unwrap();
+		fs::write(directory.join("garrysmod").join("gameinfo.txt"), "").unwrap();
+		assert_eq!(locate_game(false, Some(&directory)), Ok(Some(directory.to_owned())));
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+	#[test]
+	fn materials_found_while_reading_are_read_as_well() {
+
+		let mut used_materials: HashMap<String, SourceContentFile> = HashMap::from([material("materials\\water.vmt"), material("materials\\logo.png")]);
+
+		let read_paths = std::cell::RefCell::new(vec!());
+		let data = collect_materials_data(&mut used_materials, false, |source_file| {
+			read_paths.borrow_mut().push(source_file.local_path.to_owned());
+			let mut data = SourceMaterialData::new();
+			if source_file.local_path == "materials\\water.vmt" {
+				data.used_materials.extend([material("materials\\water_bottom.vmt")]);
+				data.used_textures.extend([material("materials\\water_normal.vtf")]);
+			}
+			return Ok(data);
+		}).unwrap();
+
+		// Images do not reference anything, so only the materials are read
+		assert_eq!(read_paths.into_inner(), vec!("materials\\water.vmt", "materials\\water_bottom.vmt"));
+		assert!(used_materials.contains_key("materials\\water_bottom.vmt"));
+		assert!(data.used_textures.contains_key("materials\\water_normal.vtf"));
+
+	}
+
+	#[test]
+	fn strict_returns_the_first_material_error() {
+
+		let mut used_materials: HashMap<String, SourceContentFile> = HashMap::from([material("materials\\broken.vmt")]);
+		let read_material_data = |_: &SourceContentFile| -> Result<SourceMaterialData, SimpleError> { bail!("Unexpected end of file") };
+
+		assert!(collect_materials_data(&mut used_materials, false, read_material_data).is_ok());
+
+		let err = collect_materials_data(&mut used_materials, true, read_material_data).err().unwrap();
+		assert_eq!(err.to_string(), "Failed to read material data of \"/content/materials\\broken.vmt\": Unexpected end of file");
+
+	}
+
+}
@@ -0,0 +1,131 @@
+use std::{collections::HashSet, fs, path::{Path, PathBuf}};
+use regex::Regex;
+use simple_error::{bail, SimpleError};
+
+// Minimal parser for Forge Game Data (.fgd) files, which only reads the classes and the types of their properties
+#[derive(Debug, Clone)]
+pub struct FgdClass {
+	pub name: String,
+	pub bases: Vec<String>,
+	// (name, type), e.g. ("model", "studio")
+	pub properties: Vec<(String, String)>,
+	// Model shown by the editor via studio("..."), which is the world model of entities like item_healthkit
+	pub studio: Option<String>,
+}
+
+// Reads an fgd including all fgds it includes via @include (relative to the including fgd)
+pub fn read(fgd_path: &Path, visited: &mut HashSet<PathBuf>) -> Result<Vec<FgdClass>, SimpleError> {
+
+	if !visited.insert(fs::canonicalize(fgd_path).unwrap_or(fgd_path.to_owned())) {
+		return Ok(vec!());
+	}
+
+	let content = match fs::read(fgd_path) {
+		Ok(content) => String::from_utf8_lossy(&content).to_string(),
+		Err(err) => bail!("Failed to read fgd \"{}\": {}", fgd_path.display(), err.to_string()),
+	};
+
+	let content = strip_comments(&content);
+	let mut classes: Vec<FgdClass> = vec!();
+
+	let include_regex = Regex::new(r#"(?i)@include\s+"([^"]+)""#).unwrap();
+	for captures in include_regex.captures_iter(&content) {
+		let include_path = fgd_path.parent().unwrap_or(Path::new(".")).join(&captures[1]);
+		classes.extend(read(&include_path, visited)?);
+	}
+
+	classes.extend(parse(&content));
+
+	return Ok(classes);
+
+}
+
+pub fn parse(content: &str) -> Vec<FgdClass> {
+
+	let content = strip_comments(content);
+
+	let class_regex = Regex::new(r"(?i)@\w+Class\b([^=\[]*)=\s*([\w\-]+)").unwrap();
+	let base_regex = Regex::new(r"(?i)\bbase\s*\(([^)]*)\)").unwrap();
+	let studio_regex = Regex::new(r#"(?i)\bstudio\s*\(\s*"([^"]+)"\s*\)"#).unwrap();
+	let property_regex = Regex::new(r"(?m)^\s*(\w+)\s*\(\s*(\w+)\s*\)").unwrap();
+
+	let mut classes: Vec<FgdClass> = vec!();
+
+	for captures in class_regex.captures_iter(&content) {
+
+		let bases: Vec<String> = match base_regex.captures(&captures[1]) {
+			Some(base_captures) => base_captures[1]
+				.split(',')
+				.map(|base| base.trim().to_lowercase())
+				.filter(|base| !base.is_empty())
+				.collect(),
+			None => vec!(),
+		};
+
+		let studio = studio_regex.captures(&captures[1]).map(|studio_captures| studio_captures[1].trim().to_lowercase());
+
+		// The class body follows the header in brackets
+		let header_end = captures.get(0).map(|header| header.end()).unwrap_or(0);
+		let body = match find_body(&content[header_end..]) {
+			Some(body) => body,
+			None => continue,
+		};
+
+		let properties: Vec<(String, String)> = property_regex
+			.captures_iter(body)
+			.map(|property_captures| (property_captures[1].to_lowercase(), property_captures[2].to_lowercase()))
+			.collect();
+
+		classes.push(FgdClass { name: captures[2].to_lowercase(), bases, properties, studio });
+
+	}
+
+	return classes;
+
+}
+
+// Returns the content between the first "[" and its matching "]", ignoring brackets inside of strings
+fn find_body(content: &str) -> Option<&str> {
+
+	let start = content.find('[')?;
+	let mut depth = 0;
+	let mut in_string = false;
+
+	for (index, char) in content[start..].char_indices() {
+		match char {
+			'"' => in_string = !in_string,
+			'[' if !in_string => depth += 1,
+			']' if !in_string => {
+				depth -= 1;
+				if depth == 0 {
+					return Some(&content[start + 1..start + index]);
+				}
+			},
+			_ => {},
+		}
+	}
+
+	return None;
+
+}
+
+fn strip_comments(content: &str) -> String {
+	return content
+		.lines()
+		.map(|line| {
+			// "//" inside of strings (e.g. URLs in descriptions) is not a comment
+			let mut in_string = false;
+			let mut previous = ' ';
+			for (index, char) in line.char_indices() {
+				if char == '"' {
+					in_string = !in_string;
+				} else if char == '/' && previous == '/' && !in_string {
+					return &line[..index - 1];
+				}
+				previous = char;
+			}
+			return line;
+		})
+		.collect::<Vec<&str>>()
+		.join("\n");
+}
@@ -0,0 +1,635 @@
+use std::{fs::File, io::{self, BufWriter, Seek, SeekFrom, Write}, path::{Path, PathBuf}, time::{SystemTime, UNIX_EPOCH}};
+use regex::Regex;
+use crate::library;
+use simple_error::{bail, SimpleError};
+
+pub const GMA_IDENT: &[u8; 4] = b"GMAD";
+pub const GMA_VERSION: u8 = 3;
+
+// Author gmad writes into every gma, used if no author is provided
+pub const GMA_DEFAULT_AUTHOR: &str = "Author Name";
+
+// Files gmad packs into a gma, it refuses to create a gma containing any other file
+pub const GMA_WHITELIST: [&str; 43] = [
+	"lua/*.lua",
+	"scenes/*.vcd",
+	"particles/*.pcf",
+	"resource/fonts/*.ttf",
+	"scripts/vehicles/*.txt",
+	"resource/localization/*/*.properties",
+	"maps/*.bsp",
+	"maps/*.lmp",
+	"maps/*.nav",
+	"maps/*.ain",
+	"maps/thumb/*.png",
+	"sound/*.wav",
+	"sound/*.mp3",
+	"sound/*.ogg",
+	"materials/*.vmt",
+	"materials/*.vtf",
+	"materials/*.png",
+	"materials/*.jpg",
+	"materials/*.jpeg",
+	"materials/colorcorrection/*.raw",
+	"models/*.mdl",
+	"models/*.vtx",
+	"models/*.phy",
+	"models/*.ani",
+	"models/*.vvd",
+	"gamemodes/*/*.txt",
+	"gamemodes/*/*.fgd",
+	"gamemodes/*/logo.png",
+	"gamemodes/*/icon24.png",
+	"gamemodes/*/gamemode/*.lua",
+	"gamemodes/*/entities/effects/*.lua",
+	"gamemodes/*/entities/weapons/*.lua",
+	"gamemodes/*/entities/entities/*.lua",
+	"gamemodes/*/backgrounds/*.png",
+	"gamemodes/*/backgrounds/*.jpg",
+	"gamemodes/*/backgrounds/*.jpeg",
+	"gamemodes/*/content/models/*.mdl",
+	"gamemodes/*/content/materials/*.vmt",
+	"gamemodes/*/content/materials/*.vtf",
+	"gamemodes/*/content/sound/*.wav",
+	"gamemodes/*/content/sound/*.mp3",
+	"gamemodes/*/content/particles/*.pcf",
+	"gamemodes/*/content/maps/*.bsp",
+];
+
+#[derive(Debug, Clone)]
+pub struct GmaEntry {
+	pub path: String,
+	pub size: u64,
+	pub crc: u32,
+	pub offset: usize,
+}
+
+#[derive(Debug)]
+pub struct Gma {
+	pub version: u8,
+	pub title: String,
+	pub description: String,
+	pub author: String,
+	pub entries: Vec<GmaEntry>,
+}
+
+impl Gma {
+
+	// The description is the JSON object gmad builds from addon.json
+	pub fn description_field(&self, field: &str) -> Option<String> {
+		let regex = Regex::new(&format!(r#""{}"\s*:\s*"((?:[^"\\]|\\.)*)""#, field)).unwrap();
+		return regex.captures(&self.description).map(|captures| captures[1].to_string());
+	}
+
+	pub fn tags(&self) -> Vec<String> {
+		let regex = Regex::new(r#""tags"\s*:\s*\[([^\]]*)\]"#).unwrap();
+		return match regex.captures(&self.description) {
+			Some(captures) => captures[1]
+				.split(',')
+				.map(|tag| tag.trim().trim_matches('"').to_string())
+				.filter(|tag| !tag.is_empty())
+				.collect(),
+			None => vec!(),
+		};
+	}
+
+}
+
+struct GmaReader<'a> {
+	bytes: &'a [u8],
+	position: usize,
+}
+
+impl<'a> GmaReader<'a> {
+
+	fn take(&mut self, length: usize) -> Result<&'a [u8], SimpleError> {
+		let end = match self.position.checked_add(length) {
+			Some(end) if end <= self.bytes.len() => end,
+			_ => bail!("Unexpected end of file at offset {}", self.position),
+		};
+		let slice = &self.bytes[self.position..end];
+		self.position = end;
+		return Ok(slice);
+	}
+
+	fn read_u8(&mut self) -> Result<u8, SimpleError> {
+		return Ok(self.take(1)?[0]);
+	}
+
+	fn read_u32(&mut self) -> Result<u32, SimpleError> {
+		return Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()));
+	}
+
+	fn read_i32(&mut self) -> Result<i32, SimpleError> {
+		return Ok(i32::from_le_bytes(self.take(4)?.try_into().unwrap()));
+	}
+
+	fn read_u64(&mut self) -> Result<u64, SimpleError> {
+		return Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()));
+	}
+
+	fn read_string(&mut self) -> Result<String, SimpleError> {
+		let remaining = &self.bytes[self.position..];
+		let length = match remaining.iter().position(|&byte| byte == 0) {
+			Some(length) => length,
+			None => bail!("Unterminated string at offset {}", self.position),
+		};
+		let string = String::from_utf8_lossy(&remaining[..length]).to_string();
+		self.position += length + 1;
+		return Ok(string);
+	}
+
+}
+
+pub fn read(bytes: &[u8]) -> Result<Gma, SimpleError> {
+
+	let mut reader = GmaReader { bytes, position: 0 };
+
+	// Header
+	if reader.take(4)? != GMA_IDENT {
+		bail!("File is not a gma (missing \"GMAD\" header)");
+	}
+
+	let version = reader.read_u8()?;
+	if version > GMA_VERSION {
+		bail!("Unsupported gma version {} (latest supported version is {})", version, GMA_VERSION);
+	}
+
+	// Steam ID and timestamp
+	reader.take(16)?;
+
+	// Required content (unused by the game, but still part of the format)
+	if version > 1 {
+		while !reader.read_string()?.is_empty() {}
+	}
+
+	let title = reader.read_string()?;
+	let description = reader.read_string()?;
+	let author = reader.read_string()?;
+	let _addon_version = reader.read_i32()?;
+
+	// File table
+	let mut entries: Vec<GmaEntry> = vec!();
+	let mut offset: usize = 0;
+	loop {
+
+		let file_number = reader.read_u32()?;
+		if file_number == 0 {
+			break;
+		}
+
+		let path = reader.read_string()?;
+		let size = reader.read_u64()?;
+		let crc = reader.read_u32()?;
+
+		// Sizes are read from the file, so they can be large enough to overflow the offsets of corrupted files
+		let next_offset = match usize::try_from(size).ok().and_then(|size| offset.checked_add(size)) {
+			Some(next_offset) => next_offset,
+			None => bail!("Invalid size {} of file \"{}\"", size, path),
+		};
+
+		entries.push(GmaEntry { path, size, crc, offset });
+		offset = next_offset;
+
+	}
+
+	// Entry offsets are relative to the start of the file contents, make them absolute.
+	// Every entry has to be inside of the file, so entry_data() can not read past its end.
+	let available = bytes.len() - reader.position;
+	for entry in &mut entries {
+		let entry_end = match entry.offset.checked_add(entry.size as usize) {
+			Some(entry_end) => entry_end,
+			None => bail!("Invalid size {} of file \"{}\"", entry.size, entry.path),
+		};
+		if entry_end > available {
+			bail!("Contents of file \"{}\" are truncated, it ends at offset {} of the file data but only {} bytes of file data are present", entry.path, entry_end, available);
+		}
+		entry.offset += reader.position;
+	}
+
+	return Ok(Gma { version, title, description, author, entries });
+
+}
+
+pub fn entry_data<'a>(bytes: &'a [u8], entry: &GmaEntry) -> &'a [u8] {
+	return &bytes[entry.offset..entry.offset + entry.size as usize];
+}
+
+struct CrcWriter<W: Write> {
+	inner: W,
+	crc: u32,
+	written: u64,
+}
+
+impl<W: Write> Write for CrcWriter<W> {
+
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		let written = self.inner.write(buf)?;
+		self.crc = crc32_update(self.crc, &buf[..written]);
+		self.written += written as u64;
+		return Ok(written);
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		return self.inner.flush();
+	}
+
+}
+
+// Builds the description gmad embeds, which is a JSON object containing the description, type and tags of addon.json
+pub fn description_json(description: &str, addon_type: &str, tags: &[&str]) -> String {
+
+	let escape = |value: &str| value.replace("\\", "\\\\").replace("\"", "\\\"");
+
+	return format!(
+		"{{\n\t\"description\": \"{}\",\n\t\"type\": \"{}\",\n\t\"tags\": [{}]\n}}",
+		escape(description),
+		escape(addon_type),
+		tags.iter().map(|tag| format!("\"{}\"", escape(tag))).collect::<Vec<String>>().join(", "),
+	);
+
+}
+
+// Writes a gma containing the provided files, given as (path inside the gma, path on disk), and returns its size in bytes
+pub fn write(gma_path: &Path, title: &str, description_json: &str, author: &str, files: &Vec<(String, PathBuf)>) -> Result<u64, SimpleError> {
+
+	let gma_file = match File::create(gma_path) {
+		Ok(file) => file,
+		Err(err) => bail!("Failed to create \"{}\": {}", gma_path.display(), err.to_string()),
+	};
+
+	let mut writer = BufWriter::new(gma_file);
+	let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0);
+
+	let write_result: io::Result<u64> = (|| {
+
+		// The file table precedes the file contents, but the sizes and checksums of the files are only known once they are read.
+		// So the head is written with empty entries first and overwritten once all files are written, which reads every file only once.
+		let mut file_table: Vec<(&String, u64, u32)> = files.iter().map(|(path, _)| (path, 0, 0)).collect();
+		let head_size = gma_head(title, description_json, author, timestamp, &file_table).len() as u64;
+		writer.seek(SeekFrom::Start(head_size))?;
+
+		// File contents
+		let mut contents_writer = CrcWriter { inner: &mut writer, crc: 0, written: 0 };
+		for ((_, full_path), (_, size, crc)) in files.iter().zip(file_table.iter_mut()) {
+			let mut file_writer = CrcWriter { inner: &mut contents_writer, crc: 0, written: 0 };
+			io::copy(&mut File::open(full_path)?, &mut file_writer)?;
+			(*size, *crc) = (file_writer.written, file_writer.crc);
+		}
+		let (contents_crc, contents_size) = (contents_writer.crc, contents_writer.written);
+
+		let head = gma_head(title, description_json, author, timestamp, &file_table);
+		writer.seek(SeekFrom::Start(0))?;
+		writer.write_all(&head)?;
+
+		// Checksum of everything written so far
+		let addon_crc = crc32_combine(crc32(&head), contents_crc, contents_size);
+		writer.seek(SeekFrom::End(0))?;
+		writer.write_all(&addon_crc.to_le_bytes())?;
+
+		writer.flush()?;
+
+		return Ok(head_size + contents_size + 4);
+
+	})();
+
+	return match write_result {
+		Ok(written) => Ok(written),
+		Err(err) => bail!("Failed to write \"{}\": {}", gma_path.display(), err.to_string()),
+	};
+
+}
+
+// Header and file table of a gma, given as (path inside the gma, size, checksum)
+fn gma_head(title: &str, description_json: &str, author: &str, timestamp: u64, file_table: &Vec<(&String, u64, u32)>) -> Vec<u8> {
+
+	let mut head: Vec<u8> = vec!();
+
+	// Header
+	head.extend_from_slice(GMA_IDENT);
+	head.push(GMA_VERSION);
+	head.extend_from_slice(&0u64.to_le_bytes()); // Steam ID
+	head.extend_from_slice(&timestamp.to_le_bytes());
+	head.push(0); // Required content (none)
+
+	for string in [title, description_json, author] {
+		head.extend_from_slice(string.as_bytes());
+		head.push(0);
+	}
+
+	head.extend_from_slice(&1i32.to_le_bytes()); // Addon version
+
+	// File table
+	for (index, (path, size, crc)) in file_table.iter().enumerate() {
+		head.extend_from_slice(&(index as u32 + 1).to_le_bytes());
+		head.extend_from_slice(path.as_bytes());
+		head.push(0);
+		head.extend_from_slice(&(*size as i64).to_le_bytes());
+		head.extend_from_slice(&crc.to_le_bytes());
+	}
+	head.extend_from_slice(&0u32.to_le_bytes());
+
+	return head;
+
+}
+
+// Matches gmad's wildcard patterns (only "*" is special) case-insensitively
+pub fn wildcard_match(pattern: &str, path: &str) -> bool {
+
+	let pattern = pattern.to_lowercase();
+	let path = path.to_lowercase();
+	let parts: Vec<&str> = pattern.split('*').collect();
+
+	// Without any wildcard, the whole path has to match
+	if parts.len() == 1 {
+		return pattern == path;
+	}
+
+	let mut remaining = path.as_str();
+
+	for (index, part) in parts.iter().enumerate() {
+		if index == 0 {
+			match remaining.strip_prefix(part) {
+				Some(rest) => remaining = rest,
+				None => return false,
+			}
+		} else if index == parts.len() - 1 {
+			return remaining.ends_with(part);
+		} else {
+			match remaining.find(part) {
+				Some(position) => remaining = &remaining[position + part.len()..],
+				None => return false,
+			}
+		}
+	}
+
+	return true;
+
+}
+
+// Whether gmad packs the file, the path has to be relative to the addon root and use forward slashes
+pub fn is_whitelisted(path: &str) -> bool {
+	return GMA_WHITELIST.iter().any(|pattern| wildcard_match(pattern, path));
+}
+
+// Extracts the "author" of an addon.json, which gmad itself does not read
+pub fn addon_json_author(addon_json: &str) -> Option<String> {
+
+	let regex = Regex::new(r#""author"\s*:\s*"((?:[^"\\]|\\.)*)""#).unwrap();
+
+	return regex
+		.captures(addon_json)
+		.map(|captures| library::json::unescape(&captures[1]))
+		.filter(|author| !author.trim().is_empty());
+
+}
+
+// Extracts the "ignore" patterns of an addon.json
+pub fn addon_json_ignore_patterns(addon_json: &str) -> Vec<String> {
+
+	let regex = Regex::new(r#""ignore"\s*:\s*\[([^\]]*)\]"#).unwrap();
+
+	return match regex.captures(addon_json) {
+		Some(captures) => captures[1]
+			.split(',')
+			.map(|pattern| pattern.trim().trim_matches('"').to_string())
+			.filter(|pattern| !pattern.is_empty())
+			.collect(),
+		None => vec!(),
+	};
+
+}
+
+pub fn crc32(bytes: &[u8]) -> u32 {
+	return crc32_update(0, bytes);
+}
+
+pub fn crc32_update(crc: u32, bytes: &[u8]) -> u32 {
+
+	let mut crc: u32 = !crc;
+
+	for &byte in bytes {
+		crc ^= byte as u32;
+		for _ in 0..8 {
+			crc = match crc & 1 {
+				1 => (crc >> 1) ^ 0xEDB88320,
+				_ => crc >> 1,
+			};
+		}
+	}
+
+	return !crc;
+
+}
+
+// Checksum of two byte sequences in a row from the checksums of both and the length of the second one (crc32_combine of zlib)
+pub fn crc32_combine(crc_a: u32, crc_b: u32, length_b: u64) -> u32 {
+
+	if length_b == 0 {
+		return crc_a;
+	}
+
+	// Operator for a single zero bit, then for two and four zero bits
+	let mut odd = [0u32; 32];
+	odd[0] = 0xEDB88320;
+	for (bit, row) in odd.iter_mut().enumerate().skip(1) {
+		*row = 1 << (bit - 1);
+	}
+	let mut even = gf2_matrix_square(&odd);
+	odd = gf2_matrix_square(&even);
+
+	// Append length_b zero bytes to crc_a, one bit of the length at a time
+	let mut crc = crc_a;
+	let mut length = length_b;
+	loop {
+
+		even = gf2_matrix_square(&odd);
+		if length & 1 == 1 {
+			crc = gf2_matrix_times(&even, crc);
+		}
+		length >>= 1;
+		if length == 0 {
+			break;
+		}
+
+		odd = gf2_matrix_square(&even);
+		if length & 1 == 1 {
+			crc = gf2_matrix_times(&odd, crc);
+		}
+		length >>= 1;
+		if length == 0 {
+			break;
+		}
+
+	}
+
+	return crc ^ crc_b;
+
+}
+
+fn gf2_matrix_times(matrix: &[u32; 32], vector: u32) -> u32 {
+
+	let mut sum: u32 = 0;
+	let mut vector = vector;
+
+	for row in matrix {
+		if vector == 0 {
+			break;
+		}
+		if vector & 1 == 1 {
+			sum ^= row;
+		}
+		vector >>= 1;
+	}
+
+	return sum;
+
+}
+
+fn gf2_matrix_square(matrix: &[u32; 32]) -> [u32; 32] {
+	let mut square = [0u32; 32];
+	for (square_row, row) in square.iter_mut().zip(matrix.iter()) {
+		*square_row = gf2_matrix_times(matrix, *row);
+	}
+	return square;
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+	use std::fs;
+
+	// Writes the files into a temporary directory and packs them into a gma, returning its bytes
+	fn write_gma(name: &str, files: &[(&str, &[u8])]) -> Vec<u8> {
+
+		let directory = std::env::temp_dir().join(format!("gcli_gma_test_{}_{}", name, std::process::id()));
+		fs::create_dir_all(&directory).unwrap();
+
+		let mut gma_files: Vec<(String, PathBuf)> = vec!();
+		for (index, (path, content)) in files.iter().enumerate() {
+			let full_path = directory.join(index.to_string());
+			fs::write(&full_path, content).unwrap();
+			gma_files.push((path.to_string(), full_path));
+		}
+
+		let gma_path = directory.join("test.gma");
+		let description = description_json("Test description", "map", &["fun", "build"]);
+		let written = write(&gma_path, "Test addon", &description, "Test author", &gma_files).unwrap();
+
+		let bytes = fs::read(&gma_path).unwrap();
+		fs::remove_dir_all(&directory).unwrap();
+
+		assert_eq!(written, bytes.len() as u64);
+		return bytes;
+
+	}
+
+	#[test]
+	fn read_returns_written_files() {
+
+		let bytes = write_gma("round_trip", &[("materials/a.vmt", b"LightmappedGeneric {}"), ("models/b.mdl", b"IDST"), ("empty.txt", b"")]);
+		let gma = read(&bytes).unwrap();
+
+		assert_eq!(gma.version, GMA_VERSION);
+		assert_eq!(gma.title, "Test addon");
+		assert_eq!(gma.author, "Test author");
+		assert_eq!(gma.description_field("type").as_deref(), Some("map"));
+		assert_eq!(gma.description_field("description").as_deref(), Some("Test description"));
+		assert_eq!(gma.tags(), vec!["fun", "build"]);
+
+		let paths: Vec<&str> = gma.entries.iter().map(|entry| entry.path.as_str()).collect();
+		assert_eq!(paths, vec!["materials/a.vmt", "models/b.mdl", "empty.txt"]);
+		assert_eq!(entry_data(&bytes, &gma.entries[0]), b"LightmappedGeneric {}");
+		assert_eq!(entry_data(&bytes, &gma.entries[1]), b"IDST");
+		assert_eq!(entry_data(&bytes, &gma.entries[2]), b"");
+
+		for entry in &gma.entries {
+			assert_eq!(entry.crc, crc32(entry_data(&bytes, entry)));
+		}
+
+		// The gma ends with the checksum of everything before it
+		assert_eq!(bytes[bytes.len() - 4..], crc32(&bytes[..bytes.len() - 4]).to_le_bytes());
+
+	}
+
+	#[test]
+	fn crc32_combine_matches_checksum_of_concatenation() {
+
+		let (a, b) = (b"LightmappedGeneric".as_slice(), b"{ \"$basetexture\" \"brick/wall\" }".as_slice());
+
+		assert_eq!(crc32_combine(crc32(a), crc32(b), b.len() as u64), crc32(&[a, b].concat()));
+		assert_eq!(crc32_combine(crc32(a), crc32(b""), 0), crc32(a));
+
+	}
+
+	#[test]
+	fn read_rejects_truncated_file_contents() {
+
+		let bytes = write_gma("truncated", &[("materials/a.vmt", b"0123456789")]);
+
+		// Cut off the addon checksum and half of the file content
+		let err = read(&bytes[..bytes.len() - 9]).err().unwrap();
+		assert_eq!(err.to_string(), "Contents of file \"materials/a.vmt\" are truncated, it ends at offset 10 of the file data but only 5 bytes of file data are present");
+		assert!(read(&bytes[..bytes.len() - 4]).is_ok());
+
+	}
+
+	#[test]
+	fn read_rejects_sizes_which_overflow_the_offsets() {
+
+		let mut bytes = write_gma("overflow", &[("a.txt", b"a"), ("b.txt", b"b")]);
+
+		// The size of the first entry follows its null-terminated path
+		let size_position = bytes.windows(6).position(|window| window == b"a.txt\0").unwrap() + 6;
+		bytes[size_position..size_position + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+
+		assert!(read(&bytes).is_err());
+
+	}
+
+	#[test]
+	fn wildcard_match_matches_gmad_patterns() {
+		assert!(wildcard_match("*.txt", "scripts/soundscapes_map.txt"));
+		assert!(wildcard_match("materials/*.vmt", "Materials/Brick/Wall.VMT"));
+		assert!(wildcard_match("resource/localization/*/*.properties", "resource/localization/en/map.properties"));
+		assert!(wildcard_match("thumbs.db", "Thumbs.db"));
+		assert!(!wildcard_match("thumbs.db", "materials/thumbs.db"));
+		assert!(!wildcard_match("materials/*.vmt", "materials/brick/wall.vtf"));
+		assert!(!wildcard_match("*.svn*", "materials/brick.vmt"));
+	}
+
+	#[test]
+	fn whitelist_excludes_scripts_gmad_refuses() {
+		assert!(is_whitelisted("materials/brick/wall.vtf"));
+		assert!(is_whitelisted("models/props/crate.dx90.vtx"));
+		assert!(is_whitelisted("scripts/vehicles/jeep.txt"));
+		assert!(is_whitelisted("materials/colorcorrection/night.raw"));
+		assert!(!is_whitelisted("scripts/soundscapes_my_map.txt"));
+		assert!(!is_whitelisted("scripts/weapon_custom.txt"));
+		assert!(!is_whitelisted("maps/my_map_particles.txt"));
+		assert!(!is_whitelisted("materials/brick/wall.psd"));
+	}
+
+	#[test]
+	fn addon_json_ignore_patterns_are_read() {
+		let addon_json = "{\n\t\"title\": \"Map\",\n\t\"ignore\":\n\t[\n\t\t\"*.psd\",\n\t\t\"maps/*.vmf\"\n\t]\n}";
+		assert_eq!(addon_json_ignore_patterns(addon_json), vec!["*.psd", "maps/*.vmf"]);
+		assert!(addon_json_ignore_patterns("{ \"title\": \"Map\" }").is_empty());
+	}
+
+	#[test]
+	fn addon_json_author_is_read() {
+		assert_eq!(addon_json_author("{\n\t\"title\": \"Map\",\n\t\"author\": \"Harbor \\\"Team\\\"\"\n}").as_deref(), Some("Harbor \"Team\""));
+		assert_eq!(addon_json_author("{ \"title\": \"Map\", \"author\": \" \" }"), None);
+		assert_eq!(addon_json_author("{ \"title\": \"Map\" }"), None);
+	}
+
+	#[test]
+	fn read_rejects_other_files() {
+		assert!(read(b"").is_err());
+		assert!(read(b"PK\x03\x04 not a gma").is_err());
+		assert!(read(b"GMAD\x03").is_err());
+	}
+
+}
@@ -1,5 +1,17 @@
+use std::sync::atomic::{AtomicBool, Ordering};
 use inquire::{Text, required, Select, Confirm};
 
+// Set by the global --assume-yes flag, confirmations are answered with "yes" without prompting, also the ones which default to "no" (e.g. overwriting existing files)
+static ASSUME_YES: AtomicBool = AtomicBool::new(false);
+
+pub fn set_assume_yes(enabled: bool) {
+	ASSUME_YES.store(enabled, Ordering::Relaxed);
+}
+
+pub fn assume_yes() -> bool {
+	return ASSUME_YES.load(Ordering::Relaxed);
+}
+
 pub fn text_required(prompt: &str) -> String {
 
 	let res_string = Text::new(prompt)
@@ -47,6 +59,9 @@ pub fn selector_index<'a>(prompt: &str, options: &Vec<&str>) -> usize {
 }
 
 pub fn confirm_no(prompt: &str) -> bool {
+	if assume_yes() {
+		return true;
+	}
 	return Confirm::new(prompt)
 		.with_default(false)
 		.prompt()
@@ -54,6 +69,9 @@ pub fn confirm_no(prompt: &str) -> bool {
 }
 
 pub fn confirm_yes(prompt: &str) -> bool {
+	if assume_yes() {
+		return true;
+	}
 	return Confirm::new(prompt)
 		.with_default(true)
 		.prompt()
@@ -0,0 +1,90 @@
+use clap::{ArgAction, ArgMatches, Command};
+
+// Helpers for the hand-written JSON output of the commands
+
+// Returns the value as a quoted and escaped JSON string
+pub fn string(value: &str) -> String {
+
+	let mut escaped = String::with_capacity(value.len() + 2);
+	escaped.push('"');
+
+	for char in value.chars() {
+		match char {
+			'"' => escaped.push_str("\\\""),
+			'\\' => escaped.push_str("\\\\"),
+			'\n' => escaped.push_str("\\n"),
+			'\r' => escaped.push_str("\\r"),
+			'\t' => escaped.push_str("\\t"),
+			char if (char as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", char as u32)),
+			char => escaped.push(char),
+		}
+	}
+
+	escaped.push('"');
+	return escaped;
+
+}
+
+// Reverses string(), expects the content of a JSON string without the quotes
+pub fn unescape(value: &str) -> String {
+
+	let mut unescaped = String::with_capacity(value.len());
+	let mut chars = value.chars();
+
+	while let Some(char) = chars.next() {
+
+		if char != '\\' {
+			unescaped.push(char);
+			continue;
+		}
+
+		match chars.next() {
+			Some('n') => unescaped.push('\n'),
+			Some('r') => unescaped.push('\r'),
+			Some('t') => unescaped.push('\t'),
+			Some('u') => {
+				let code: String = chars.by_ref().take(4).collect();
+				match u32::from_str_radix(&code, 16).ok().and_then(char::from_u32) {
+					Some(char) => unescaped.push(char),
+					None => unescaped.push(char::REPLACEMENT_CHARACTER),
+				}
+			},
+			// \" / \\ / \/
+			Some(char) => unescaped.push(char),
+			None => {},
+		}
+
+	}
+
+	return unescaped;
+
+}
+
+// Returns the values of all arguments of a command as JSON object fields keyed by their long name (e.g. "\"source-path\": [...]"), including default values.
+// Flags are booleans, arguments which can be used multiple times are arrays and arguments which are not set are null.
+pub fn argument_fields(command: &Command, matches: &ArgMatches) -> Vec<String> {
+
+	let mut fields: Vec<String> = vec!();
+
+	for arg in command.get_arguments() {
+
+		let id = arg.get_id().as_str();
+		let values: Vec<String> = match matches.try_get_raw(id) {
+			Ok(Some(values)) => values.map(|value| value.to_string_lossy().to_string()).collect(),
+			_ => vec!(),
+		};
+
+		let value = match arg.get_action() {
+			ArgAction::Help | ArgAction::HelpShort | ArgAction::HelpLong | ArgAction::Version => continue,
+			ArgAction::SetTrue | ArgAction::SetFalse => (values.first().map(|value| value.as_str()) == Some("true")).to_string(),
+			ArgAction::Append => format!("[{}]", values.iter().map(|value| string(value)).collect::<Vec<String>>().join(", ")),
+			_ => values.first().map(|value| string(value)).unwrap_or(String::from("null")),
+		};
+
+		fields.push(format!("{}: {}", string(arg.get_long().unwrap_or(id)), value));
+
+	}
+
+	return fields;
+
+}
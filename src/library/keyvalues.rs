@@ -0,0 +1,184 @@
+use simple_error::{bail, SimpleError};
+
+// Minimal parser for Valve's KeyValues text format (e.g. soundscape scripts)
+#[derive(Debug, Clone)]
+pub enum KeyValue {
+	Value(String),
+	Block(Vec<(String, KeyValue)>),
+}
+
+impl KeyValue {
+
+	// Returns the first value with the provided key (case-insensitive) inside this block
+	pub fn get(&self, key: &str) -> Option<&KeyValue> {
+		return match self {
+			KeyValue::Block(children) => children
+				.iter()
+				.find(|(child_key, _)| child_key.eq_ignore_ascii_case(key))
+				.map(|(_, value)| value),
+			KeyValue::Value(_) => None,
+		};
+	}
+
+	pub fn as_str(&self) -> Option<&str> {
+		return match self {
+			KeyValue::Value(value) => Some(value),
+			KeyValue::Block(_) => None,
+		};
+	}
+
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+	String(String),
+	BlockStart,
+	BlockEnd,
+}
+
+fn tokenize(content: &str) -> Result<Vec<Token>, SimpleError> {
+
+	let mut tokens: Vec<Token> = vec!();
+	let mut chars = content.chars().peekable();
+
+	while let Some(char) = chars.next() {
+		match char {
+			'{' => tokens.push(Token::BlockStart),
+			'}' => tokens.push(Token::BlockEnd),
+			'"' => {
+				let mut string = String::new();
+				loop {
+					match chars.next() {
+						Some('"') => break,
+						Some(char) => string.push(char),
+						None => bail!("Unterminated string \"{}\"", string),
+					}
+				}
+				tokens.push(Token::String(string));
+			},
+			'/' if chars.peek() == Some(&'/') => {
+				// Comment until the end of the line
+				while let Some(char) = chars.next() {
+					if char == '\n' {
+						break;
+					}
+				}
+			},
+			'[' => {
+				// Conditionals like [$WIN32] are ignored
+				while let Some(char) = chars.next() {
+					if char == ']' {
+						break;
+					}
+				}
+			},
+			char if char.is_whitespace() => {},
+			char => {
+				// Unquoted string
+				let mut string = String::from(char);
+				while let Some(&next) = chars.peek() {
+					if next.is_whitespace() || next == '{' || next == '}' || next == '"' {
+						break;
+					}
+					string.push(next);
+					chars.next();
+				}
+				tokens.push(Token::String(string));
+			},
+		}
+	}
+
+	return Ok(tokens);
+
+}
+
+fn parse_block(tokens: &mut std::vec::IntoIter<Token>, nested: bool) -> Result<Vec<(String, KeyValue)>, SimpleError> {
+
+	let mut children: Vec<(String, KeyValue)> = vec!();
+
+	loop {
+
+		let key = match tokens.next() {
+			Some(Token::String(key)) => key,
+			Some(Token::BlockEnd) if nested => return Ok(children),
+			Some(token) => bail!("Unexpected {:?}, expected a key", token),
+			None if nested => bail!("Unexpected end of file, expected \"{}\"", "}"),
+			None => return Ok(children),
+		};
+
+		let value = match tokens.next() {
+			Some(Token::String(value)) => KeyValue::Value(value),
+			Some(Token::BlockStart) => KeyValue::Block(parse_block(tokens, true)?),
+			Some(Token::BlockEnd) => bail!("Unexpected \"}}\" after key \"{}\"", key),
+			None => bail!("Unexpected end of file after key \"{}\"", key),
+		};
+
+		children.push((key, value));
+
+	}
+
+}
+
+pub fn parse(content: &str) -> Result<Vec<(String, KeyValue)>, SimpleError> {
+	let mut tokens = tokenize(content)?.into_iter();
+	return parse_block(&mut tokens, false);
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+
+	#[test]
+	fn parses_soundscape_script() {
+
+		let content = r#"
+// Harbor ambience
+"harbor.docks"
+{
+	"dsp"	"1"
+	playlooping
+	{
+		volume 0.6
+		wave "ambient/levels/harbor/water_lap.wav" [$X360]
+	}
+	"playlooping"
+	{
+		"wave"	"MyMap/Gulls.wav"
+	}
+}
+"#;
+
+		let parsed = parse(content).unwrap();
+		assert_eq!(parsed.len(), 1);
+
+		let (name, soundscape) = &parsed[0];
+		assert_eq!(name, "harbor.docks");
+		assert_eq!(soundscape.get("DSP").and_then(|dsp| dsp.as_str()), Some("1"));
+
+		// Keys can repeat, get only returns the first one
+		let waves: Vec<&str> = match soundscape {
+			KeyValue::Block(children) => children
+				.iter()
+				.filter(|(key, _)| key == "playlooping")
+				.filter_map(|(_, rule)| rule.get("wave").and_then(|wave| wave.as_str()))
+				.collect(),
+			KeyValue::Value(_) => vec!(),
+		};
+		assert_eq!(waves, vec!("ambient/levels/harbor/water_lap.wav", "MyMap/Gulls.wav"));
+		assert!(soundscape.get("wave").is_none());
+
+	}
+
+	#[test]
+	fn rejects_unbalanced_blocks_and_strings() {
+
+		assert_eq!(parse("\"harbor.docks\" { \"dsp\" \"1\"").unwrap_err().to_string(), "Unexpected end of file, expected \"}\"");
+		assert_eq!(parse("\"harbor.docks\" }").unwrap_err().to_string(), "Unexpected \"}\" after key \"harbor.docks\"");
+		assert_eq!(parse("\"harbor.docks").unwrap_err().to_string(), "Unterminated string \"harbor.docks\"");
+		assert_eq!(parse("dsp").unwrap_err().to_string(), "Unexpected end of file after key \"dsp\"");
+		assert!(parse("").unwrap().is_empty());
+
+	}
+
+}
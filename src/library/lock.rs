@@ -0,0 +1,93 @@
+use std::{fs, io::{ErrorKind, Write}, path::{Path, PathBuf}, thread, time::Duration};
+use simple_error::{bail, SimpleError};
+use crate::library::log::info;
+
+pub const LOCK_FILE_NAME: &str = ".gcli.lock";
+
+// Advisory lock of a directory, so concurrent runs do not write into the same directory at the same time.
+// The lock file is removed when the lock is dropped.
+pub struct DirectoryLock {
+	path: PathBuf,
+}
+
+impl DirectoryLock {
+
+	// Creates the lock file exclusively. If another run holds the lock, this fails or waits until the lock is released.
+	pub fn acquire(directory: &Path, wait: bool) -> Result<DirectoryLock, SimpleError> {
+
+		let path = directory.join(LOCK_FILE_NAME);
+		let mut waiting = false;
+
+		loop {
+			match fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+				Ok(mut file) => {
+					// The process id helps to find out if a lock is stale
+					let _ = write!(file, "{}", std::process::id());
+					return Ok(DirectoryLock { path });
+				},
+				Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+
+					let holder = fs::read_to_string(&path).unwrap_or_default();
+
+					if !wait {
+						bail!("\"{}\" is locked by another run (process {}). Use --wait to wait for it, or remove \"{}\" if no other run is active.", directory.display(), holder.trim(), path.display());
+					}
+
+					if !waiting {
+						info!("Waiting for another run (process {}) to release \"{}\"...", holder.trim(), path.display());
+						waiting = true;
+					}
+
+					thread::sleep(Duration::from_secs(1));
+
+				},
+				Err(err) => bail!("Failed to create lock file \"{}\": {}", path.display(), err.to_string()),
+			}
+		}
+
+	}
+
+}
+
+impl Drop for DirectoryLock {
+	fn drop(&mut self) {
+		let _ = fs::remove_file(&self.path);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+
+	#[test]
+	fn held_lock_is_refused_until_released() {
+
+		let directory = std::env::temp_dir().join(format!("gcli_lock_test_{}", std::process::id()));
+		fs::create_dir_all(&directory).unwrap();
+
+		let lock = DirectoryLock::acquire(&directory, false).unwrap();
+		assert_eq!(fs::read_to_string(directory.join(LOCK_FILE_NAME)).unwrap(), std::process::id().to_string());
+
+		// A concurrent run without --wait gives up right away
+		let err = DirectoryLock::acquire(&directory, false).err().unwrap();
+		assert!(err.to_string().contains(&format!("process {}", std::process::id())), "{}", err);
+
+		drop(lock);
+		assert!(!directory.join(LOCK_FILE_NAME).exists());
+
+		// A waiting run gets the lock as soon as it is released
+		let lock = DirectoryLock::acquire(&directory, false).unwrap();
+		let waiting_run = thread::spawn({
+			let directory = directory.to_owned();
+			move || DirectoryLock::acquire(&directory, true).map(|_| ())
+		});
+		thread::sleep(Duration::from_millis(200));
+		drop(lock);
+		assert!(waiting_run.join().unwrap().is_ok());
+
+		fs::remove_dir_all(&directory).unwrap();
+
+	}
+
+}
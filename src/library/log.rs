@@ -0,0 +1,167 @@
+use std::sync::{atomic::{AtomicBool, Ordering}, Mutex, MutexGuard};
+use regex::Regex;
+
+// Wrappers around the paris macros which can redirect all human-readable output to stderr,
+// e.g. to keep stdout free for machine-readable output.
+static LOG_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+pub fn set_log_to_stderr(enabled: bool) {
+	LOG_TO_STDERR.store(enabled, Ordering::Relaxed);
+}
+
+pub fn log_to_stderr() -> bool {
+	return LOG_TO_STDERR.load(Ordering::Relaxed);
+}
+
+// Held while a message is printed, so messages of parallel tasks never end up on the same line
+static LOG_LOCK: Mutex<()> = Mutex::new(());
+
+pub fn lock() -> MutexGuard<'static, ()> {
+	return LOG_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+}
+
+pub fn strip_color_tags(message: &str) -> String {
+	let regex = Regex::new(r"</?[a-zA-Z\-]*>").unwrap();
+	return regex.replace_all(message, "").to_string();
+}
+
+macro_rules! info {
+	($($arg:tt)*) => {{
+		// Formatted before locking, in case an argument logs itself
+		let message = format!($($arg)*);
+		let _guard = $crate::library::log::lock();
+		if $crate::library::log::log_to_stderr() {
+			eprintln!("ℹ {}", $crate::library::log::strip_color_tags(&message));
+		} else {
+			paris::info!("{}", message);
+		}
+	}}
+}
+
+macro_rules! success {
+	($($arg:tt)*) => {{
+		// Formatted before locking, in case an argument logs itself
+		let message = format!($($arg)*);
+		let _guard = $crate::library::log::lock();
+		if $crate::library::log::log_to_stderr() {
+			eprintln!("✔ {}", $crate::library::log::strip_color_tags(&message));
+		} else {
+			paris::success!("{}", message);
+		}
+	}}
+}
+
+// Named differently since "warn" would be ambiguous with the built-in attribute inside this module
+macro_rules! warning {
+	($($arg:tt)*) => {{
+		// Formatted before locking, in case an argument logs itself
+		let message = format!($($arg)*);
+		let _guard = $crate::library::log::lock();
+		if $crate::library::log::log_to_stderr() {
+			eprintln!("⚠ {}", $crate::library::log::strip_color_tags(&message));
+		} else {
+			paris::warn!("{}", message);
+		}
+	}}
+}
+
+macro_rules! error {
+	($($arg:tt)*) => {{
+		// Formatted before locking, in case an argument logs itself
+		let message = format!($($arg)*);
+		let _guard = $crate::library::log::lock();
+		if $crate::library::log::log_to_stderr() {
+			eprintln!("✖ {}", $crate::library::log::strip_color_tags(&message));
+		} else {
+			paris::error!("{}", message);
+		}
+	}}
+}
+
+pub(crate) use {info, success, warning as warn, error};
+
+// Collects the warnings of parallel tasks, so they can be printed grouped once all tasks of a phase are done
+pub struct BufferedLog {
+	warnings: Mutex<Vec<String>>,
+}
+
+impl BufferedLog {
+
+	pub fn new() -> Self {
+		return Self { warnings: Mutex::new(vec!()) };
+	}
+
+	pub fn warn(&self, message: String) {
+		self.warnings.lock().unwrap_or_else(|err| err.into_inner()).push(message);
+	}
+
+	// Prints all collected warnings in the order they were collected
+	pub fn flush(self) {
+		for message in self.warnings.into_inner().unwrap_or_else(|err| err.into_inner()) {
+			warning!("{}", message);
+		}
+	}
+
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+	use std::thread;
+
+	#[test]
+	fn lock_keeps_lines_of_parallel_tasks_whole() {
+
+		let output = Mutex::new(String::new());
+
+		// Every character is written separately, so lines would be interleaved without the lock
+		thread::scope(|scope| {
+			for task in 0..8 {
+				let output = &output;
+				scope.spawn(move || {
+					for line in 0..20 {
+						let _guard = lock();
+						for character in format!("task {} line {}\n", task, line).chars() {
+							output.lock().unwrap().push(character);
+							thread::yield_now();
+						}
+					}
+				});
+			}
+		});
+
+		let output = output.into_inner().unwrap();
+		let line_regex = Regex::new(r"^task \d line \d+$").unwrap();
+		assert_eq!(output.lines().count(), 160);
+		assert!(output.lines().all(|line| line_regex.is_match(line)), "{}", output);
+
+	}
+
+	#[test]
+	fn buffered_log_keeps_whole_messages_in_order() {
+
+		let buffered_log = BufferedLog::new();
+
+		thread::scope(|scope| {
+			for task in 0..4 {
+				let buffered_log = &buffered_log;
+				scope.spawn(move || {
+					for message in 0..10 {
+						buffered_log.warn(format!("Task {} failed to find file {}", task, message));
+					}
+				});
+			}
+		});
+
+		let warnings = buffered_log.warnings.into_inner().unwrap();
+		assert_eq!(warnings.len(), 40);
+		for task in 0..4 {
+			let task_warnings: Vec<&String> = warnings.iter().filter(|warning| warning.starts_with(&format!("Task {} ", task))).collect();
+			let expected: Vec<String> = (0..10).map(|message| format!("Task {} failed to find file {}", task, message)).collect();
+			assert_eq!(task_warnings, expected.iter().collect::<Vec<_>>());
+		}
+
+	}
+
+}
@@ -0,0 +1,394 @@
+use simple_error::{bail, SimpleError};
+
+// Minimal reader for the parts of a model (.mdl) which plumber_core does not expose
+
+pub const MDL_IDENT: &[u8; 4] = b"IDST";
+
+// Offsets into studiohdr_t
+const SEQUENCE_COUNT_OFFSET: usize = 188;
+const SEQUENCE_INDEX_OFFSET: usize = 192;
+const TEXTURE_COUNT_OFFSET: usize = 204;
+const TEXTURE_INDEX_OFFSET: usize = 208;
+const SKIN_REFERENCE_COUNT_OFFSET: usize = 220;
+const SKIN_FAMILY_COUNT_OFFSET: usize = 224;
+const SKIN_INDEX_OFFSET: usize = 228;
+const KEYVALUES_INDEX_OFFSET: usize = 312;
+const KEYVALUES_SIZE_OFFSET: usize = 316;
+const INCLUDE_MODEL_COUNT_OFFSET: usize = 336;
+const INCLUDE_MODEL_INDEX_OFFSET: usize = 340;
+
+// Size of mstudiotexture_t, its first field is the offset of the name relative to the texture
+const TEXTURE_SIZE: usize = 64;
+
+// Size of mstudioseqdesc_t (version 44 to 49) and the offsets of numevents / eventindex, the event index is relative to the sequence
+pub const SEQUENCE_SIZE: usize = 212;
+const SEQUENCE_EVENT_COUNT_OFFSET: usize = 24;
+const SEQUENCE_EVENT_INDEX_OFFSET: usize = 28;
+
+// Size of mstudioevent_t and the offset and size of its options
+pub const EVENT_SIZE: usize = 80;
+const EVENT_OPTIONS_OFFSET: usize = 12;
+const EVENT_OPTIONS_SIZE: usize = 64;
+
+// Size of mstudiomodelgroup_t and the offset of sznameindex, which is relative to the model group
+const MODEL_GROUP_SIZE: usize = 8;
+const MODEL_GROUP_NAME_OFFSET: usize = 4;
+
+// Offsets into the header of a .phy
+const PHY_HEADER_SIZE_OFFSET: usize = 0;
+const PHY_SOLID_COUNT_OFFSET: usize = 8;
+
+fn read_i32(bytes: &[u8], offset: usize) -> Result<i32, SimpleError> {
+	return match offset.checked_add(4).and_then(|end| bytes.get(offset..end)) {
+		Some(slice) => Ok(i32::from_le_bytes(slice.try_into().unwrap())),
+		None => bail!("Unexpected end of file at offset {}", offset),
+	};
+}
+
+fn read_i16(bytes: &[u8], offset: usize) -> Result<i16, SimpleError> {
+	return match offset.checked_add(2).and_then(|end| bytes.get(offset..end)) {
+		Some(slice) => Ok(i16::from_le_bytes(slice.try_into().unwrap())),
+		None => bail!("Unexpected end of file at offset {}", offset),
+	};
+}
+
+fn read_count(bytes: &[u8], offset: usize) -> Result<usize, SimpleError> {
+	let count = read_i32(bytes, offset)?;
+	return match usize::try_from(count) {
+		Ok(count) => Ok(count),
+		Err(_) => bail!("Invalid count {} at offset {}", count, offset),
+	};
+}
+
+// Offset of the element of a table (e.g. the sequences) which starts at index
+fn element_offset(index: usize, element: usize, element_size: usize) -> Result<usize, SimpleError> {
+	return match element.checked_mul(element_size).and_then(|offset| offset.checked_add(index)) {
+		Some(offset) => Ok(offset),
+		None => bail!("Invalid offset of element {} of the table at offset {}", element, index),
+	};
+}
+
+fn field_offset(offset: usize, field_offset: usize) -> Result<usize, SimpleError> {
+	return match offset.checked_add(field_offset) {
+		Some(offset) => Ok(offset),
+		None => bail!("Invalid offset {}", offset),
+	};
+}
+
+fn read_bytes(bytes: &[u8], offset: usize, length: usize) -> Result<&[u8], SimpleError> {
+	return match offset.checked_add(length).and_then(|end| bytes.get(offset..end)) {
+		Some(slice) => Ok(slice),
+		None => bail!("Unexpected end of file, expected {} bytes at offset {}", length, offset),
+	};
+}
+
+// Reads a string of a fixed size field, which ends at the first zero byte or the end of the field
+fn read_fixed_string(bytes: &[u8], offset: usize, size: usize) -> Result<String, SimpleError> {
+	let field = read_bytes(bytes, offset, size)?;
+	let length = field.iter().position(|&byte| byte == 0).unwrap_or(field.len());
+	return Ok(String::from_utf8_lossy(&field[..length]).to_string());
+}
+
+fn read_string(bytes: &[u8], offset: usize) -> Result<String, SimpleError> {
+	let remaining = match bytes.get(offset..) {
+		Some(remaining) => remaining,
+		None => bail!("Unexpected end of file at offset {}", offset),
+	};
+	return match remaining.iter().position(|&byte| byte == 0) {
+		Some(length) => Ok(String::from_utf8_lossy(&remaining[..length]).to_string()),
+		None => bail!("Unterminated string at offset {}", offset),
+	};
+}
+
+// Returns the texture (material) names of every skin family of the model, one list per family.
+// A skin family replaces the textures of the default family (family 0) at the same position, e.g. for reskinned props.
+pub fn skin_families(bytes: &[u8]) -> Result<Vec<Vec<String>>, SimpleError> {
+
+	check_header(bytes)?;
+
+	let texture_count = read_count(bytes, TEXTURE_COUNT_OFFSET)?;
+	let texture_index = read_count(bytes, TEXTURE_INDEX_OFFSET)?;
+	let skin_reference_count = read_count(bytes, SKIN_REFERENCE_COUNT_OFFSET)?;
+	let skin_family_count = read_count(bytes, SKIN_FAMILY_COUNT_OFFSET)?;
+	let skin_index = read_count(bytes, SKIN_INDEX_OFFSET)?;
+
+	let mut texture_names: Vec<String> = Vec::with_capacity(texture_count.min(bytes.len() / TEXTURE_SIZE));
+	for texture in 0..texture_count {
+		let texture_offset = match texture.checked_mul(TEXTURE_SIZE).and_then(|offset| offset.checked_add(texture_index)) {
+			Some(offset) => offset,
+			None => bail!("Invalid texture index {}", texture_index),
+		};
+		let name_offset = read_i32(bytes, texture_offset)?;
+		let name_offset = match texture_offset.checked_add_signed(name_offset as isize) {
+			Some(offset) => offset,
+			None => bail!("Invalid name offset {} of texture {}", name_offset, texture),
+		};
+		texture_names.push(read_string(bytes, name_offset)?);
+	}
+
+	let mut families: Vec<Vec<String>> = vec!();
+	for family in 0..skin_family_count {
+		let mut family_names: Vec<String> = vec!();
+		for reference in 0..skin_reference_count {
+			let offset = match family.checked_mul(skin_reference_count).and_then(|position| position.checked_add(reference)).and_then(|position| position.checked_mul(2)).and_then(|offset| offset.checked_add(skin_index)) {
+				Some(offset) => offset,
+				None => bail!("Invalid skin index {}", skin_index),
+			};
+			let texture = read_i16(bytes, offset)?;
+			match usize::try_from(texture).ok().and_then(|texture| texture_names.get(texture)) {
+				Some(name) => family_names.push(name.to_owned()),
+				None => bail!("Skin family {} references texture {}, but the model only has {} textures", family, texture, texture_names.len()),
+			}
+		}
+		families.push(family_names);
+	}
+
+	return Ok(families);
+
+}
+
+// Returns the options of the animation events of all sequences of the model, e.g. the sound file played by a footstep event.
+// Sequences of models included via $includemodel are not read. Reading stops at the first event outside of the file, the options read until then are returned.
+pub fn sequence_event_options(bytes: &[u8]) -> Result<Vec<String>, SimpleError> {
+
+	check_header(bytes)?;
+
+	let sequence_count = read_count(bytes, SEQUENCE_COUNT_OFFSET)?;
+	let sequence_index = read_count(bytes, SEQUENCE_INDEX_OFFSET)?;
+
+	// A corrupt sequence count would otherwise loop over billions of sequences
+	match element_offset(sequence_index, sequence_count, SEQUENCE_SIZE) {
+		Ok(sequences_end) if sequences_end <= bytes.len() => {},
+		_ => bail!("Table of {} sequences at offset {} exceeds the file size of {} bytes", sequence_count, sequence_index, bytes.len()),
+	}
+
+	let mut event_options: Vec<String> = vec!();
+	'sequences: for sequence in 0..sequence_count {
+
+		let sequence_offset = element_offset(sequence_index, sequence, SEQUENCE_SIZE)?;
+		let event_count = read_count(bytes, field_offset(sequence_offset, SEQUENCE_EVENT_COUNT_OFFSET)?)?;
+		let event_index = field_offset(sequence_offset, read_count(bytes, field_offset(sequence_offset, SEQUENCE_EVENT_INDEX_OFFSET)?)?)?;
+
+		for event in 0..event_count {
+			let options = element_offset(event_index, event, EVENT_SIZE)
+				.and_then(|event_offset| field_offset(event_offset, EVENT_OPTIONS_OFFSET))
+				.and_then(|options_offset| read_fixed_string(bytes, options_offset, EVENT_OPTIONS_SIZE));
+			match options {
+				Ok(options) => event_options.push(options),
+				Err(_) => break 'sequences,
+			}
+		}
+
+	}
+
+	return Ok(event_options);
+
+}
+
+// Returns the names of the models the model includes via $includemodel, usually shared animations (e.g. "models/m_anm.mdl" of playermodels)
+pub fn include_models(bytes: &[u8]) -> Result<Vec<String>, SimpleError> {
+
+	check_header(bytes)?;
+
+	let include_model_count = read_count(bytes, INCLUDE_MODEL_COUNT_OFFSET)?;
+	let include_model_index = read_count(bytes, INCLUDE_MODEL_INDEX_OFFSET)?;
+
+	let mut include_models: Vec<String> = vec!();
+	for include_model in 0..include_model_count {
+
+		let group_offset = element_offset(include_model_index, include_model, MODEL_GROUP_SIZE)?;
+
+		// Model groups without a name are skipped
+		let name_index = read_count(bytes, field_offset(group_offset, MODEL_GROUP_NAME_OFFSET)?)?;
+		if name_index == 0 {
+			continue;
+		}
+
+		include_models.push(read_string(bytes, field_offset(group_offset, name_index)?)?);
+
+	}
+
+	return Ok(include_models);
+
+}
+
+// Returns the keyvalues text the model embeds via $keyvalues, or None if it has none
+pub fn keyvalues_text(bytes: &[u8]) -> Result<Option<String>, SimpleError> {
+
+	check_header(bytes)?;
+
+	let keyvalues_index = read_count(bytes, KEYVALUES_INDEX_OFFSET)?;
+	let keyvalues_size = read_count(bytes, KEYVALUES_SIZE_OFFSET)?;
+	if keyvalues_index == 0 || keyvalues_size == 0 {
+		return Ok(None);
+	}
+
+	let keyvalues = read_bytes(bytes, keyvalues_index, keyvalues_size)?;
+
+	return Ok(Some(String::from_utf8_lossy(keyvalues).trim_end_matches('\0').to_string()));
+
+}
+
+// Returns the collision text of a .phy (e.g. $collisiontext of the model), which follows its header and all solids, each prefixed with its size
+pub fn phy_keyvalues_text(bytes: &[u8]) -> Result<String, SimpleError> {
+
+	let header_size = read_count(bytes, PHY_HEADER_SIZE_OFFSET)?;
+	let solid_count = read_count(bytes, PHY_SOLID_COUNT_OFFSET)?;
+
+	let mut offset = header_size;
+	for _ in 0..solid_count {
+		let solid_size = read_count(bytes, offset)?;
+		offset = field_offset(field_offset(offset, 4)?, solid_size)?;
+	}
+
+	let text = match bytes.get(offset..) {
+		Some(text) => text,
+		None => bail!("Unexpected end of file at offset {}", offset),
+	};
+	let text_end = text.iter().position(|&byte| byte == 0).unwrap_or(text.len());
+
+	return Ok(String::from_utf8_lossy(&text[..text_end]).to_string());
+
+}
+
+fn check_header(bytes: &[u8]) -> Result<(), SimpleError> {
+	if bytes.get(..4) != Some(MDL_IDENT.as_slice()) {
+		bail!("Not a model, invalid header");
+	}
+	return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+
+	// Builds a model containing only the header, the textures and the skin table
+	fn write_mdl(texture_names: &[&str], families: &[&[i16]]) -> Vec<u8> {
+
+		let texture_index = 240;
+		let skin_index = texture_index + texture_names.len() * TEXTURE_SIZE;
+		let names_index = skin_index + families.iter().map(|family| family.len() * 2).sum::<usize>();
+
+		let mut bytes = vec!(0u8; names_index);
+		bytes[..4].copy_from_slice(MDL_IDENT);
+		bytes[4..8].copy_from_slice(&48i32.to_le_bytes());
+
+		let write_i32 = |bytes: &mut Vec<u8>, offset: usize, value: usize| bytes[offset..offset + 4].copy_from_slice(&(value as i32).to_le_bytes());
+		write_i32(&mut bytes, TEXTURE_COUNT_OFFSET, texture_names.len());
+		write_i32(&mut bytes, TEXTURE_INDEX_OFFSET, texture_index);
+		write_i32(&mut bytes, SKIN_REFERENCE_COUNT_OFFSET, families.first().map(|family| family.len()).unwrap_or(0));
+		write_i32(&mut bytes, SKIN_FAMILY_COUNT_OFFSET, families.len());
+		write_i32(&mut bytes, SKIN_INDEX_OFFSET, skin_index);
+
+		for (texture, name) in texture_names.iter().enumerate() {
+			let texture_offset = texture_index + texture * TEXTURE_SIZE;
+			let name_offset = bytes.len() - texture_offset;
+			write_i32(&mut bytes, texture_offset, name_offset);
+			bytes.extend_from_slice(name.as_bytes());
+			bytes.push(0);
+		}
+
+		for (position, texture) in families.iter().flat_map(|family| family.iter()).enumerate() {
+			let offset = skin_index + position * 2;
+			bytes[offset..offset + 2].copy_from_slice(&texture.to_le_bytes());
+		}
+
+		return bytes;
+
+	}
+
+	#[test]
+	fn reads_textures_of_every_skin_family() {
+
+		let mdl = write_mdl(&["crate_wood", "crate_metal", "crate_wood_dirty"], &[&[0, 1], &[2, 1]]);
+
+		assert_eq!(skin_families(&mdl).unwrap(), vec!(
+			vec!(String::from("crate_wood"), String::from("crate_metal")),
+			vec!(String::from("crate_wood_dirty"), String::from("crate_metal")),
+		));
+
+	}
+
+	#[test]
+	fn rejects_invalid_models() {
+
+		assert!(skin_families(b"IDSP").is_err());
+
+		// Skin table referencing a texture the model does not have
+		let mdl = write_mdl(&["crate_wood"], &[&[0], &[3]]);
+		assert!(skin_families(&mdl).is_err());
+
+		// Truncated texture table
+		let mdl = write_mdl(&["crate_wood", "crate_metal"], &[&[0, 1]]);
+		assert!(skin_families(&mdl[..TEXTURE_INDEX_OFFSET + 8]).is_err());
+
+	}
+
+	#[test]
+	fn reads_event_options_of_truncated_and_corrupt_models() {
+
+		let write_i32 = |bytes: &mut Vec<u8>, offset: usize, value: usize| bytes[offset..offset + 4].copy_from_slice(&(value as i32).to_le_bytes());
+
+		// One sequence with three events
+		let sequence_index = 400;
+		let event_index = sequence_index + SEQUENCE_SIZE;
+		let mut mdl = vec!(0u8; event_index + 3 * EVENT_SIZE);
+		mdl[..4].copy_from_slice(MDL_IDENT);
+		write_i32(&mut mdl, SEQUENCE_COUNT_OFFSET, 1);
+		write_i32(&mut mdl, SEQUENCE_INDEX_OFFSET, sequence_index);
+		write_i32(&mut mdl, sequence_index + SEQUENCE_EVENT_COUNT_OFFSET, 3);
+		write_i32(&mut mdl, sequence_index + SEQUENCE_EVENT_INDEX_OFFSET, SEQUENCE_SIZE);
+		for (event, options) in ["npc/footstep1.wav", "npc/footstep2.wav", "npc/footstep3.wav"].iter().enumerate() {
+			let options_offset = event_index + event * EVENT_SIZE + EVENT_OPTIONS_OFFSET;
+			mdl[options_offset..options_offset + options.len()].copy_from_slice(options.as_bytes());
+		}
+
+		assert_eq!(sequence_event_options(&mdl).unwrap().len(), 3);
+
+		// The last event is cut off, the events before it are still read
+		let truncated = &mdl[..event_index + 2 * EVENT_SIZE + EVENT_OPTIONS_OFFSET + 8];
+		assert_eq!(sequence_event_options(truncated).unwrap(), vec!(String::from("npc/footstep1.wav"), String::from("npc/footstep2.wav")));
+
+		// A sequence count far beyond the file size is rejected instead of being looped over
+		let mut corrupt = mdl.clone();
+		write_i32(&mut corrupt, SEQUENCE_COUNT_OFFSET, i32::MAX as usize);
+		assert!(sequence_event_options(&corrupt).is_err());
+
+		// A sequence table at an offset outside of the file
+		let mut corrupt = mdl.clone();
+		write_i32(&mut corrupt, SEQUENCE_INDEX_OFFSET, i32::MAX as usize);
+		assert!(sequence_event_options(&corrupt).is_err());
+
+	}
+
+	#[test]
+	fn reads_include_models_and_keyvalues() {
+
+		let keyvalues = "\"prop_data\" { \"base\" \"Wooden.Small\" }";
+
+		let mut mdl = vec!(0u8; 400);
+		mdl[..4].copy_from_slice(MDL_IDENT);
+
+		let write_i32 = |bytes: &mut Vec<u8>, offset: usize, value: usize| bytes[offset..offset + 4].copy_from_slice(&(value as i32).to_le_bytes());
+		write_i32(&mut mdl, INCLUDE_MODEL_COUNT_OFFSET, 2);
+		write_i32(&mut mdl, INCLUDE_MODEL_INDEX_OFFSET, 360);
+		// The first model group has no name
+		write_i32(&mut mdl, 360 + MODEL_GROUP_SIZE + MODEL_GROUP_NAME_OFFSET, 400 - 360 - MODEL_GROUP_SIZE);
+		mdl.extend_from_slice(b"models/m_anm.mdl\0");
+
+		let keyvalues_index = mdl.len();
+		write_i32(&mut mdl, KEYVALUES_INDEX_OFFSET, keyvalues_index);
+		write_i32(&mut mdl, KEYVALUES_SIZE_OFFSET, keyvalues.len() + 1);
+		mdl.extend_from_slice(keyvalues.as_bytes());
+		mdl.push(0);
+
+		assert_eq!(include_models(&mdl).unwrap(), vec!(String::from("models/m_anm.mdl")));
+		assert_eq!(keyvalues_text(&mdl).unwrap(), Some(String::from(keyvalues)));
+
+		// Keyvalues reaching past the end of the file
+		assert!(keyvalues_text(&mdl[..mdl.len() - 1]).is_err());
+
+	}
+
+}
@@ -0,0 +1,84 @@
+use std::sync::{atomic::{AtomicU8, Ordering}, Mutex};
+use clap::ValueEnum;
+use crate::library;
+
+// Structured output of the commands (e.g. the content summary and the missing files), selected via the global --format option.
+// Records are JSON objects with a "type" field, which are either printed as they happen (ndjson) or as a single array once the command is done (json).
+// All human-readable output is written to stderr in these formats, so stdout only contains the records.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+	Human,
+	Json,
+	Ndjson,
+}
+
+static FORMAT: AtomicU8 = AtomicU8::new(OutputFormat::Human as u8);
+
+// Records of the json format, printed by finish()
+static RECORDS: Mutex<Vec<String>> = Mutex::new(vec!());
+
+pub fn set_format(format: OutputFormat) {
+	FORMAT.store(format as u8, Ordering::Relaxed);
+	library::log::set_log_to_stderr(format != OutputFormat::Human);
+}
+
+pub fn format() -> OutputFormat {
+	return match FORMAT.load(Ordering::Relaxed) {
+		value if value == OutputFormat::Json as u8 => OutputFormat::Json,
+		value if value == OutputFormat::Ndjson as u8 => OutputFormat::Ndjson,
+		_ => OutputFormat::Human,
+	};
+}
+
+pub fn is_structured() -> bool {
+	return format() != OutputFormat::Human;
+}
+
+// Outputs a record of the provided type, fields_json are the already formatted fields of the object, e.g. "\"path\": \"x\", \"size\": 1"
+pub fn record(record_type: &str, fields_json: &str) {
+
+	let record = match fields_json.is_empty() {
+		true => format!("{{\"type\": {}}}", library::json::string(record_type)),
+		false => format!("{{\"type\": {}, {}}}", library::json::string(record_type), fields_json),
+	};
+
+	match format() {
+		OutputFormat::Human => {},
+		OutputFormat::Json => RECORDS.lock().unwrap_or_else(|err| err.into_inner()).push(record),
+		OutputFormat::Ndjson => {
+			let _guard = library::log::lock();
+			println!("{}", record);
+		},
+	}
+
+}
+
+// Prints text to stdout as it is, e.g. the JSON object of --summary-json
+pub fn print(text: &str) {
+	let _guard = library::log::lock();
+	println!("{}", text);
+}
+
+// Outputs a document the user asked for (e.g. the schema of --print-schema), which is printed as it is in the human format
+// and output as a record of the provided type in the structured formats, so stdout only contains the records
+pub fn document(record_type: &str, text: &str, fields_json: &str) {
+	match is_structured() {
+		true => record(record_type, fields_json),
+		false => print(text),
+	}
+}
+
+// Prints the records of the json format as a single array, has to be called once the command is done
+pub fn finish() {
+
+	if format() != OutputFormat::Json {
+		return;
+	}
+
+	let records = std::mem::take(&mut *RECORDS.lock().unwrap_or_else(|err| err.into_inner()));
+	match records.is_empty() {
+		true => println!("[]"),
+		false => println!("[\n\t{}\n]", records.join(",\n\t")),
+	}
+
+}
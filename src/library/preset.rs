@@ -0,0 +1,321 @@
+use std::{collections::HashMap, fs, path::PathBuf};
+use clap::{parser::ValueSource, ArgAction, ArgMatches, Command};
+use simple_error::{bail, SimpleError};
+
+// Presets are tables of a small TOML file, e.g.
+//
+// [my-server]
+// source-path = ["C:/content/css", "C:/content/custom"]
+// concurrency-limit = 4
+// flatten-case = true
+//
+// Every key is the long name of a collect-content option.
+
+// Only "vmf collect-content" has a --preset option
+pub const PRESET_SUBCOMMAND_PATH: [&str; 2] = ["vmf", "collect-content"];
+
+pub fn presets_path() -> Option<PathBuf> {
+	return dirs::config_dir().map(|config_dir| config_dir.join("gmod-developer-cli").join("presets.toml"));
+}
+
+#[derive(Debug, Clone)]
+pub enum PresetValue {
+	String(String),
+	Bool(bool),
+	Array(Vec<String>),
+}
+
+// Parses the supported subset of TOML: tables, strings, numbers, booleans and arrays of strings / numbers
+pub fn parse_presets(content: &str) -> Result<Vec<(String, Vec<(String, PresetValue)>)>, SimpleError> {
+
+	let mut presets: Vec<(String, Vec<(String, PresetValue)>)> = vec!();
+
+	for (line_index, line) in content.lines().enumerate() {
+
+		let line = strip_comment(line).trim();
+		if line.is_empty() {
+			continue;
+		}
+
+		// Table header
+		if line.starts_with('[') {
+			match line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+				Some(name) => presets.push((unquote(name.trim()), vec!())),
+				None => bail!("Invalid table header on line {}", line_index + 1),
+			}
+			continue;
+		}
+
+		let (key, value) = match line.split_once('=') {
+			Some((key, value)) => (unquote(key.trim()), value.trim()),
+			None => bail!("Expected \"key = value\" on line {}", line_index + 1),
+		};
+
+		let value = if value == "true" || value == "false" {
+			PresetValue::Bool(value == "true")
+		} else if value.starts_with('[') {
+			match value.strip_prefix('[').and_then(|value| value.strip_suffix(']')) {
+				Some(values) => PresetValue::Array(
+					values
+						.split(',')
+						.map(|value| unquote(value.trim()))
+						.filter(|value| !value.is_empty())
+						.collect()
+				),
+				None => bail!("Arrays have to be written on a single line (line {})", line_index + 1),
+			}
+		} else {
+			PresetValue::String(unquote(value))
+		};
+
+		match presets.last_mut() {
+			Some((_, values)) => values.push((key, value)),
+			None => bail!("Key \"{}\" on line {} is not inside a preset table", key, line_index + 1),
+		}
+
+	}
+
+	return Ok(presets);
+
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PresetOptionKind {
+	// Flag without a value, e.g. --flatten-case
+	Flag,
+	// Option with a single value, e.g. --concurrency-limit 4
+	Single,
+	// Option which can be used multiple times, e.g. -s
+	Multiple,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PresetOption {
+	pub kind: PresetOptionKind,
+	// Whether the option, or an option it conflicts with, is provided on the command line, in which case the preset value is not used
+	pub provided: bool,
+}
+
+// Converts the values of a preset into command line arguments for the options (keyed by their long name) of the subcommand.
+// Options which are provided on the command line take precedence, including options which can be used multiple times.
+pub fn preset_args(preset_name: &str, values: &Vec<(String, PresetValue)>, options: &HashMap<String, PresetOption>) -> Result<Vec<String>, SimpleError> {
+
+	let mut args: Vec<String> = vec!();
+
+	for (key, value) in values {
+
+		let key = key.replace("_", "-");
+		let option = match options.get(&key) {
+			Some(option) => option,
+			None => bail!("Preset \"{}\" contains \"{}\", which is not an option of \"{}\"", preset_name, key, PRESET_SUBCOMMAND_PATH.join(" ")),
+		};
+
+		if option.provided {
+			continue;
+		}
+
+		// Values are attached with "=", so values starting with "-" are not mistaken for options
+		let arg = format!("--{}", key);
+		match (option.kind, value) {
+			(PresetOptionKind::Flag, PresetValue::Bool(true)) => args.push(arg),
+			// Flags are off unless they are provided
+			(PresetOptionKind::Flag, PresetValue::Bool(false)) => {},
+			(PresetOptionKind::Flag, _) => bail!("\"{}\" of preset \"{}\" is a flag, expected true or false", key, preset_name),
+			(PresetOptionKind::Single, PresetValue::String(value)) => args.push(format!("{}={}", arg, value)),
+			(PresetOptionKind::Single, PresetValue::Bool(value)) => args.push(format!("{}={}", arg, value)),
+			(PresetOptionKind::Single, PresetValue::Array(_)) => bail!("\"{}\" of preset \"{}\" can only have a single value, not an array", key, preset_name),
+			(PresetOptionKind::Multiple, PresetValue::String(value)) => args.push(format!("{}={}", arg, value)),
+			(PresetOptionKind::Multiple, PresetValue::Bool(value)) => args.push(format!("{}={}", arg, value)),
+			(PresetOptionKind::Multiple, PresetValue::Array(values)) => args.extend(values.iter().map(|value| format!("{}={}", arg, value))),
+		}
+
+	}
+
+	return Ok(args);
+
+}
+
+// Returns the options of the subcommand keyed by their long name, and whether they (or an option they conflict with) are provided on the command line
+fn subcommand_options(subcommand: &Command, matches: &ArgMatches) -> HashMap<String, PresetOption> {
+
+	let is_provided = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+
+	let mut options: HashMap<String, PresetOption> = HashMap::new();
+
+	for arg in subcommand.get_arguments() {
+
+		// Global options (e.g. --format) are not options of the subcommand, and presets can not select other presets
+		let long = match arg.get_long() {
+			Some(long) if !arg.is_global_set() && long != "preset" => long,
+			_ => continue,
+		};
+
+		let kind = match arg.get_action() {
+			ArgAction::SetTrue => PresetOptionKind::Flag,
+			ArgAction::Set => PresetOptionKind::Single,
+			ArgAction::Append => PresetOptionKind::Multiple,
+			_ => continue,
+		};
+
+		let provided = is_provided(arg.get_id().as_str()) || subcommand.get_arg_conflicts_with(arg).iter().any(|conflict| is_provided(conflict.get_id().as_str()));
+
+		options.insert(long.to_owned(), PresetOption { kind, provided });
+
+	}
+
+	return options;
+
+}
+
+// Appends the arguments of the preset selected via "--preset <name>" of "vmf collect-content" to the arguments.
+// The subcommand and the options provided on the command line are found by parsing the arguments with the command.
+pub fn expand_preset_args(command: Command, args: Vec<String>) -> Result<Vec<String>, SimpleError> {
+
+	// Required arguments may be supplied by the preset, so errors are ignored. Help and version requests are handled by the actual parsing.
+	let matches = match command.clone().ignore_errors(true).try_get_matches_from(&args) {
+		Ok(matches) => matches,
+		Err(_) => return Ok(args),
+	};
+
+	// Building the command adds the global arguments to the subcommands, which options can conflict with
+	let mut subcommand = command;
+	subcommand.build();
+
+	let mut subcommand_matches = &matches;
+	for subcommand_name in PRESET_SUBCOMMAND_PATH {
+		subcommand = match subcommand.find_subcommand(subcommand_name) {
+			Some(found_subcommand) => found_subcommand.to_owned(),
+			None => return Ok(args),
+		};
+		subcommand_matches = match subcommand_matches.subcommand() {
+			Some((name, found_matches)) if name == subcommand_name => found_matches,
+			_ => return Ok(args),
+		};
+	}
+
+	let preset_name = match subcommand_matches.try_get_raw("preset") {
+		Ok(Some(mut values)) => match values.next() {
+			Some(name) => name.to_string_lossy().to_string(),
+			None => return Ok(args),
+		},
+		_ => return Ok(args),
+	};
+
+	let presets_path = match presets_path() {
+		Some(path) => path,
+		None => bail!("Failed to locate the config directory"),
+	};
+
+	let presets_content = match fs::read_to_string(&presets_path) {
+		Ok(content) => content,
+		Err(err) => bail!("Failed to read presets file \"{}\": {}", presets_path.display(), err.to_string()),
+	};
+
+	let presets = match parse_presets(&presets_content) {
+		Ok(presets) => presets,
+		Err(err) => bail!("Failed to parse presets file \"{}\": {}", presets_path.display(), err.to_string()),
+	};
+
+	let preset_values = match presets.iter().find(|(name, _)| name == &preset_name) {
+		Some((_, values)) => values,
+		None => bail!("Preset \"{}\" does not exist in \"{}\"", preset_name, presets_path.display()),
+	};
+
+	let preset_args = preset_args(&preset_name, preset_values, &subcommand_options(&subcommand, subcommand_matches))?;
+
+	// Arguments after "--" are positional, so the preset arguments are inserted before it
+	let insert_index = args.iter().position(|arg| arg == "--").unwrap_or(args.len());
+	let mut expanded_args = args[..insert_index].to_vec();
+	expanded_args.extend(preset_args);
+	expanded_args.extend_from_slice(&args[insert_index..]);
+
+	return Ok(expanded_args);
+
+}
+
+fn strip_comment(line: &str) -> &str {
+
+	// "#" inside of strings is not a comment
+	let mut quote: Option<char> = None;
+	for (index, char) in line.char_indices() {
+		match (char, quote) {
+			('"', None) | ('\'', None) => quote = Some(char),
+			(char, Some(quote_char)) if char == quote_char => quote = None,
+			('#', None) => return &line[..index],
+			_ => {},
+		}
+	}
+
+	return line;
+
+}
+
+fn unquote(value: &str) -> String {
+	for quote in ['"', '\''] {
+		if value.len() >= 2 && value.starts_with(quote) && value.ends_with(quote) {
+			return value[1..value.len() - 1].to_string();
+		}
+	}
+	return value.to_string();
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+
+	fn options(provided: &[&str]) -> HashMap<String, PresetOption> {
+		let mut options: HashMap<String, PresetOption> = HashMap::new();
+		for (long, kind) in [("source-path", PresetOptionKind::Multiple), ("concurrency-limit", PresetOptionKind::Single), ("flatten-case", PresetOptionKind::Flag), ("clean", PresetOptionKind::Flag)] {
+			options.insert(long.to_owned(), PresetOption { kind, provided: provided.contains(&long) });
+		}
+		return options;
+	}
+
+	fn preset(content: &str) -> Vec<(String, PresetValue)> {
+		return parse_presets(content).unwrap().remove(0).1;
+	}
+
+	#[test]
+	fn parse_presets_reads_tables_and_values() {
+
+		let presets = parse_presets("# Comment\n[server]\nsource-path = [\"C:/a\", 'C:/b#1'] # Trailing comment\nconcurrency-limit = 4\nflatten-case = true\n\n[\"local\"]\nclean = false\n").unwrap();
+
+		assert_eq!(presets.len(), 2);
+		assert_eq!(presets[0].0, "server");
+		assert_eq!(presets[1].0, "local");
+		assert!(matches!(&presets[0].1[0], (key, PresetValue::Array(values)) if key == "source-path" && values == &vec!["C:/a", "C:/b#1"]));
+		assert!(matches!(&presets[0].1[1], (key, PresetValue::String(value)) if key == "concurrency-limit" && value == "4"));
+		assert!(matches!(&presets[0].1[2], (key, PresetValue::Bool(true)) if key == "flatten-case"));
+		assert!(matches!(&presets[1].1[0], (key, PresetValue::Bool(false)) if key == "clean"));
+
+	}
+
+	#[test]
+	fn parse_presets_rejects_invalid_lines() {
+		assert!(parse_presets("flatten-case = true").is_err());
+		assert!(parse_presets("[server]\nflatten-case").is_err());
+		assert!(parse_presets("[server\n").is_err());
+		assert!(parse_presets("[server]\nsource-path = [\"C:/a\",\n\"C:/b\"]").is_err());
+	}
+
+	#[test]
+	fn preset_args_are_used_for_options_which_are_not_provided() {
+		let values = preset("[server]\nsource-path = [\"C:/a\", \"-C:/b\"]\nconcurrency-limit = 4\nflatten-case = true\nclean = false");
+		assert_eq!(preset_args("server", &values, &options(&[])).unwrap(), vec!["--source-path=C:/a", "--source-path=-C:/b", "--concurrency-limit=4", "--flatten-case"]);
+	}
+
+	#[test]
+	fn provided_options_replace_preset_values() {
+		let values = preset("[server]\nsource-path = [\"C:/a\", \"C:/b\"]\nconcurrency-limit = 4\nflatten-case = true");
+		assert_eq!(preset_args("server", &values, &options(&["source-path", "concurrency-limit"])).unwrap(), vec!["--flatten-case"]);
+	}
+
+	#[test]
+	fn preset_args_reject_unknown_options_and_wrong_values() {
+		assert!(preset_args("server", &preset("[server]\noutput = \"C:/out\""), &options(&[])).is_err());
+		assert!(preset_args("server", &preset("[server]\nflatten-case = \"yes\""), &options(&[])).is_err());
+		assert!(preset_args("server", &preset("[server]\nconcurrency-limit = [\"1\", \"2\"]"), &options(&[])).is_err());
+	}
+
+}
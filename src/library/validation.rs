@@ -31,6 +31,39 @@ pub fn validate_path_is_directory(path: &str) -> Result<PathBuf, String> {
 
 }
 
+// Output paths can contain placeholders which are replaced with parts of the input path, e.g. "./content/{name}".
+// They do not exist yet, so they are only validated once they are expanded.
+pub fn validate_output_path_template(path: &str) -> Result<PathBuf, String> {
+
+	if has_output_path_placeholders(Path::new(path)) {
+		return Ok(PathBuf::from(path));
+	}
+
+	return validate_path_is_directory(path);
+
+}
+
+pub fn has_output_path_placeholders(path: &Path) -> bool {
+	let path = path.to_string_lossy();
+	return ["{name}", "{stem}", "{ext}"].iter().any(|placeholder| path.contains(placeholder));
+}
+
+// Replaces {name} / {stem} with the file name of the input without its extension and {ext} with the extension of the input
+pub fn expand_output_path(output_path: &Path, input: &Path) -> PathBuf {
+
+	let stem = input.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+	let extension = input.extension().map(|extension| extension.to_string_lossy().to_string()).unwrap_or_default();
+
+	return PathBuf::from(
+		output_path
+			.to_string_lossy()
+			.replace("{name}", &stem)
+			.replace("{stem}", &stem)
+			.replace("{ext}", &extension)
+	);
+
+}
+
 pub fn validate_input_file_exists(path_to_file: &str, expected_extension: &str) -> Result<PathBuf, String> {
 
 	let path = Path::new(path_to_file);
@@ -50,3 +83,83 @@ pub fn validate_input_file_exists(path_to_file: &str, expected_extension: &str)
 	return Ok(path.into());
 
 }
+
+// Creates and removes a temporary file, since permissions can not be checked reliably on all platforms
+pub fn validate_directory_is_writable(path: &Path) -> Result<(), String> {
+
+	let test_file_path = path.join(format!(".gcli_write_test_{}", std::process::id()));
+
+	if let Err(err) = std::fs::write(&test_file_path, b"") {
+		return Err(format!("Directory is not writable: {}", err));
+	}
+
+	if let Err(err) = std::fs::remove_file(&test_file_path) {
+		return Err(format!("Failed to remove test file \"{}\": {}", test_file_path.display(), err));
+	}
+
+	return Ok(());
+
+}
+
+pub fn validate_directory_is_readable(path: &Path) -> Result<(), String> {
+
+	if let Err(err) = std::fs::read_dir(path) {
+		return Err(format!("Directory is not readable: {}", err));
+	}
+
+	return Ok(());
+
+}
+
+// Parses a size like "500MB", "1.5 GB" or "1048576" (bytes) into bytes, with 1 KB = 1024 bytes
+pub fn validate_size(input: &str) -> Result<u64, String> {
+
+	let input = input.trim().to_uppercase();
+	let number_end = input.find(|char: char| !char.is_ascii_digit() && char != '.').unwrap_or(input.len());
+
+	let number: f64 = match input[..number_end].parse() {
+		Ok(number) => number,
+		Err(_) => return Err("Expected a size like 500MB or 2GB".to_owned()),
+	};
+
+	let multiplier: u64 = match input[number_end..].trim() {
+		"" | "B" => 1,
+		"K" | "KB" => 1024,
+		"M" | "MB" => 1024 * 1024,
+		"G" | "GB" => 1024 * 1024 * 1024,
+		unit => return Err(format!("Unknown size unit \"{}\", expected B, KB, MB or GB", unit)),
+	};
+
+	let size = (number * multiplier as f64) as u64;
+	if size == 0 {
+		return Err("The size has to be greater than 0".to_owned());
+	}
+
+	return Ok(size);
+
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+
+	#[test]
+	fn output_path_placeholders_expand_to_parts_of_the_input() {
+
+		let input = Path::new("maps/rp_harbor_v2.vmf");
+
+		assert_eq!(expand_output_path(Path::new("./content/{name}"), input), PathBuf::from("./content/rp_harbor_v2"));
+		assert_eq!(expand_output_path(Path::new("out/{stem}_{ext}/{name}"), input), PathBuf::from("out/rp_harbor_v2_vmf/rp_harbor_v2"));
+		assert_eq!(expand_output_path(Path::new("models/{name}.{ext}"), Path::new("props/crate")), PathBuf::from("models/crate."));
+		assert_eq!(expand_output_path(Path::new("./content"), input), PathBuf::from("./content"));
+
+		// Templates do not exist yet and are only validated after expanding them
+		assert!(has_output_path_placeholders(Path::new("./content/{ext}")));
+		assert!(!has_output_path_placeholders(Path::new("./content/{map}")));
+		assert_eq!(validate_output_path_template("./does_not_exist/{name}"), Ok(PathBuf::from("./does_not_exist/{name}")));
+		assert!(validate_output_path_template("./does_not_exist").is_err());
+
+	}
+
+}
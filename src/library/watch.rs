@@ -0,0 +1,125 @@
+use std::{fs, path::{Path, PathBuf}, sync::mpsc::channel, time::Duration};
+use notify_debouncer_mini::{new_debouncer, notify::RecursiveMode, DebounceEventResult};
+use simple_error::{bail, SimpleError};
+use crate::library::lock::LOCK_FILE_NAME;
+use crate::library::log::warn;
+
+// Saving a map or exporting a model writes several files in a row, which should only cause a single run
+pub const WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
+
+// Watches the provided files and directories (recursively) and calls on_change with the changed paths after each batch of changes.
+// Changes inside of the ignored paths, e.g. the output path, are not reported. Blocks until the process is interrupted.
+pub fn watch(paths: &Vec<PathBuf>, ignored_paths: &Vec<PathBuf>, mut on_change: impl FnMut(&Vec<PathBuf>)) -> Result<(), SimpleError> {
+
+	let (sender, receiver) = channel::<DebounceEventResult>();
+
+	// Events of the same path within WATCH_DEBOUNCE are merged and delivered together with the other changed paths
+	let mut debouncer = match new_debouncer(WATCH_DEBOUNCE, sender) {
+		Ok(debouncer) => debouncer,
+		Err(err) => bail!("Failed to create file watcher: {}", err.to_string()),
+	};
+
+	let mut watched_directories: Vec<PathBuf> = vec!();
+	let mut watched_files: Vec<PathBuf> = vec!();
+
+	for path in paths {
+
+		let path = fs::canonicalize(path).unwrap_or(path.to_owned());
+
+		// Files are watched through their directory, since some editors replace a file instead of writing into it
+		let (watch_path, recursive_mode) = match path.is_dir() {
+			true => {
+				watched_directories.push(path.to_owned());
+				(path, RecursiveMode::Recursive)
+			},
+			false => match path.parent() {
+				Some(parent) => {
+					watched_files.push(path.to_owned());
+					(parent.to_owned(), RecursiveMode::NonRecursive)
+				},
+				None => continue,
+			},
+		};
+
+		if let Err(err) = debouncer.watcher().watch(&watch_path, recursive_mode) {
+			bail!("Failed to watch \"{}\": {}", watch_path.display(), err.to_string());
+		}
+
+	}
+
+	let ignored_paths: Vec<PathBuf> = ignored_paths
+		.iter()
+		.map(|path| fs::canonicalize(path).unwrap_or(path.to_owned()))
+		.collect();
+
+	loop {
+
+		let events = match receiver.recv() {
+			Ok(Ok(events)) => events,
+			Ok(Err(err)) => {
+				warn!("File watcher error: {}", err.to_string());
+				continue;
+			},
+			Err(_) => bail!("File watcher stopped unexpectedly"),
+		};
+
+		let mut changed_paths: Vec<PathBuf> = events
+			.into_iter()
+			.map(|event| event.path)
+			.filter(|path| is_relevant(path, &watched_files, &watched_directories, &ignored_paths))
+			.collect();
+
+		if changed_paths.is_empty() {
+			continue;
+		}
+
+		changed_paths.sort();
+		changed_paths.dedup();
+
+		on_change(&changed_paths);
+
+	}
+
+}
+
+// Whether a changed path is one of the watched files or inside of a watched directory, and not ignored
+fn is_relevant(path: &Path, watched_files: &Vec<PathBuf>, watched_directories: &Vec<PathBuf>, ignored_paths: &Vec<PathBuf>) -> bool {
+	if ignored_paths.iter().any(|ignored_path| path.starts_with(ignored_path)) {
+		return false;
+	}
+	if path.file_name().is_some_and(|file_name| file_name == LOCK_FILE_NAME) {
+		return false;
+	}
+	return watched_files.iter().any(|watched_file| path == watched_file) || watched_directories.iter().any(|watched_directory| path.starts_with(watched_directory));
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+
+	#[test]
+	fn changes_of_ignored_paths_and_lock_file_are_not_relevant() {
+
+		let watched_files = vec!(PathBuf::from("/maps/harbor.vmf"));
+		let watched_directories = vec!(PathBuf::from("/content"));
+		let ignored_paths = vec!(PathBuf::from("/content/output"));
+
+		let is_relevant = |path: &str| is_relevant(Path::new(path), &watched_files, &watched_directories, &ignored_paths);
+
+		assert!(is_relevant("/maps/harbor.vmf"));
+		assert!(is_relevant("/content/materials/brick/wall.vmt"));
+		// Other files next to the watched vmf
+		assert!(!is_relevant("/maps/harbor.vmx"));
+		assert!(!is_relevant("/content/output/materials/brick/wall.vmt"));
+		assert!(!is_relevant(&format!("/content/{}", LOCK_FILE_NAME)));
+
+	}
+
+	#[test]
+	fn watching_a_missing_path_fails() {
+		let result = watch(&vec!(PathBuf::from("/gcli/does/not/exist/map.vmf")), &vec!(), |_| panic!("No changes expected"));
+		assert!(result.is_err());
+	}
+
+}
@@ -1,19 +1,34 @@
-use clap::{Parser, Subcommand};
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
 
 // cli
 mod cli {
 	pub mod addon;
 	pub mod entity;
+	pub mod gma;
+	pub mod model;
 	pub mod vmf;
 }
 use cli::addon;
 use cli::entity;
+use cli::gma;
+use cli::model;
 use cli::vmf;
 
 // library
 mod library {
 	pub mod validation;
 	pub mod inquire;
+	pub mod gma;
+	pub mod mdl;
+	pub mod log;
+	pub mod keyvalues;
+	pub mod preset;
+	pub mod json;
+	pub mod fgd;
+	pub mod lock;
+	pub mod watch;
+	pub mod output;
+	pub mod collector;
 }
 
 // templates
@@ -26,7 +41,11 @@ mod templates {
 #[command(author, version, about, long_about = None)]
 struct Cli {
 	#[command(subcommand)]
-	command: Commands
+	command: Commands,
+	#[arg(short = 'y', long, global = true, help = "Answer all confirmations with yes instead of prompting, including the ones which default to no: existing files and entity directories are overwritten and optional steps (e.g. creating the spawn menu icon directory) are done.")]
+	assume_yes: bool,
+	#[arg(long, global = true, value_enum, default_value_t = library::output::OutputFormat::Human, help = "Format of the structured output (e.g. the content summary, missing files and stats): `human` for readable text, `json` for a single JSON array of records once the command is done, `ndjson` for one JSON record per line as they happen. In the JSON formats, all other output is written to stderr.")]
+	format: library::output::OutputFormat,
 }
 
 #[derive(Subcommand)]
@@ -42,12 +61,37 @@ enum Commands {
 	VMF {
 		#[command(subcommand)]
 		action: vmf::Actions,
+	},
+	Model {
+		#[command(subcommand)]
+		action: model::Actions,
+	},
+	GMA {
+		#[command(subcommand)]
+		action: gma::Actions,
 	}
 }
 
 fn main() {
 
-	let cli = Cli::parse();
+	// Presets are expanded into regular arguments before parsing
+	let args = match library::preset::expand_preset_args(Cli::command(), std::env::args().collect()) {
+		Ok(args) => args,
+		Err(err) => {
+			library::log::error!("Failed to load preset: {}", err.to_string());
+			return;
+		}
+	};
+
+	// The matches are kept for --print-config
+	let matches = Cli::command().get_matches_from(args);
+	let cli = match Cli::from_arg_matches(&matches) {
+		Ok(cli) => cli,
+		Err(err) => err.format(&mut Cli::command()).exit(),
+	};
+
+	library::inquire::set_assume_yes(cli.assume_yes);
+	library::output::set_format(cli.format);
 
 	match cli.command {
 
@@ -60,6 +104,15 @@ fn main() {
 					addon::init(target_directory);
 				}
 
+				// addon check-content
+				addon::Actions::CheckContent { game_dir, skip_game_check } => {
+					// Exits with a nonzero exit code if the addon is not self-contained, e.g. to fail a CI job
+					if addon::check_content(game_dir, skip_game_check) != Some(true) {
+						library::output::finish();
+						std::process::exit(1);
+					}
+				}
+
 			}
 		}
 
@@ -72,6 +125,16 @@ fn main() {
 					entity::create(directory_name);
 				}
 
+				// entity lua-deps <name>
+				entity::Actions::LuaDeps { directory_name } => {
+					entity::lua_deps(directory_name);
+				}
+
+				// entity rename <old_name> <new_name>
+				entity::Actions::Rename { old_directory_name, new_directory_name, print_name } => {
+					entity::rename(old_directory_name, new_directory_name, print_name);
+				}
+
 			}
 		}
 
@@ -79,9 +142,85 @@ fn main() {
 		Commands::VMF { action } => {
 			match action {
 
-				// vmf collect-content <vmf-path>
-				vmf::Actions::CollectContent { vmf_path, source_path, output_path } => {
-					vmf::content_collector::collect_content(&vmf_path, source_path, &output_path);
+				// vmf collect-content <vmf-path> / vmf collect-content --input-list <list-path>
+				vmf::Actions::CollectContent { vmf_path, input_list, shared_output, source_path, output_path, options } => {
+					if options.print_schema {
+						let schema = vmf::content_collector::copy::manifest_json_schema();
+						library::output::document("schema", &schema, &format!("\"schema\": {}", schema));
+						library::output::finish();
+						return;
+					}
+					if options.print_config {
+						print_config(&matches, &["vmf", "collect-content"]);
+					}
+					match (input_list, vmf_path) {
+						(Some(input_list), _) => {
+							let complete = vmf::content_collector::collect_content_list(&input_list, source_path, &output_path, shared_output, &options);
+							// Like for a single map, a failed map exits with a nonzero exit code, as well as --only-missing / --summary-only-missing if anything is missing
+							if complete.is_none() || ((options.only_missing || options.summary_only_missing) && complete == Some(false)) {
+								library::output::finish();
+								std::process::exit(1);
+							}
+						},
+						(None, Some(vmf_path)) if options.watch => vmf::content_collector::watch_content(&vmf_path, source_path, &output_path, &options),
+						(None, Some(vmf_path)) => {
+							let summary_categories = vmf::content_collector::collect_content(&vmf_path, source_path, &output_path, &options);
+							// A failed collection (e.g. with --strict) exits with a nonzero exit code, as well as --only-missing / --summary-only-missing if anything is missing
+							let incomplete = summary_categories.as_ref().is_some_and(|summary_categories| summary_categories.iter().any(|category| category.missing > 0));
+							if summary_categories.is_none() || ((options.only_missing || options.summary_only_missing) && incomplete) {
+								library::output::finish();
+								std::process::exit(1);
+							}
+						},
+						// Prevented by clap (vmf_path is required unless input_list is present)
+						(None, None) => {},
+					}
+				}
+
+				// vmf stats <vmf-path>
+				vmf::Actions::Stats { vmf_path, json } => {
+					vmf::stats::output_vmf_stats(&vmf_path, json);
+				}
+
+			}
+		}
+
+		// model <action>
+		Commands::Model { action } => {
+			match action {
+
+				// model collect-content <model-path> / model collect-content --input-list <list-path>
+				model::Actions::CollectContent { model_path, input_list, shared_output, source_path, output_path, options } => {
+					if options.print_config {
+						print_config(&matches, &["model", "collect-content"]);
+					}
+					// A failed collection (e.g. with --strict) exits with a nonzero exit code
+					let failed = match (input_list, model_path) {
+						(Some(input_list), _) => model::content_collector::collect_content_list(&input_list, source_path, &output_path, shared_output, &options).is_none(),
+						(None, Some(model_path)) if options.watch => {
+							model::content_collector::watch_content(&model_path, source_path, &output_path, &options);
+							false
+						},
+						(None, Some(model_path)) => model::content_collector::collect_content(&model_path, source_path, &output_path, &options).is_none(),
+						// Prevented by clap (model_path is required unless input_list is present)
+						(None, None) => false,
+					};
+					if failed {
+						library::output::finish();
+						std::process::exit(1);
+					}
+				}
+
+			}
+		}
+
+		// gma <action>
+		Commands::GMA { action } => {
+			match action {
+
+				// gma list <gma-path>
+				gma::Actions::List { gma_path } => {
+					gma::list(&gma_path);
 				}
 
 			}
@@ -89,4 +228,32 @@ fn main() {
 
 	}
 
+	library::output::finish();
+
+}
+
+// Prints the resolved arguments of a subcommand, i.e. after the preset is applied and including the default values
+fn print_config(matches: &ArgMatches, subcommand_path: &[&str]) {
+
+	// Building the command adds the global arguments to the subcommands
+	let mut root_command = Cli::command();
+	root_command.build();
+
+	let mut command = &root_command;
+	let mut matches = matches;
+	for subcommand_name in subcommand_path {
+		command = match command.find_subcommand(subcommand_name) {
+			Some(subcommand) => subcommand,
+			None => return,
+		};
+		matches = match matches.subcommand() {
+			Some((name, subcommand_matches)) if name == *subcommand_name => subcommand_matches,
+			_ => return,
+		};
+	}
+
+	let fields = library::json::argument_fields(command, matches);
+
+	library::output::document("config", &format!("{{\n\t{}\n}}", fields.join(",\n\t")), &format!("\"config\": {{{}}}", fields.join(", ")));
+
 }